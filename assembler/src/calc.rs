@@ -0,0 +1,174 @@
+//! Evaluates the expressions of `:calc` and `:byte { ... }`.
+//!
+//! Like Octo, there is no operator precedence: binary operators are
+//! evaluated right to left, so `2 * 3 + 4` is 14. Parentheses group. The
+//! arithmetic is done on integers.
+//!
+
+use super::error::AssembleError;
+use super::token::{parse_number, Token};
+use std::convert::TryFrom;
+
+/// Evaluates the expression.
+///
+///### Arguments
+///
+///- **tokens** : The tokens between the braces.
+///- **end**    : The closing brace, for errors at the end of the expression.
+///- **lookup** : Returns the value of a name.
+///
+pub(crate) fn evaluate(
+    tokens: &[Token],
+    end: &Token,
+    lookup: &dyn Fn(&str) -> Option<i64>,
+) -> Result<i64, AssembleError> {
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        end,
+        lookup,
+    };
+
+    let value = parser.expression()?;
+
+    match parser.peek() {
+        Some(token) => Err(AssembleError::at(
+            token,
+            format!("unexpected '{}' in expression", token.text),
+        )),
+        None => Ok(value),
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    end: &'a Token,
+    lookup: &'a dyn Fn(&str) -> Option<i64>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<&'a Token, AssembleError> {
+        let token = self
+            .peek()
+            .ok_or_else(|| AssembleError::at(self.end, "incomplete expression"))?;
+        self.position += 1;
+
+        Ok(token)
+    }
+
+    /// expression := term [binary expression]
+    ///
+    fn expression(&mut self) -> Result<i64, AssembleError> {
+        let lhs = self.term()?;
+
+        let op = match self.peek() {
+            Some(token) if is_binary(&token.text) => token,
+            _ => return Ok(lhs),
+        };
+
+        self.position += 1;
+        let rhs = self.expression()?;
+
+        binary(op, lhs, rhs)
+    }
+
+    /// term := number | name | ( expression ) | unary term
+    ///
+    fn term(&mut self) -> Result<i64, AssembleError> {
+        let token = self.next()?;
+
+        match token.text.as_str() {
+            "(" => {
+                let value = self.expression()?;
+                let close = self.next()?;
+
+                if close.text != ")" {
+                    return Err(AssembleError::at(close, "expected ')'"));
+                }
+
+                Ok(value)
+            }
+            "-" => Ok(self.term()?.wrapping_neg()),
+            "~" => Ok(!self.term()?),
+            "!" => Ok((self.term()? == 0) as i64),
+            text => parse_number(text)
+                .or_else(|| (self.lookup)(text))
+                .ok_or_else(|| AssembleError::at(token, format!("undefined name '{}'", text))),
+        }
+    }
+}
+
+fn is_binary(op: &str) -> bool {
+    matches!(
+        op,
+        "+" | "-" | "*" | "/" | "%" | "&" | "|" | "^" | "<<" | ">>" | "min" | "max"
+    )
+}
+
+fn binary(op: &Token, lhs: i64, rhs: i64) -> Result<i64, AssembleError> {
+    let shift = |value: Option<i64>| {
+        value.ok_or_else(|| AssembleError::at(op, format!("can not shift by {}", rhs)))
+    };
+
+    match op.text.as_str() {
+        "+" => Ok(lhs.wrapping_add(rhs)),
+        "-" => Ok(lhs.wrapping_sub(rhs)),
+        "*" => Ok(lhs.wrapping_mul(rhs)),
+        "/" | "%" if rhs == 0 => Err(AssembleError::at(op, "division by zero")),
+        "/" => Ok(lhs.wrapping_div(rhs)),
+        "%" => Ok(lhs.wrapping_rem(rhs)),
+        "&" => Ok(lhs & rhs),
+        "|" => Ok(lhs | rhs),
+        "^" => Ok(lhs ^ rhs),
+        "<<" => shift(u32::try_from(rhs).ok().and_then(|n| lhs.checked_shl(n))),
+        ">>" => shift(u32::try_from(rhs).ok().and_then(|n| lhs.checked_shr(n))),
+        "min" => Ok(lhs.min(rhs)),
+        _ => Ok(lhs.max(rhs)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::token::tokenize;
+    use super::*;
+
+    fn calc(source: &str) -> Result<i64, AssembleError> {
+        let tokens = tokenize(source);
+        let end = tokens.last().unwrap().clone();
+
+        evaluate(&tokens, &end, &|name| {
+            if name == "width" {
+                Some(64)
+            } else {
+                None
+            }
+        })
+    }
+
+    #[test]
+    fn evaluates_right_to_left() {
+        assert_eq!(calc("2 * 3 + 4"), Ok(14));
+        assert_eq!(calc("( 2 * 3 ) + 4"), Ok(10));
+        assert_eq!(calc("width / 2 - 4"), Ok(-32));
+        assert_eq!(calc("- 1 & 0xFF"), Ok(0xFF));
+        assert_eq!(calc("1 << 4 max 3"), Ok(16));
+    }
+
+    #[test]
+    fn reports_where_the_expression_is_wrong() {
+        let error = calc("1 +\n height").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.message, "undefined name 'height'");
+
+        assert_eq!(calc("4 / 0").unwrap_err().message, "division by zero");
+        assert_eq!(
+            calc("1 2").unwrap_err().message,
+            "unexpected '2' in expression"
+        );
+    }
+}
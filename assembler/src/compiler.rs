@@ -0,0 +1,1146 @@
+use super::calc;
+use super::error::AssembleError;
+use super::token::{is_name, parse_number, tokenize, Token};
+use data::{Address, Byte, Nibble};
+use instruction::{Instruction, START};
+use model::Register;
+use std::collections::{HashMap, VecDeque};
+
+/// The most macro expansions in a program, to stop macros calling
+/// themselves forever.
+///
+const MAX_EXPANSIONS: usize = 10_000;
+
+/// The words that can not name a label, constant, alias or macro.
+///
+const KEYWORDS: [&str; 36] = [
+    "return",
+    "clear",
+    "bcd",
+    "save",
+    "load",
+    "saveflags",
+    "loadflags",
+    "sprite",
+    "jump",
+    "jump0",
+    "native",
+    "if",
+    "then",
+    "begin",
+    "else",
+    "end",
+    "loop",
+    "again",
+    "while",
+    "key",
+    "random",
+    "delay",
+    "buzzer",
+    "pitch",
+    "hex",
+    "bighex",
+    "long",
+    "i",
+    "exit",
+    "lores",
+    "hires",
+    "plane",
+    "audio",
+    "scroll-down",
+    "scroll-up",
+    "scroll-left",
+];
+
+/// A user defined macro.
+///
+struct Macro {
+    params: Vec<String>,
+    body: Vec<Token>,
+}
+
+/// How an address waiting for a label is stored.
+///
+#[derive(Clone, Copy, PartialEq)]
+enum Width {
+    /// The low 12 bits of the instruction at the address.
+    Twelve,
+    /// The whole word following `F000`.
+    Sixteen,
+}
+
+/// A reference to a label that was not defined yet.
+///
+struct Fixup {
+    addr: u16,
+    width: Width,
+    token: Token,
+}
+
+/// An open control flow block.
+///
+enum Flow {
+    /// The jump to patch with the start of the else branch or the end.
+    If { jump: u16, token: Token },
+    /// The jump at the end of the then branch to patch with the end.
+    Else { jump: u16, token: Token },
+    /// The start of the loop and the jumps out of it.
+    Loop {
+        start: u16,
+        exits: Vec<u16>,
+        token: Token,
+    },
+}
+
+/// The right hand side of a comparison.
+///
+#[derive(Clone, Copy)]
+enum Operand {
+    Register(Register),
+    Byte(Byte),
+}
+
+pub(crate) struct Compiler {
+    tokens: VecDeque<Token>,
+    last: Token,
+
+    rom: Vec<u8>,
+    here: u16,
+
+    labels: HashMap<String, u16>,
+    constants: HashMap<String, i64>,
+    aliases: HashMap<String, Register>,
+    macros: HashMap<String, Macro>,
+
+    fixups: Vec<Fixup>,
+    flow: Vec<Flow>,
+    expansions: usize,
+}
+
+impl Compiler {
+    pub(crate) fn new(source: &str) -> Self {
+        let tokens: VecDeque<Token> = tokenize(source).into();
+        let last = tokens.back().cloned().unwrap_or(Token {
+            text: String::new(),
+            line: 1,
+            column: 1,
+        });
+
+        Compiler {
+            tokens,
+            last,
+            rom: Vec::new(),
+            here: START,
+            labels: HashMap::new(),
+            constants: HashMap::new(),
+            aliases: HashMap::new(),
+            macros: HashMap::new(),
+            fixups: Vec::new(),
+            flow: Vec::new(),
+            expansions: 0,
+        }
+    }
+
+    /// Compiles the whole program.
+    ///
+    pub(crate) fn compile(mut self) -> Result<Vec<u8>, AssembleError> {
+        let starts_with_main = self.tokens.front().is_some_and(|t| t.text == ":")
+            && self.tokens.get(1).is_some_and(|t| t.text == "main");
+
+        // Execution starts at main, which is jumped to unless it comes first.
+        if !starts_with_main {
+            let main = Token {
+                text: "main".to_string(),
+                line: 1,
+                column: 1,
+            };
+
+            self.reference(main, Width::Twelve);
+            self.emit(Instruction::Jump(Address::new(0)))?;
+        }
+
+        while !self.tokens.is_empty() {
+            self.statement()?;
+        }
+
+        if let Some(flow) = self.flow.pop() {
+            return Err(match flow {
+                Flow::If { token, .. } => AssembleError::at(&token, "'begin' without 'end'"),
+                Flow::Else { token, .. } => AssembleError::at(&token, "'else' without 'end'"),
+                Flow::Loop { token, .. } => AssembleError::at(&token, "'loop' without 'again'"),
+            });
+        }
+
+        for fixup in std::mem::take(&mut self.fixups) {
+            let addr = match self.labels.get(&fixup.token.text) {
+                Some(addr) => *addr,
+                None if fixup.token.text == "main" && fixup.addr == START => {
+                    return Err(AssembleError::at(
+                        &fixup.token,
+                        "the program has no 'main' label",
+                    ))
+                }
+                None => {
+                    return Err(AssembleError::at(
+                        &fixup.token,
+                        format!("undefined name '{}'", fixup.token.text),
+                    ))
+                }
+            };
+
+            self.patch(&fixup.token, fixup.addr, fixup.width, addr)?;
+        }
+
+        Ok(self.rom)
+    }
+
+    /// Writes the address into the instruction at `at`.
+    ///
+    fn patch(
+        &mut self,
+        token: &Token,
+        at: u16,
+        width: Width,
+        addr: u16,
+    ) -> Result<(), AssembleError> {
+        let offset = (at - START) as usize;
+
+        match width {
+            Width::Twelve => {
+                if addr > 0xFFF {
+                    return Err(AssembleError::at(
+                        token,
+                        format!("the address 0x{:X} does not fit in 12 bits", addr),
+                    ));
+                }
+
+                self.rom[offset] = (self.rom[offset] & 0xF0) | (addr >> 8) as u8;
+                self.rom[offset + 1] = addr as u8;
+            }
+            Width::Sixteen => {
+                let [high, low] = addr.to_be_bytes();
+                self.rom[offset + 2] = high;
+                self.rom[offset + 3] = low;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes bytes at the current address.
+    ///
+    fn write(&mut self, bytes: &[u8]) -> Result<(), AssembleError> {
+        let offset = (self.here - START) as usize;
+        let end = offset + bytes.len();
+
+        if end > (u16::MAX - START) as usize + 1 {
+            return Err(AssembleError::at(
+                &self.last,
+                "the program does not fit in memory",
+            ));
+        }
+
+        if self.rom.len() < end {
+            self.rom.resize(end, 0);
+        }
+
+        self.rom[offset..end].copy_from_slice(bytes);
+        self.here = self.here.wrapping_add(bytes.len() as u16);
+
+        Ok(())
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> Result<(), AssembleError> {
+        let bytes = instruction
+            .encode()
+            .map_err(|e| AssembleError::at(&self.last, e.to_string()))?;
+
+        self.write(&bytes)
+    }
+
+    fn next(&mut self) -> Result<Token, AssembleError> {
+        let token = self
+            .tokens
+            .pop_front()
+            .ok_or_else(|| AssembleError::at(&self.last, "unexpected end of the program"))?;
+
+        self.last = token.clone();
+
+        Ok(token)
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.front().map(|t| t.text.as_str())
+    }
+
+    fn expect(&mut self, text: &str) -> Result<Token, AssembleError> {
+        let token = self.next()?;
+
+        if token.text != text {
+            return Err(AssembleError::at(
+                &token,
+                format!("expected '{}', found '{}'", text, token.text),
+            ));
+        }
+
+        Ok(token)
+    }
+
+    /// Returns the register the token names, directly or through an alias.
+    ///
+    fn as_register(&self, text: &str) -> Option<Register> {
+        let lower = text.to_ascii_lowercase();
+
+        if let Some(n) = lower.strip_prefix('v') {
+            if n.len() == 1 {
+                if let Ok(n) = u8::from_str_radix(n, 16) {
+                    return Register::new(n).ok();
+                }
+            }
+        }
+
+        self.aliases.get(text).copied()
+    }
+
+    fn register(&mut self) -> Result<Register, AssembleError> {
+        let token = self.next()?;
+
+        self.as_register(&token.text).ok_or_else(|| {
+            AssembleError::at(
+                &token,
+                format!("expected a register, found '{}'", token.text),
+            )
+        })
+    }
+
+    /// Returns the value of a number, constant or label defined so far.
+    ///
+    fn lookup(&self, text: &str) -> Option<i64> {
+        parse_number(text)
+            .or_else(|| self.constants.get(text).copied())
+            .or_else(|| self.labels.get(text).map(|addr| *addr as i64))
+            .or_else(|| {
+                if text == "HERE" {
+                    Some(self.here as i64)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Reads a value that has to be known already.
+    ///
+    fn value(&mut self) -> Result<(Token, i64), AssembleError> {
+        let token = self.next()?;
+
+        match self.lookup(&token.text) {
+            Some(value) => Ok((token, value)),
+            None => Err(AssembleError::at(
+                &token,
+                format!("expected a value, found '{}'", token.text),
+            )),
+        }
+    }
+
+    /// Reads a value from -128 to 255, negative values wrap.
+    ///
+    fn byte(&mut self) -> Result<Byte, AssembleError> {
+        let (token, value) = self.value()?;
+
+        to_byte(&token, value)
+    }
+
+    fn nibble(&mut self) -> Result<Nibble, AssembleError> {
+        let (token, value) = self.value()?;
+
+        if !(0..=15).contains(&value) {
+            return Err(AssembleError::at(
+                &token,
+                format!("{} does not fit in 4 bits", value),
+            ));
+        }
+
+        Ok(Nibble::new(value as u8))
+    }
+
+    /// Reads the address of an instruction, which may be a label that is
+    /// defined further down.
+    ///
+    ///### Arguments
+    ///
+    ///- **width** : How the address is stored in the instruction emitted next.
+    ///
+    fn address(&mut self, width: Width) -> Result<Address, AssembleError> {
+        let token = self.next()?;
+
+        match self.lookup(&token.text) {
+            Some(value) => {
+                let max = match width {
+                    Width::Twelve => 0xFFF,
+                    Width::Sixteen => 0xFFFF,
+                };
+
+                if !(0..=max).contains(&value) {
+                    return Err(AssembleError::at(
+                        &token,
+                        format!("the address {} is out of range", value),
+                    ));
+                }
+
+                Ok(Address::new(value as u16))
+            }
+            None if is_name(&token.text) => {
+                self.reference(token, width);
+                Ok(Address::new(0))
+            }
+            None => Err(AssembleError::at(
+                &token,
+                format!("expected an address, found '{}'", token.text),
+            )),
+        }
+    }
+
+    /// Remembers that the instruction emitted next needs the address of a
+    /// label.
+    ///
+    fn reference(&mut self, token: Token, width: Width) {
+        self.fixups.push(Fixup {
+            addr: self.here,
+            width,
+            token,
+        });
+    }
+
+    /// Checks that a new name is free.
+    ///
+    fn new_name(&mut self) -> Result<Token, AssembleError> {
+        let token = self.next()?;
+        let text = token.text.as_str();
+
+        if !is_name(text) || KEYWORDS.contains(&text) || self.as_register(text).is_some() {
+            return Err(AssembleError::at(
+                &token,
+                format!("'{}' can not be used as a name", text),
+            ));
+        }
+
+        if self.labels.contains_key(text)
+            || self.constants.contains_key(text)
+            || self.macros.contains_key(text)
+        {
+            return Err(AssembleError::at(
+                &token,
+                format!("'{}' is already defined", text),
+            ));
+        }
+
+        Ok(token)
+    }
+
+    /// Reads the tokens between braces, which may nest.
+    ///
+    fn braced(&mut self) -> Result<(Vec<Token>, Token), AssembleError> {
+        self.expect("{")?;
+
+        let mut depth = 0;
+        let mut tokens = Vec::new();
+
+        loop {
+            let token = self.next()?;
+
+            match token.text.as_str() {
+                "{" => depth += 1,
+                "}" if depth == 0 => return Ok((tokens, token)),
+                "}" => depth -= 1,
+                _ => {}
+            }
+
+            tokens.push(token);
+        }
+    }
+
+    /// Evaluates a `{ ... }` expression.
+    ///
+    fn calc(&mut self) -> Result<(Token, i64), AssembleError> {
+        let (tokens, end) = self.braced()?;
+        let value = calc::evaluate(&tokens, &end, &|name| self.lookup(name))?;
+
+        Ok((end, value))
+    }
+
+    /// Compiles the next statement.
+    ///
+    fn statement(&mut self) -> Result<(), AssembleError> {
+        use Instruction::*;
+
+        let token = self.next()?;
+
+        match token.text.as_str() {
+            ":" => {
+                let name = self.new_name()?;
+                self.labels.insert(name.text, self.here);
+            }
+            ":next" => {
+                // Names the second byte of the next instruction, for code
+                // that modifies its own operands.
+                let name = self.new_name()?;
+                self.labels.insert(name.text, self.here.wrapping_add(1));
+            }
+            ":alias" => {
+                let name = self.new_name()?;
+                let register = self.register()?;
+                self.aliases.insert(name.text, register);
+            }
+            ":const" => {
+                let name = self.new_name()?;
+                let (_, value) = self.value()?;
+                self.constants.insert(name.text, value);
+            }
+            ":calc" => {
+                let name = self.new_name()?;
+                let (_, value) = self.calc()?;
+                self.constants.insert(name.text, value);
+            }
+            ":byte" => {
+                let byte = if self.peek() == Some("{") {
+                    let (end, value) = self.calc()?;
+                    to_byte(&end, value)?
+                } else {
+                    self.byte()?
+                };
+
+                self.write(&[byte.get_raw()])?;
+            }
+            ":org" => {
+                let (token, value) = self.value()?;
+
+                if !(START as i64..=u16::MAX as i64).contains(&value) {
+                    return Err(AssembleError::at(
+                        &token,
+                        format!("can not assemble at {}", value),
+                    ));
+                }
+
+                self.here = value as u16;
+            }
+            ":macro" => {
+                let name = self.new_name()?;
+                let mut params = Vec::new();
+
+                while self.peek().is_some_and(|t| t != "{") {
+                    params.push(self.next()?.text);
+                }
+
+                let (body, _) = self.braced()?;
+                self.macros.insert(name.text, Macro { params, body });
+            }
+            ":call" => {
+                let addr = self.address(Width::Twelve)?;
+                self.emit(Call(addr))?;
+            }
+
+            ";" | "return" => self.emit(Return)?,
+            "clear" => self.emit(Cls)?,
+            "exit" => self.emit(Exit)?,
+            "lores" => self.emit(LowRes)?,
+            "hires" => self.emit(HighRes)?,
+            "scroll-left" => self.emit(ScrollLeft)?,
+            "scroll-right" => self.emit(ScrollRight)?,
+            "scroll-down" => {
+                let n = self.nibble()?;
+                self.emit(ScrollDown(n))?;
+            }
+            "scroll-up" => {
+                let n = self.nibble()?;
+                self.emit(ScrollUp(n))?;
+            }
+            "plane" => {
+                let n = self.nibble()?;
+                self.emit(SelectPlanes(n))?;
+            }
+            "audio" => self.emit(LoadAudioPattern)?,
+
+            "bcd" => {
+                let vx = self.register()?;
+                self.emit(LoadBCD(vx))?;
+            }
+            "save" | "load" => {
+                let vx = self.register()?;
+
+                let instruction = if self.peek() == Some("-") {
+                    self.next()?;
+                    let vy = self.register()?;
+
+                    if token.text == "save" {
+                        SaveRange(vx, vy)
+                    } else {
+                        LoadRange(vx, vy)
+                    }
+                } else if token.text == "save" {
+                    CopyToRam(vx)
+                } else {
+                    CopyToRegisters(vx)
+                };
+
+                self.emit(instruction)?;
+            }
+            "saveflags" => {
+                let vx = self.register()?;
+                self.emit(SaveFlags(vx))?;
+            }
+            "loadflags" => {
+                let vx = self.register()?;
+                self.emit(LoadFlags(vx))?;
+            }
+            "sprite" => {
+                let vx = self.register()?;
+                let vy = self.register()?;
+                let n = self.nibble()?;
+                self.emit(Draw(vx, vy, n))?;
+            }
+            "jump" => {
+                let addr = self.address(Width::Twelve)?;
+                self.emit(Jump(addr))?;
+            }
+            "jump0" => {
+                let addr = self.address(Width::Twelve)?;
+                self.emit(JumpPlus(addr))?;
+            }
+            "native" => {
+                let addr = self.address(Width::Twelve)?;
+                self.emit(Sys(addr))?;
+            }
+
+            "delay" | "buzzer" | "pitch" => {
+                self.expect(":=")?;
+                let vx = self.register()?;
+
+                self.emit(match token.text.as_str() {
+                    "delay" => SetDelayTimer(vx),
+                    "buzzer" => SetSoundTimer(vx),
+                    _ => SetPitch(vx),
+                })?;
+            }
+            "i" => self.assign_i()?,
+
+            "if" => self.conditional(token)?,
+            "else" => match self.flow.pop() {
+                Some(Flow::If { jump, .. }) => {
+                    let exit = self.here;
+                    self.emit(Jump(Address::new(0)))?;
+
+                    let here = self.here;
+                    self.patch(&token, jump, Width::Twelve, here)?;
+                    self.flow.push(Flow::Else { jump: exit, token });
+                }
+                _ => return Err(AssembleError::at(&token, "'else' without 'if ... begin'")),
+            },
+            "end" => match self.flow.pop() {
+                Some(Flow::If { jump, .. }) | Some(Flow::Else { jump, .. }) => {
+                    let here = self.here;
+                    self.patch(&token, jump, Width::Twelve, here)?;
+                }
+                _ => return Err(AssembleError::at(&token, "'end' without 'if ... begin'")),
+            },
+            "loop" => {
+                self.flow.push(Flow::Loop {
+                    start: self.here,
+                    exits: Vec::new(),
+                    token,
+                });
+            }
+            "while" => {
+                let start = self
+                    .flow
+                    .iter()
+                    .rposition(|f| matches!(f, Flow::Loop { .. }));
+                if start.is_none() {
+                    return Err(AssembleError::at(&token, "'while' outside of a loop"));
+                }
+
+                // Leave the loop unless the condition holds.
+                self.condition(true)?;
+                let exit = self.here;
+                self.emit(Jump(Address::new(0)))?;
+
+                if let Some(Flow::Loop { exits, .. }) = start.map(|n| &mut self.flow[n]) {
+                    exits.push(exit);
+                }
+            }
+            "again" => match self.flow.pop() {
+                Some(Flow::Loop { start, exits, .. }) => {
+                    self.emit(Jump(Address::new(start)))?;
+
+                    let here = self.here;
+                    for exit in exits {
+                        self.patch(&token, exit, Width::Twelve, here)?;
+                    }
+                }
+                _ => return Err(AssembleError::at(&token, "'again' without 'loop'")),
+            },
+
+            text => {
+                if let Some(vx) = self.as_register(text) {
+                    self.assign(vx)?;
+                } else if self.macros.contains_key(text) {
+                    self.expand(token)?;
+                } else if let Some(value) = self
+                    .lookup(text)
+                    .filter(|_| !self.labels.contains_key(text))
+                {
+                    // Numbers and constants on their own are data.
+                    let byte = to_byte(&token, value)?;
+                    self.write(&[byte.get_raw()])?;
+                } else if self.peek().is_some_and(is_operator) {
+                    return Err(AssembleError::at(
+                        &token,
+                        format!("'{}' is not a register", text),
+                    ));
+                } else if is_name(text) && !KEYWORDS.contains(&text) {
+                    self.tokens.push_front(token);
+                    let addr = self.address(Width::Twelve)?;
+                    self.emit(Call(addr))?;
+                } else {
+                    return Err(AssembleError::at(&token, format!("unexpected '{}'", text)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compiles `vx <op> ...`.
+    ///
+    fn assign(&mut self, vx: Register) -> Result<(), AssembleError> {
+        use Instruction::*;
+
+        let op = self.next()?;
+        let source = self.peek().and_then(|t| self.as_register(t));
+
+        let instruction = match (op.text.as_str(), source) {
+            (":=", Some(vy)) => LoadFromReg(vx, vy),
+            ("+=", Some(vy)) => AddReg(vx, vy),
+            ("-=", Some(vy)) => SubReg(vx, vy),
+            ("=-", Some(vy)) => ReverseSub(vx, vy),
+            ("|=", Some(vy)) => Or(vx, vy),
+            ("&=", Some(vy)) => And(vx, vy),
+            ("^=", Some(vy)) => XOr(vx, vy),
+            (">>=", Some(vy)) => ShiftRight(vx, vy),
+            ("<<=", Some(vy)) => ShiftLeft(vx, vy),
+
+            (":=", None) => match self.peek() {
+                Some("random") => {
+                    self.next()?;
+                    Rand(vx, self.byte()?)
+                }
+                Some("delay") => {
+                    self.next()?;
+                    LoadDelayTimer(vx)
+                }
+                Some("key") => {
+                    self.next()?;
+                    WaitForKey(vx)
+                }
+                _ => Load(vx, self.byte()?),
+            },
+            ("+=", None) => Add(vx, self.byte()?),
+            ("-=", None) => Add(vx, Byte::new(self.byte()?.get_raw().wrapping_neg())),
+
+            _ => {
+                return Err(AssembleError::at(
+                    &op,
+                    format!("unknown operator '{}'", op.text),
+                ))
+            }
+        };
+
+        if source.is_some() {
+            self.next()?;
+        }
+
+        self.emit(instruction)
+    }
+
+    /// Compiles `i := ...` and `i += vx`.
+    ///
+    fn assign_i(&mut self) -> Result<(), AssembleError> {
+        use Instruction::*;
+
+        let op = self.next()?;
+
+        let instruction = match (op.text.as_str(), self.peek()) {
+            ("+=", _) => IncrementAddress(self.register()?),
+            (":=", Some("hex")) => {
+                self.next()?;
+                LoadSpriteAddress(self.register()?)
+            }
+            (":=", Some("bighex")) => {
+                self.next()?;
+                LoadBigSpriteAddress(self.register()?)
+            }
+            (":=", Some("long")) => {
+                self.next()?;
+                LoadLongInstr(self.address(Width::Sixteen)?)
+            }
+            (":=", _) => LoadInstr(self.address(Width::Twelve)?),
+            _ => {
+                return Err(AssembleError::at(
+                    &op,
+                    format!("unknown operator '{}'", op.text),
+                ))
+            }
+        };
+
+        self.emit(instruction)
+    }
+
+    /// Compiles `if <condition> then` and `if <condition> begin`.
+    ///
+    fn conditional(&mut self, token: Token) -> Result<(), AssembleError> {
+        // Find out which form it is before emitting anything.
+        let form = self
+            .tokens
+            .iter()
+            .take(4)
+            .find(|t| t.text == "then" || t.text == "begin")
+            .map(|t| t.text.clone());
+
+        match form.as_deref() {
+            Some("then") => {
+                // Skip the next statement unless the condition holds.
+                self.condition(false)?;
+                self.expect("then")?;
+            }
+            Some(_) => {
+                // Jump to the else branch or the end unless it holds.
+                self.condition(true)?;
+                self.expect("begin")?;
+
+                let jump = self.here;
+                self.emit(Instruction::Jump(Address::new(0)))?;
+                self.flow.push(Flow::If { jump, token });
+            }
+            None => {
+                return Err(AssembleError::at(&token, "expected 'then' or 'begin'"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compiles a comparison into instructions that skip the next one when
+    /// the comparison is `skip_if`.
+    ///
+    /// `<`, `>`, `<=` and `>=` are worked out by subtracting into vf, which
+    /// is overwritten.
+    ///
+    fn condition(&mut self, skip_if: bool) -> Result<(), AssembleError> {
+        use Instruction::*;
+
+        let vx = self.register()?;
+        let op = self.next()?;
+
+        let operand = match op.text.as_str() {
+            "key" | "-key" => None,
+            _ => {
+                let token = self.next()?;
+
+                Some(match self.as_register(&token.text) {
+                    Some(vy) => Operand::Register(vy),
+                    None => {
+                        self.tokens.push_front(token);
+                        Operand::Byte(self.byte()?)
+                    }
+                })
+            }
+        };
+
+        // The instructions skip when the comparison holds, so skipping when
+        // it does not is the negated comparison.
+        let op_text = if skip_if {
+            op.text.clone()
+        } else {
+            match op.text.as_str() {
+                "==" => "!=",
+                "!=" => "==",
+                "key" => "-key",
+                "-key" => "key",
+                "<" => ">=",
+                ">=" => "<",
+                ">" => "<=",
+                "<=" => ">",
+                other => other,
+            }
+            .to_string()
+        };
+
+        let instructions = match (op_text.as_str(), operand) {
+            ("==", Some(Operand::Byte(b))) => vec![SkipEqualByte(vx, b)],
+            ("==", Some(Operand::Register(vy))) => vec![SkipEqualReg(vx, vy)],
+            ("!=", Some(Operand::Byte(b))) => vec![SkipNotEqualByte(vx, b)],
+            ("!=", Some(Operand::Register(vy))) => vec![SkipNotEqualReg(vx, vy)],
+            ("key", None) => vec![SkipPressed(vx)],
+            ("-key", None) => vec![SkipNotPressed(vx)],
+
+            (cmp @ "<", Some(operand))
+            | (cmp @ ">", Some(operand))
+            | (cmp @ "<=", Some(operand))
+            | (cmp @ ">=", Some(operand)) => {
+                let vf = Register::VF;
+
+                if vx == vf || matches!(operand, Operand::Register(Register::VF)) {
+                    return Err(AssembleError::at(
+                        &op,
+                        "vf holds the comparison and can not be compared",
+                    ));
+                }
+
+                let load = match operand {
+                    Operand::Register(vy) => LoadFromReg(vf, vy),
+                    Operand::Byte(b) => Load(vf, b),
+                };
+
+                // vf := b; vf -= a leaves the flag set when b >= a, and
+                // vf := b; vf =- a leaves it set when a >= b.
+                let (subtract, flag) = match cmp {
+                    ">" => (SubReg(vf, vx), 0),
+                    "<" => (ReverseSub(vf, vx), 0),
+                    ">=" => (ReverseSub(vf, vx), 1),
+                    _ => (SubReg(vf, vx), 1),
+                };
+
+                vec![load, subtract, SkipEqualByte(vf, Byte::new(flag))]
+            }
+
+            _ => {
+                return Err(AssembleError::at(
+                    &op,
+                    format!("unknown comparison '{}'", op.text),
+                ))
+            }
+        };
+
+        for instruction in instructions {
+            self.emit(instruction)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces a macro call with the body of the macro.
+    ///
+    fn expand(&mut self, token: Token) -> Result<(), AssembleError> {
+        self.expansions += 1;
+        if self.expansions > MAX_EXPANSIONS {
+            return Err(AssembleError::at(&token, "too many macro expansions"));
+        }
+
+        let (params, body) = match self.macros.get(&token.text) {
+            Some(m) => (m.params.clone(), m.body.clone()),
+            None => return Ok(()),
+        };
+
+        let mut args = HashMap::new();
+        for param in params {
+            let arg = self.next()?;
+            args.insert(param, arg.text);
+        }
+
+        for mut body_token in body.into_iter().rev() {
+            if let Some(arg) = args.get(&body_token.text) {
+                body_token.text = arg.clone();
+            }
+
+            self.tokens.push_front(body_token);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns a flag indicating if the text assigns to a register.
+///
+fn is_operator(text: &str) -> bool {
+    matches!(
+        text,
+        ":=" | "+=" | "-=" | "=-" | "|=" | "&=" | "^=" | ">>=" | "<<="
+    )
+}
+
+/// Converts a value from -128 to 255 into a byte.
+///
+fn to_byte(token: &Token, value: i64) -> Result<Byte, AssembleError> {
+    if !(-128..=255).contains(&value) {
+        return Err(AssembleError::at(
+            token,
+            format!("{} does not fit in a byte", value),
+        ));
+    }
+
+    Ok(Byte::new(value as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assemble(source: &str) -> Vec<u8> {
+        match Compiler::new(source).compile() {
+            Ok(rom) => rom,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn error(source: &str) -> (usize, usize, String) {
+        let e = Compiler::new(source).compile().unwrap_err();
+        (e.line, e.column, e.message)
+    }
+
+    #[test]
+    fn assembles_statements() {
+        let rom = assemble(
+            ": main
+               clear
+               v0 := 0x1F  v1 := v0  v2 += 3  v2 -= 1  v3 =- v1
+               v4 := random 0xF0  v5 := key  v6 := delay
+               i := hex v0  i += v1  bcd v2  save v3  load v3
+               delay := v0  buzzer := v1
+               sprite v0 v1 5
+               return",
+        );
+
+        assert_eq!(
+            rom,
+            vec![
+                0x00, 0xE0, 0x60, 0x1F, 0x81, 0x00, 0x72, 0x03, 0x72, 0xFF, 0x83, 0x17, 0xC4, 0xF0,
+                0xF5, 0x0A, 0xF6, 0x07, 0xF0, 0x29, 0xF1, 0x1E, 0xF2, 0x33, 0xF3, 0x55, 0xF3, 0x65,
+                0xF0, 0x15, 0xF1, 0x18, 0xD0, 0x15, 0x00, 0xEE,
+            ]
+        );
+    }
+
+    #[test]
+    fn jumps_to_main_and_resolves_forward_labels() {
+        let rom = assemble(
+            ": sprite-data 0xF0 0x90
+             : main
+               i := sprite-data
+               draw
+               jump main
+             : draw
+               sprite v0 v0 2 ;",
+        );
+
+        assert_eq!(
+            rom,
+            vec![
+                0x12, 0x04, 0xF0, 0x90, 0xA2, 0x02, 0x22, 0x0A, 0x12, 0x04, 0xD0, 0x02, 0x00, 0xEE,
+            ]
+        );
+    }
+
+    #[test]
+    fn compiles_conditionals() {
+        let rom = assemble(": main if v0 == 5 then v1 := 1 if v0 key then ;");
+        assert_eq!(rom, vec![0x40, 0x05, 0x61, 0x01, 0xE0, 0xA1, 0x00, 0xEE]);
+
+        let rom = assemble(
+            ": main
+               if v0 != v1 begin v2 := 1 else v2 := 2 end",
+        );
+        assert_eq!(
+            rom,
+            vec![0x90, 0x10, 0x12, 0x08, 0x62, 0x01, 0x12, 0x0A, 0x62, 0x02]
+        );
+    }
+
+    #[test]
+    fn compiles_loops() {
+        let rom = assemble(
+            ": main
+               loop
+                 v0 += 1
+                 while v0 != 10
+               again",
+        );
+
+        assert_eq!(rom, vec![0x70, 0x01, 0x40, 0x0A, 0x12, 0x08, 0x12, 0x00]);
+    }
+
+    #[test]
+    fn compiles_comparisons_through_vf() {
+        // Skips the assignment unless v0 > v1, so when v1 - v0 borrows.
+        let rom = assemble(": main if v0 > v1 then v2 := 1");
+        assert_eq!(rom, vec![0x8F, 0x10, 0x8F, 0x05, 0x3F, 0x01, 0x62, 0x01]);
+
+        let rom = assemble(": main if v0 < 5 then v2 := 1");
+        assert_eq!(rom, vec![0x6F, 0x05, 0x8F, 0x07, 0x3F, 0x01, 0x62, 0x01]);
+    }
+
+    #[test]
+    fn supports_directives() {
+        let rom = assemble(
+            ":const WIDTH 64
+             :calc HALF { WIDTH / 2 }
+             :alias x v4
+             :macro twice reg { reg += reg }
+             : main
+               x := HALF
+               twice x
+             :next target
+               v0 := 0
+               i := target
+               :byte { HALF + 1 }
+               WIDTH",
+        );
+
+        assert_eq!(
+            rom,
+            vec![0x12, 0x02, 0x64, 0x20, 0x84, 0x44, 0x60, 0x00, 0xA2, 0x07, 0x21, 0x40]
+        );
+    }
+
+    #[test]
+    fn reports_errors_at_the_token() {
+        assert_eq!(
+            error(": main\n  v0 := 256"),
+            (2, 9, "256 does not fit in a byte".to_string())
+        );
+        assert_eq!(
+            error(": main\n  jump nowhere"),
+            (2, 8, "undefined name 'nowhere'".to_string())
+        );
+        assert_eq!(
+            error(": main\n  loop\n  v0 += 1"),
+            (2, 3, "'loop' without 'again'".to_string())
+        );
+        assert_eq!(
+            error(": main\n  vx := 1"),
+            (2, 3, "'vx' is not a register".to_string())
+        );
+        assert_eq!(
+            error(": main\n: main"),
+            (2, 3, "'main' is already defined".to_string())
+        );
+        assert_eq!(
+            error(": main if vf > v1 then ;"),
+            (
+                1,
+                14,
+                "vf holds the comparison and can not be compared".to_string()
+            )
+        );
+        assert_eq!(
+            error(": main\n  native 0xE0"),
+            (
+                2,
+                10,
+                "SYS 0x0E0 encodes to 0x00E0, which is CLS".to_string()
+            )
+        );
+        assert_eq!(
+            error("v0 := 1"),
+            (1, 1, "the program has no 'main' label".to_string())
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use super::token::Token;
+use std::error::Error;
+use std::fmt;
+
+/// An error in the source, at the line and column it was found.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssembleError {
+    /// The line, counting from 1.
+    pub line: usize,
+
+    /// The column in characters, counting from 1.
+    pub column: usize,
+
+    pub message: String,
+}
+
+impl AssembleError {
+    /// Creates a new AssembleError at the position of the token.
+    ///
+    ///### Arguments
+    ///
+    ///- **token**   : The token the error is about.
+    ///- **message** : What is wrong.
+    ///
+    pub(crate) fn at<S: Into<String>>(token: &Token, message: S) -> Self {
+        AssembleError {
+            line: token.line,
+            column: token.column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for AssembleError {}
@@ -0,0 +1,41 @@
+//! ##Chip-8 Assembler
+//!
+//! Assembles programs written in the syntax of
+//! [Octo](https://github.com/JohnEarnest/Octo) into ROMs.
+//!
+//! Supported are labels, `:alias`, `:const`, `:calc`, `:byte`, `:next`,
+//! `:org`, `:call`, `:macro`, the register and `i` statements, and the
+//! control flow sugar `if ... then`, `if ... begin ... else ... end` and
+//! `loop ... while ... again`.
+//!
+//! Execution starts at the `main` label. Unless `main` comes first, the ROM
+//! starts with a jump to it.
+//!
+//! The comparisons `<`, `>`, `<=` and `>=` are worked out by subtracting in
+//! vf, so vf is overwritten and can not be compared itself.
+//!
+
+extern crate data;
+extern crate instruction;
+extern crate model;
+
+mod calc;
+mod compiler;
+mod error;
+mod token;
+
+pub use error::AssembleError;
+
+/// Assembles the program.
+///
+///### Arguments
+///
+///- **source** : The program text.
+///
+///### Returns
+///
+/// The bytes of the rom, to be loaded at 0x200, or the first error.
+///
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssembleError> {
+    compiler::Compiler::new(source).compile()
+}
@@ -0,0 +1,48 @@
+//! Assembles an Octo source file into a Chip-8 ROM.
+//!
+//! Usage: `assembler <source.8o> [out.ch8]`. Without an output file the rom
+//! is written next to the source with the `ch8` extension.
+//!
+
+extern crate assembler;
+
+use assembler::assemble;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("usage: assembler <source.8o> [out.ch8]");
+        process::exit(2);
+    }
+
+    let source = match fs::read_to_string(&args[0]) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("{}: {}", args[0], e);
+            process::exit(1);
+        }
+    };
+
+    let rom = match assemble(&source) {
+        Ok(rom) => rom,
+        Err(e) => {
+            eprintln!("{}:{}", args[0], e);
+            process::exit(1);
+        }
+    };
+
+    let out = match args.get(1) {
+        Some(out) => PathBuf::from(out),
+        None => PathBuf::from(&args[0]).with_extension("ch8"),
+    };
+
+    if let Err(e) = fs::write(&out, &rom) {
+        eprintln!("{}: {}", out.display(), e);
+        process::exit(1);
+    }
+}
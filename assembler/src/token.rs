@@ -0,0 +1,117 @@
+/// A word of the source and where it starts.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Token {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Splits the source into whitespace separated tokens, dropping comments.
+///
+/// A comment starts with `#` and runs to the end of the line.
+///
+///### Arguments
+///
+///- **source** : The program text.
+///
+pub(crate) fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+
+    for (n, line) in source.lines().enumerate() {
+        let mut current: Option<Token> = None;
+
+        for (column, c) in line.chars().enumerate() {
+            if c == '#' {
+                break;
+            }
+
+            if c.is_whitespace() {
+                tokens.extend(current.take());
+            } else {
+                current
+                    .get_or_insert_with(|| Token {
+                        text: String::new(),
+                        line: n + 1,
+                        column: column + 1,
+                    })
+                    .text
+                    .push(c);
+            }
+        }
+
+        tokens.extend(current);
+    }
+
+    tokens
+}
+
+/// Parses a numeric literal: decimal, `0x` hex or `0b` binary, optionally
+/// negative.
+///
+pub(crate) fn parse_number(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        i64::from_str_radix(binary, 2).ok()?
+    } else if digits.starts_with(|c: char| c.is_ascii_digit()) {
+        digits.parse().ok()?
+    } else {
+        return None;
+    };
+
+    Some(if negative { -value } else { value })
+}
+
+/// Returns a flag indicating if the text can name a label, constant, alias
+/// or macro.
+///
+pub(crate) fn is_name(text: &str) -> bool {
+    let mut chars = text.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_know_their_position_and_skip_comments() {
+        let tokens = tokenize(": main\n  v0 := 0x1F # set v0\n\n#only a comment\n  ;");
+        let found: Vec<(&str, usize, usize)> = tokens
+            .iter()
+            .map(|t| (t.text.as_str(), t.line, t.column))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (":", 1, 1),
+                ("main", 1, 3),
+                ("v0", 2, 3),
+                (":=", 2, 6),
+                ("0x1F", 2, 9),
+                (";", 5, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_can_be_hex_binary_and_negative() {
+        assert_eq!(parse_number("42"), Some(42));
+        assert_eq!(parse_number("0xFF"), Some(255));
+        assert_eq!(parse_number("0b1010"), Some(10));
+        assert_eq!(parse_number("-1"), Some(-1));
+        assert_eq!(parse_number("v0"), None);
+        assert_eq!(parse_number("0xZZ"), None);
+    }
+}
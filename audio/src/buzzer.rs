@@ -0,0 +1,155 @@
+use super::ToneGenerator;
+use data::Byte;
+use model::Chip8Audio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// An XO-CHIP audio pattern and its pitch.
+type Pattern = Option<([u8; 16], u8)>;
+
+/// The side of the buzzer the machine switches on and off.
+///
+#[derive(Debug)]
+pub struct Buzzer {
+    on: Arc<AtomicBool>,
+    pattern: Arc<Mutex<Pattern>>,
+}
+
+impl Buzzer {
+    /// Returns a flag indicating if the buzzer is sounding.
+    ///
+    pub fn is_on(&self) -> bool {
+        self.on.load(Ordering::Relaxed)
+    }
+}
+
+impl Chip8Audio for Buzzer {
+    fn start(&mut self) {
+        self.on.store(true, Ordering::Relaxed);
+    }
+
+    fn stop(&mut self) {
+        self.on.store(false, Ordering::Relaxed);
+    }
+
+    fn set_pattern(&mut self, pattern: &[Byte; 16], pitch: Byte) {
+        if let Ok(mut shared) = self.pattern.lock() {
+            *shared = Some((pattern.map(|b| b.get_raw()), pitch.get_raw()));
+        }
+    }
+
+    fn clear_pattern(&mut self) {
+        if let Ok(mut shared) = self.pattern.lock() {
+            *shared = None;
+        }
+    }
+}
+
+/// The side of the buzzer an audio callback pulls samples from, usually on
+/// another thread.
+///
+#[derive(Debug)]
+pub struct Tone {
+    generator: ToneGenerator,
+    on: Arc<AtomicBool>,
+    pattern: Arc<Mutex<Pattern>>,
+
+    /// The pattern the generator was last given.
+    playing: Pattern,
+}
+
+impl Tone {
+    /// Returns the generator, to change the sound while playing.
+    ///
+    pub fn generator_mut(&mut self) -> &mut ToneGenerator {
+        &mut self.generator
+    }
+
+    /// Fills the buffer with the tone while the buzzer is on and with silence
+    /// otherwise. The audio pattern is played instead of the tone once the
+    /// machine set one.
+    ///
+    pub fn fill(&mut self, out: &mut [f32]) {
+        if let Ok(shared) = self.pattern.lock() {
+            if *shared != self.playing {
+                match *shared {
+                    Some((pattern, pitch)) => self.generator.set_pattern(pattern, pitch),
+                    None => self.generator.clear_pattern(),
+                }
+
+                self.playing = *shared;
+            }
+        }
+
+        if self.on.load(Ordering::Relaxed) {
+            self.generator.fill(out);
+        } else {
+            for sample in out.iter_mut() {
+                *sample = 0.0;
+            }
+        }
+    }
+}
+
+/// Creates a buzzer playing the tone of the generator.
+///
+///### Arguments
+///
+///- **generator** : The sound of the buzzer.
+///
+pub fn buzzer(generator: ToneGenerator) -> (Buzzer, Tone) {
+    let on = Arc::new(AtomicBool::new(false));
+    let pattern = Arc::new(Mutex::new(None));
+
+    (
+        Buzzer {
+            on: Arc::clone(&on),
+            pattern: Arc::clone(&pattern),
+        },
+        Tone {
+            generator,
+            on,
+            pattern,
+            playing: None,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Waveform;
+    use super::*;
+
+    #[test]
+    fn tone_is_silent_until_the_buzzer_starts() {
+        let (mut buzzer, mut tone) = buzzer(ToneGenerator::new(Waveform::Square, 440.0, 1.0, 8000));
+        let mut out = [1.0; 8];
+
+        tone.fill(&mut out);
+        assert!(out.iter().all(|s| *s == 0.0));
+
+        buzzer.start();
+        tone.fill(&mut out);
+        assert!(buzzer.is_on());
+        assert!(out.iter().all(|s| *s != 0.0));
+
+        buzzer.stop();
+        tone.fill(&mut out);
+        assert!(out.iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn tone_plays_the_pattern_once_set() {
+        let (mut buzzer, mut tone) = buzzer(ToneGenerator::new(Waveform::Square, 440.0, 1.0, 4000));
+        let mut out = [0.0; 8];
+        buzzer.start();
+
+        buzzer.set_pattern(&[0x0F.into(); 16], 64.into());
+        tone.fill(&mut out);
+        assert_eq!(out, [-1.0, -1.0, -1.0, -1.0, 1.0, 1.0, 1.0, 1.0]);
+
+        buzzer.clear_pattern();
+        tone.fill(&mut out);
+        assert_eq!(out[..4], [1.0; 4]);
+    }
+}
@@ -0,0 +1,178 @@
+use std::f32::consts::PI;
+
+/// The number of 1-bit samples in an XO-CHIP audio pattern.
+const PATTERN_SAMPLES: usize = 128;
+
+/// The shape of the tone.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    Square,
+    Triangle,
+    Sawtooth,
+    Sine,
+}
+
+/// Produces samples of a tone in the range -volume to volume.
+///
+#[derive(Clone, Debug)]
+pub struct ToneGenerator {
+    pub waveform: Waveform,
+    pub frequency: f32,
+    pub volume: f32,
+    sample_rate: u32,
+
+    /// The XO-CHIP audio pattern played instead of the waveform, and the
+    /// number of times per second it is played through.
+    pattern: Option<([u8; 16], f32)>,
+
+    /// The position within the current period, from 0 to 1.
+    phase: f32,
+}
+
+impl ToneGenerator {
+    /// Creates a new ToneGenerator.
+    ///
+    ///### Arguments
+    ///
+    ///- **waveform**    : The shape of the tone.
+    ///- **frequency**   : The pitch in Hz.
+    ///- **volume**      : The amplitude, from 0 to 1.
+    ///- **sample_rate** : The number of samples per second.
+    ///
+    pub fn new(waveform: Waveform, frequency: f32, volume: f32, sample_rate: u32) -> Self {
+        ToneGenerator {
+            waveform,
+            frequency,
+            volume: volume.clamp(0.0, 1.0),
+            sample_rate: sample_rate.max(1),
+            pattern: None,
+            phase: 0.0,
+        }
+    }
+
+    /// Returns the number of samples per second.
+    ///
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Changes the number of samples per second, for when the audio device
+    /// does not give us the rate we asked for.
+    ///
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate.max(1);
+    }
+
+    /// Plays an XO-CHIP audio pattern instead of the waveform.
+    ///
+    ///### Arguments
+    ///
+    ///- **pattern** : 128 1-bit samples, the first one in the top bit of
+    ///  byte 0.
+    ///- **pitch**   : The XO-CHIP pitch, 64 plays the samples at 4000 Hz.
+    ///
+    pub fn set_pattern(&mut self, pattern: [u8; 16], pitch: u8) {
+        let rate = 4000.0 * 2f32.powf((pitch as f32 - 64.0) / 48.0);
+
+        self.pattern = Some((pattern, rate / PATTERN_SAMPLES as f32));
+    }
+
+    /// Goes back to playing the waveform.
+    ///
+    pub fn clear_pattern(&mut self) {
+        self.pattern = None;
+    }
+
+    /// Returns the next sample.
+    ///
+    pub fn next_sample(&mut self) -> f32 {
+        let p = self.phase;
+        let (value, frequency) = match &self.pattern {
+            Some((pattern, frequency)) => {
+                let n = ((p * PATTERN_SAMPLES as f32) as usize).min(PATTERN_SAMPLES - 1);
+                let bit = pattern[n / 8] >> (7 - n % 8) & 1;
+
+                (if bit == 1 { 1.0 } else { -1.0 }, *frequency)
+            }
+            None => {
+                let value = match self.waveform {
+                    Waveform::Square => {
+                        if p < 0.5 {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    }
+                    Waveform::Triangle => 1.0 - 4.0 * (p - 0.5).abs(),
+                    Waveform::Sawtooth => 2.0 * p - 1.0,
+                    Waveform::Sine => (2.0 * PI * p).sin(),
+                };
+
+                (value, self.frequency)
+            }
+        };
+
+        self.phase = (self.phase + frequency / self.sample_rate as f32).fract();
+
+        value * self.volume
+    }
+
+    /// Fills the buffer with the next samples.
+    ///
+    pub fn fill(&mut self, out: &mut [f32]) {
+        for sample in out.iter_mut() {
+            *sample = self.next_sample();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_wave_alternates_at_the_frequency() {
+        let mut tone = ToneGenerator::new(Waveform::Square, 1000.0, 0.5, 8000);
+        let mut out = [0.0; 16];
+
+        tone.fill(&mut out);
+
+        assert_eq!(out[..8], [0.5, 0.5, 0.5, 0.5, -0.5, -0.5, -0.5, -0.5]);
+        assert_eq!(out[..8], out[8..]);
+    }
+
+    #[test]
+    fn patterns_play_one_bit_per_sample_at_pitch_64() {
+        let mut tone = ToneGenerator::new(Waveform::Sine, 440.0, 0.5, 4000);
+        let mut pattern = [0; 16];
+        pattern[0] = 0b1010_0000;
+        tone.set_pattern(pattern, 64);
+
+        let mut out = [0.0; 4];
+        tone.fill(&mut out);
+        assert_eq!(out, [0.5, -0.5, 0.5, -0.5]);
+
+        tone.clear_pattern();
+        tone.fill(&mut out);
+        assert!(out.iter().any(|s| s.abs() != 0.5));
+    }
+
+    #[test]
+    fn samples_stay_within_the_volume() {
+        for waveform in &[
+            Waveform::Square,
+            Waveform::Triangle,
+            Waveform::Sawtooth,
+            Waveform::Sine,
+        ] {
+            let mut tone = ToneGenerator::new(*waveform, 440.0, 0.25, 44100);
+            let mut out = [0.0; 1000];
+
+            tone.fill(&mut out);
+
+            assert!(out.iter().all(|s| s.abs() <= 0.25), "{:?}", waveform);
+            assert!(out.iter().any(|s| *s != 0.0), "{:?}", waveform);
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Sound for the Chip-8 buzzer.
+//!
+//! `ToneGenerator` produces the samples. `buzzer` splits it into the part the
+//! machine switches on and off and the part an audio callback pulls samples
+//! from, while `WavRecorder` renders the sound offline. Both play the XO-CHIP
+//! audio pattern instead of the tone once the machine sets one.
+//!
+
+extern crate data;
+extern crate model;
+
+mod buzzer;
+mod generator;
+mod wav;
+
+pub use buzzer::{buzzer, Buzzer, Tone};
+pub use generator::{ToneGenerator, Waveform};
+pub use wav::WavRecorder;
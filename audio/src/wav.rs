@@ -0,0 +1,182 @@
+use super::ToneGenerator;
+use data::Byte;
+use model::Chip8Audio;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The number of frames per second the machine notifies `end_frame` at.
+const FRAMES_PER_SECOND: u32 = 60;
+
+/// Renders the buzzer to 16 bit mono PCM without a sound card.
+///
+/// Every frame adds 1/60 of a second of samples, the tone while the buzzer is
+/// on and silence otherwise.
+///
+#[derive(Debug)]
+pub struct WavRecorder {
+    generator: ToneGenerator,
+    on: bool,
+    samples: Vec<i16>,
+    frames: u32,
+}
+
+impl WavRecorder {
+    /// Creates a new WavRecorder.
+    ///
+    ///### Arguments
+    ///
+    ///- **generator** : The sound of the buzzer, its sample rate is the one
+    ///  of the recording.
+    ///
+    pub fn new(generator: ToneGenerator) -> Self {
+        WavRecorder {
+            generator,
+            on: false,
+            samples: Vec::new(),
+            frames: 0,
+        }
+    }
+
+    /// Returns the samples recorded so far.
+    ///
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+
+    /// Returns the recording as the bytes of a WAV file.
+    ///
+    pub fn to_wav(&self) -> Vec<u8> {
+        let sample_rate = self.generator.sample_rate();
+        let data_len = (self.samples.len() * 2) as u32;
+        let mut out = Vec::with_capacity(44 + data_len as usize);
+
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVE");
+
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        out.extend_from_slice(&1u16.to_le_bytes()); // Mono
+        out.extend_from_slice(&sample_rate.to_le_bytes());
+        out.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // Bytes per second
+        out.extend_from_slice(&2u16.to_le_bytes()); // Bytes per sample
+        out.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            out.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        out
+    }
+
+    /// Writes the recording to a WAV file.
+    ///
+    ///### Arguments
+    ///
+    ///- **path** : The file to write.
+    ///
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_wav())
+    }
+}
+
+impl Chip8Audio for WavRecorder {
+    fn start(&mut self) {
+        self.on = true;
+    }
+
+    fn stop(&mut self) {
+        self.on = false;
+    }
+
+    fn set_pattern(&mut self, pattern: &[Byte; 16], pitch: Byte) {
+        self.generator
+            .set_pattern(pattern.map(|b| b.get_raw()), pitch.get_raw());
+    }
+
+    fn clear_pattern(&mut self) {
+        self.generator.clear_pattern();
+    }
+
+    fn end_frame(&mut self) {
+        // Spread the remainder when the rate is not a multiple of 60.
+        let rate = self.generator.sample_rate() as u64;
+        let frames = self.frames as u64;
+        let count = (frames + 1) * rate / FRAMES_PER_SECOND as u64
+            - frames * rate / FRAMES_PER_SECOND as u64;
+
+        self.frames = (self.frames + 1) % FRAMES_PER_SECOND;
+
+        for _ in 0..count {
+            let sample = if self.on {
+                (self.generator.next_sample() * i16::MAX as f32) as i16
+            } else {
+                0
+            };
+
+            self.samples.push(sample);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Waveform;
+    use super::*;
+
+    #[test]
+    fn records_a_second_of_samples_per_60_frames() {
+        let mut recorder =
+            WavRecorder::new(ToneGenerator::new(Waveform::Square, 440.0, 1.0, 44100));
+
+        for _ in 0..60 {
+            recorder.end_frame();
+        }
+
+        assert_eq!(recorder.samples().len(), 44100);
+    }
+
+    #[test]
+    fn only_records_sound_while_on() {
+        let mut recorder = WavRecorder::new(ToneGenerator::new(Waveform::Square, 500.0, 0.5, 6000));
+
+        recorder.end_frame();
+        recorder.start();
+        recorder.end_frame();
+        recorder.stop();
+        recorder.end_frame();
+
+        let samples = recorder.samples();
+        assert_eq!(samples.len(), 300);
+        assert!(samples[..100].iter().all(|s| *s == 0));
+        assert_eq!(samples[100], i16::MAX / 2);
+        assert!(samples[100..200].iter().any(|s| *s < 0));
+        assert!(samples[200..].iter().all(|s| *s == 0));
+    }
+
+    #[test]
+    fn wav_header_describes_the_samples() {
+        let mut recorder = WavRecorder::new(ToneGenerator::new(Waveform::Sine, 440.0, 1.0, 6000));
+        recorder.start();
+        recorder.end_frame();
+
+        let wav = recorder.to_wav();
+
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(
+            u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+            6000
+        );
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(
+            u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]),
+            200
+        );
+        assert_eq!(wav.len(), 44 + 200);
+    }
+}
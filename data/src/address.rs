@@ -1,15 +1,18 @@
 //! Basic Address data type.
+//!
+//! Addresses span the full 16 bits so XO-CHIP can reach 64 KiB of memory.
+//! The classic 12 bit address of an instruction is masked out when decoding.
 //
 
 use super::byte::Byte;
 
-numeric_wrapper!(Address, u16, |x| x & 0x0FFF);
+numeric_wrapper!(Address, u16, |x| x);
 
 use std::ops::{Add, AddAssign};
 
 impl AddAssign for Address {
     fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
+        self.0 = self.0.wrapping_add(rhs.0);
     }
 }
 
@@ -18,6 +21,23 @@ impl Add<Byte> for Address {
     fn add(self, rhs: Byte) -> Self {
         let rhs: u16 = rhs.get_raw().into();
 
-        Self(self.0 + rhs)
+        Self(self.0.wrapping_add(rhs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_keeps_all_16_bits() {
+        assert_eq!(Address::new(0xF123).get_raw(), 0xF123);
+    }
+
+    #[test]
+    fn add_wraps_at_the_end_of_the_address_space() {
+        let addr = Address::new(0xFFFF) + Byte::new(2);
+
+        assert_eq!(addr, Address::new(0x0001));
     }
 }
@@ -92,4 +92,10 @@ mod tests {
 
         assert_eq!(x.get_bcd_rep(), (0, 0, 1));
     }
+
+    #[test]
+    fn add_and_sub_wrap_around() {
+        assert_eq!(Byte::new(255) + 1u8, Byte::new(0));
+        assert_eq!(Byte::new(1) - Byte::new(2), Byte::new(255));
+    }
 }
@@ -85,7 +85,7 @@ macro_rules! numeric_wrapper {
             type Output = Self;
 
             fn add(self, rhs: T) -> Self {
-                Self(self.0 + rhs.into())
+                Self(self.0.wrapping_add(rhs.into()))
             }
         }
 
@@ -93,7 +93,7 @@ macro_rules! numeric_wrapper {
             type Output = Self;
 
             fn sub(self, rhs: Self) -> Self {
-                (self.0 - rhs.0).into()
+                Self(self.0.wrapping_sub(rhs.0))
             }
         }
 
@@ -0,0 +1,320 @@
+//! ##Chip-8 Disassembler
+//!
+//! Turns a ROM back into a listing of instructions.
+//!
+//! A ROM mixes code with sprites and other data, so the disassembler walks
+//! the program like the machine would, see `instruction::Rom::walk`.
+//! Whatever is never reached is data and is printed as binary, so sprites can
+//! be made out.
+//!
+//! The targets of jumps and calls get `L` labels and the addresses loaded
+//! into I get `D` labels, e.g. `L208` and `D2EA`.
+//!
+
+extern crate data;
+extern crate instruction;
+
+use data::Address;
+use instruction::{Instruction, Rom, START};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A line of the listing.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Line {
+    /// An instruction and the word it was decoded from.
+    Code {
+        addr: u16,
+        raw: u16,
+        instruction: Instruction,
+    },
+
+    /// A byte that is never executed.
+    Data { addr: u16, byte: u8 },
+}
+
+impl Line {
+    /// Returns the address of the line.
+    ///
+    pub fn addr(&self) -> u16 {
+        match self {
+            Line::Code { addr, .. } | Line::Data { addr, .. } => *addr,
+        }
+    }
+}
+
+/// The listing of a ROM.
+///
+#[derive(Debug)]
+pub struct Disassembly {
+    lines: Vec<Line>,
+    labels: BTreeMap<u16, String>,
+}
+
+impl Disassembly {
+    /// Returns the lines of the listing in address order.
+    ///
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Returns the label of the address, if anything refers to it.
+    ///
+    ///### Arguments
+    ///
+    ///- **addr** : The address.
+    ///
+    pub fn label(&self, addr: u16) -> Option<&str> {
+        self.labels.get(&addr).map(|label| label.as_str())
+    }
+
+    /// Returns the instruction with its address replaced by a label.
+    ///
+    fn text(&self, instruction: Instruction) -> String {
+        use Instruction::*;
+
+        let label = |addr: Address| self.label(addr.get_raw());
+
+        let text = match instruction {
+            Jump(addr) => label(addr).map(|l| format!("JP {}", l)),
+            Call(addr) => label(addr).map(|l| format!("CALL {}", l)),
+            JumpPlus(addr) => label(addr).map(|l| format!("JP V0, {}", l)),
+            LoadInstr(addr) => label(addr).map(|l| format!("LD I, {}", l)),
+            LoadLongInstr(addr) => label(addr).map(|l| format!("LD I, long {}", l)),
+            _ => None,
+        };
+
+        text.unwrap_or_else(|| instruction.to_string())
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            let addr = line.addr();
+
+            if let Some(label) = self.label(addr) {
+                writeln!(f, "{}:", label)?;
+            }
+
+            match *line {
+                Line::Code {
+                    raw, instruction, ..
+                } => {
+                    let words = match instruction {
+                        Instruction::LoadLongInstr(long) => {
+                            format!("{:04X} {:04X}", raw, long.get_raw())
+                        }
+                        _ => format!("{:04X}", raw),
+                    };
+
+                    writeln!(
+                        f,
+                        "0x{:03X}  {:<9}  {}",
+                        addr,
+                        words,
+                        self.text(instruction)
+                    )?;
+                }
+                Line::Data { byte, .. } => {
+                    let pixels: String = (0..8)
+                        .map(|bit| if byte & (0x80 >> bit) != 0 { '#' } else { '.' })
+                        .collect();
+
+                    writeln!(
+                        f,
+                        "0x{:03X}  {:02X}         DB 0b{:08b}  ; {}",
+                        addr, byte, byte, pixels
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Disassembles the rom.
+///
+///### Arguments
+///
+///- **rom** : The program, as loaded at 0x200.
+///
+pub fn disassemble(rom: &[u8]) -> Disassembly {
+    use Instruction::*;
+
+    let rom = Rom::new(rom);
+
+    let mut code: BTreeMap<u16, (u16, Instruction)> = BTreeMap::new();
+    let mut covered = vec![false; rom.bytes().len()];
+    let mut branches = Vec::new();
+    let mut data = Vec::new();
+
+    rom.walk(|step| {
+        // An instruction overlapping another one is most likely a wrong
+        // guess.
+        let span: Vec<usize> = (0..step.instruction.size())
+            .filter_map(|n| rom.offset(step.addr.wrapping_add(n)))
+            .collect();
+
+        if span.iter().any(|n| covered[*n]) {
+            return false;
+        }
+
+        for n in span {
+            covered[n] = true;
+        }
+
+        code.insert(step.addr, (step.opcode.0, step.instruction));
+
+        match step.instruction {
+            Jump(target) | JumpPlus(target) | Call(target) => branches.push(target.get_raw()),
+            LoadInstr(target) | LoadLongInstr(target) => data.push(target.get_raw()),
+            _ => {}
+        }
+
+        true
+    });
+
+    let mut labels = BTreeMap::new();
+    for addr in data {
+        if rom.offset(addr).is_some() {
+            labels.insert(addr, format!("D{:03X}", addr));
+        }
+    }
+
+    // A branch into data is still a branch.
+    for addr in branches {
+        if rom.offset(addr).is_some() {
+            labels.insert(addr, format!("L{:03X}", addr));
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut offset = 0;
+
+    while offset < rom.bytes().len() {
+        let addr = START.wrapping_add(offset as u16);
+
+        match code.get(&addr) {
+            Some((raw, instruction)) => {
+                lines.push(Line::Code {
+                    addr,
+                    raw: *raw,
+                    instruction: *instruction,
+                });
+
+                offset += instruction.size() as usize;
+            }
+            None => {
+                lines.push(Line::Data {
+                    addr,
+                    byte: rom.bytes()[offset],
+                });
+
+                offset += 1;
+            }
+        }
+    }
+
+    Disassembly { lines, labels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 200: JP 204, 202: sprite, 204: LD I, 202, 206: DRW V0, V1, 2, 208: JP 208
+    const SPRITE_PROGRAM: [u8; 10] = [0x12, 0x04, 0xF0, 0x90, 0xA2, 0x02, 0xD0, 0x12, 0x12, 0x08];
+
+    fn kinds(disassembly: &Disassembly) -> Vec<(u16, bool)> {
+        disassembly
+            .lines()
+            .iter()
+            .map(|line| (line.addr(), matches!(line, Line::Code { .. })))
+            .collect()
+    }
+
+    #[test]
+    fn separates_code_from_data() {
+        let disassembly = disassemble(&SPRITE_PROGRAM);
+
+        assert_eq!(
+            kinds(&disassembly),
+            vec![
+                (0x200, true),
+                (0x202, false),
+                (0x203, false),
+                (0x204, true),
+                (0x206, true),
+                (0x208, true),
+            ]
+        );
+
+        assert_eq!(disassembly.label(0x202), Some("D202"));
+        assert_eq!(disassembly.label(0x204), Some("L204"));
+        assert_eq!(disassembly.label(0x206), None);
+    }
+
+    #[test]
+    fn follows_skips_and_calls() {
+        // 200: SE V0, 0, 202: JP 20A, 204: CALL 20C, 206: JP 206,
+        // 208: never reached, 20A: JP 20A, 20C: RET
+        let rom = [
+            0x30, 0x00, 0x12, 0x0A, 0x22, 0x0C, 0x12, 0x06, 0xFF, 0x81, 0x12, 0x0A, 0x00, 0xEE,
+        ];
+
+        let disassembly = disassemble(&rom);
+        let code: Vec<u16> = kinds(&disassembly)
+            .into_iter()
+            .filter(|(_, code)| *code)
+            .map(|(addr, _)| addr)
+            .collect();
+
+        assert_eq!(code, vec![0x200, 0x202, 0x204, 0x206, 0x20A, 0x20C]);
+    }
+
+    #[test]
+    fn skips_step_over_long_loads() {
+        // 200: SE V0, 0, 202: LD I, long 0x1234, 206: EXIT
+        let rom = [0x30, 0x00, 0xF0, 0x00, 0x12, 0x34, 0x00, 0xFD];
+
+        let disassembly = disassemble(&rom);
+
+        assert_eq!(disassembly.lines().len(), 3);
+        assert_eq!(
+            disassembly.lines()[1],
+            Line::Code {
+                addr: 0x202,
+                raw: 0xF000,
+                instruction: Instruction::LoadLongInstr(Address::new(0x1234)),
+            }
+        );
+    }
+
+    #[test]
+    fn listing_uses_labels_and_prints_data_as_binary() {
+        let listing = disassemble(&SPRITE_PROGRAM).to_string();
+        let lines: Vec<&str> = listing.lines().collect();
+
+        assert_eq!(lines[0], "0x200  1204       JP L204");
+        assert_eq!(lines[1], "D202:");
+        assert_eq!(lines[2], "0x202  F0         DB 0b11110000  ; ####....");
+        assert!(lines.contains(&"0x204  A202       LD I, D202"));
+        assert!(lines.contains(&"0x206  D012       DRW V0, V1, 2"));
+    }
+
+    #[test]
+    fn a_trailing_odd_byte_is_data() {
+        let disassembly = disassemble(&[0x00, 0xFD, 0x42]);
+
+        assert_eq!(
+            disassembly.lines()[1],
+            Line::Data {
+                addr: 0x202,
+                byte: 0x42
+            }
+        );
+    }
+}
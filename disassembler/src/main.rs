@@ -0,0 +1,48 @@
+//! Prints the listing of Chip-8 ROMs.
+//!
+//! Usage: `disassembler <file or rom name>...` or `disassembler --all` for
+//! every ROM in the library.
+//!
+
+extern crate disassembler;
+extern crate rom_library;
+
+use disassembler::disassemble;
+use rom_library::{read_rom, ROM_NAMES};
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() {
+        eprintln!("usage: disassembler <file or rom name>... | --all");
+        eprintln!("roms: {}", ROM_NAMES.join(", "));
+        process::exit(2);
+    }
+
+    let names: Vec<String> = if args.iter().any(|arg| arg == "--all") {
+        ROM_NAMES.iter().map(|name| name.to_string()).collect()
+    } else {
+        args
+    };
+
+    for (n, name) in names.iter().enumerate() {
+        let rom = match read_rom(name) {
+            Ok(rom) => rom,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+
+        if names.len() > 1 {
+            if n > 0 {
+                println!();
+            }
+            println!("; {}", name);
+        }
+
+        print!("{}", disassemble(&rom));
+    }
+}
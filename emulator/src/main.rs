@@ -3,46 +3,89 @@
 use sdl2::render::WindowCanvas;
 
 extern crate sdl2;
+use sdl2::audio::{AudioCallback, AudioSpecDesired};
 use sdl2::{event::Event, keyboard::Keycode, pixels::Color, rect::Rect};
 
+extern crate audio;
 extern crate cpu;
 extern crate framebuffer;
 extern crate keyboard;
 extern crate memory;
 extern crate model;
 extern crate program_counter;
+extern crate random;
 extern crate register_bank;
 extern crate rom_library;
 
 // We want all traits from the model in scope.
 use model::*;
 
-use cpu::VirtualMachine;
+use audio::{Buzzer, Tone, ToneGenerator, Waveform};
+use cpu::{Clock, Quirks, RealTime, Rewind, VMState, VirtualMachine, FRAMES_PER_SECOND};
 use framebuffer::FrameBuffer;
 use keyboard::Keyboard;
 use memory::Memory;
 use program_counter::ProgramCounter;
+use random::SeededRandom;
 use register_bank::RegisterBank;
 use rom_library::load_rom;
 
-type VM = VirtualMachine<Memory, ProgramCounter, RegisterBank, FrameBuffer, Keyboard>;
+type VM = VirtualMachine<
+    Memory,
+    ProgramCounter,
+    RegisterBank,
+    FrameBuffer,
+    Keyboard,
+    SeededRandom,
+    Buzzer,
+>;
+
+/// The sample rate asked from the audio device.
+///
+const SAMPLE_RATE: i32 = 44100;
+
+/// Plays the buzzer through SDL.
+///
+struct SdlTone(Tone);
+
+impl AudioCallback for SdlTone {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        self.0.fill(out);
+    }
+}
 
 /// Creates a new VirtualMachine.
 ///
-fn get_vm() -> VM {
+///### Arguments
+///
+///- **buzzer** : Where the sound timer is heard.
+///
+fn get_vm(buzzer: Buzzer) -> VM {
     VM::new(
-        Memory::new(),
+        Memory::with_size(memory::XO_CHIP_SIZE),
         ProgramCounter::new(0x200u16.into()),
         RegisterBank::new(),
         FrameBuffer::new(32),
         Keyboard::new(),
+        SeededRandom::from_time(),
+        buzzer,
+        Quirks::default(),
     )
 }
 
 fn main() {
-    let cpu_hz: usize = 480;
-    let frames_per_second: usize = 60;
-    let cycles_per_frame = cpu_hz / frames_per_second;
+    let cpu_hz: u64 = 480;
+
+    // Keep the last 10 seconds, recording every other frame with a full
+    // snapshot once per second.
+    let mut rewind = Rewind::new(300, 2, 30);
+    let mut rewinding = false;
+    let mut waiting = false;
+
+    // The frame each key was last pressed at.
+    let mut pressed_at = [0u64; 16];
 
     let sdl_context = sdl2::init().unwrap();
     let video_subsystem = sdl_context.video().unwrap();
@@ -60,14 +103,38 @@ fn main() {
     canvas.clear();
     canvas.present(); // Kind of like flushing the buffer?
 
+    let (buzzer, tone) = audio::buzzer(ToneGenerator::new(
+        Waveform::Square,
+        440.0,
+        0.25,
+        SAMPLE_RATE as u32,
+    ));
+
+    let audio_subsystem = sdl_context.audio().unwrap();
+    let desired_spec = AudioSpecDesired {
+        freq: Some(SAMPLE_RATE),
+        channels: Some(1),
+        samples: None,
+    };
+    let device = audio_subsystem
+        .open_playback(None, &desired_spec, |spec| {
+            let mut tone = tone;
+            tone.generator_mut().set_sample_rate(spec.freq as u32);
+
+            SdlTone(tone)
+        })
+        .unwrap();
+    device.resume();
+
     let mut event_pump = sdl_context.event_pump().unwrap();
-    let mut vm = get_vm();
+    let mut vm = get_vm(buzzer);
     let rom = load_rom("tetris".into()).unwrap();
-    vm.load_rom(&rom);
+    vm.load_rom(&rom).unwrap();
 
-    'running: loop {
-        let mut cycles = cycles_per_frame;
+    // The timers run at 60 Hz no matter how fast the cpu is.
+    let mut clock = Clock::new(RealTime::new(), cpu_hz);
 
+    'running: loop {
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. }
@@ -75,98 +142,115 @@ fn main() {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(Keycode::Backspace),
+                    ..
+                } => rewinding = true,
+                Event::KeyUp {
+                    keycode: Some(Keycode::Backspace),
+                    ..
+                } => {
+                    // Carry on from now rather than catching up on the time
+                    // spent rewinding.
+                    rewinding = false;
+                    clock.skip_due();
+                }
                 Event::KeyDown {
                     keycode: Some(keycode),
                     ..
                 } => {
-                    process_keycode(keycode, &mut vm);
+                    if let Some(key) = get_key(keycode) {
+                        let frame = vm.frames();
+
+                        pressed_at[key as usize] = frame;
+                        vm.queue_input(InputEvent::press(InputTime::Frame(frame), key));
+                    }
                 }
-                Event::KeyUp { .. } => {
-                    vm.keyboard.clear();
+                Event::KeyUp {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    if let Some(key) = get_key(keycode) {
+                        // Hold a tap for at least a frame so the ROM sees it.
+                        let frame = vm.frames().max(pressed_at[key as usize] + 1);
+
+                        vm.queue_input(InputEvent::release(InputTime::Frame(frame), key));
+                    }
                 }
                 _ => {}
             }
+        }
 
-            if cycles > 0 {
-                vm.execute();
-                cycles -= 1;
+        if rewinding {
+            // Holding the rewind key steps back one recorded state per frame.
+            if let Err(e) = rewind.rewind(&mut vm) {
+                eprintln!("{}", e);
+                break 'running;
+            }
+
+            // The queued events and the frames keys were pressed at are in
+            // the future of the restored state.
+            vm.clear_input();
+            pressed_at = [0; 16];
+        } else {
+            match clock.run(&mut vm) {
+                Ok(frames) => {
+                    for _ in 0..frames {
+                        rewind.record(&vm);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    break 'running;
+                }
             }
         }
 
+        // Let the player know the game is waiting on them.
+        let is_waiting = matches!(vm.state, VMState::WaitingForKey(_, _));
+        if is_waiting != waiting {
+            let title = if is_waiting {
+                "chip - 8 (press a key)"
+            } else {
+                "chip - 8"
+            };
 
-        if cycles > 0 {
-            vm.execute_cycles(cycles)
+            canvas.window_mut().set_title(title).unwrap();
+            waiting = is_waiting;
         }
 
         // This can happen on the main thread.
         draw_vm(&mut canvas, vm.get_framebuffer());
 
-        std::thread::sleep(std::time::Duration::new(
-            0,
-            1_000_000_000u32 / (frames_per_second as u32),
-        ));
+        std::thread::sleep(std::time::Duration::from_millis(1000 / FRAMES_PER_SECOND));
     }
 }
 
-/// Calls press_key on the vm for the Chip-8 key corresponding
-/// to a SDL Keycode.
+/// Gets the Chip-8 key corresponding to a SDL Keycode.
 ///
 ///### Arguments
 ///
-///- **keycode**    : The SDL Keycode that is pressed.
-///- **vm**         : The Chip8VirtualMachine
+///- **keycode**    : The SDL Keycode that is pressed or released.
 ///
-fn process_keycode(keycode: Keycode, vm: &mut VM) {
+fn get_key(keycode: Keycode) -> Option<u8> {
     match keycode {
-        Keycode::Num1 => {
-            vm.press_key(0x1);
-        }
-        Keycode::Num2 => {
-            vm.press_key(0x2);
-        }
-        Keycode::Num3 => {
-            vm.press_key(0x3);
-        }
-        Keycode::Num4 => {
-            vm.press_key(0xC);
-        }
-        Keycode::Q => {
-            vm.press_key(0x4);
-        }
-        Keycode::W => {
-            vm.press_key(0x5);
-        }
-        Keycode::E => {
-            vm.press_key(0x6);
-        }
-        Keycode::R => {
-            vm.press_key(0xD);
-        }
-        Keycode::A => {
-            vm.press_key(0x7);
-        }
-        Keycode::S => {
-            vm.press_key(0x8);
-        }
-        Keycode::D => {
-            vm.press_key(0x9);
-        }
-        Keycode::F => {
-            vm.press_key(0xE);
-        }
-        Keycode::Z => {
-            vm.press_key(0xA);
-        }
-        Keycode::X => {
-            vm.press_key(0x0);
-        }
-        Keycode::C => {
-            vm.press_key(0xB);
-        }
-        Keycode::V => {
-            vm.press_key(0xF);
-        }
-        _ => {}
+        Keycode::Num1 => Some(0x1),
+        Keycode::Num2 => Some(0x2),
+        Keycode::Num3 => Some(0x3),
+        Keycode::Num4 => Some(0xC),
+        Keycode::Q => Some(0x4),
+        Keycode::W => Some(0x5),
+        Keycode::E => Some(0x6),
+        Keycode::R => Some(0xD),
+        Keycode::A => Some(0x7),
+        Keycode::S => Some(0x8),
+        Keycode::D => Some(0x9),
+        Keycode::F => Some(0xE),
+        Keycode::Z => Some(0xA),
+        Keycode::X => Some(0x0),
+        Keycode::C => Some(0xB),
+        Keycode::V => Some(0xF),
+        _ => None,
     }
 }
 
@@ -174,9 +258,17 @@ fn process_keycode(keycode: Keycode, vm: &mut VM) {
 ///
 static BACKGROUND_COLOR: Color = Color::RGB(0, 15, 15);
 
-/// Foreground Color.
+/// Colors for the XO-CHIP plane combinations.
 ///
-static FOREGROUND_COLOR: Color = Color::RGB(128, 0, 128);
+/// Index 0 is the background, 1 the first plane (the classic foreground),
+/// 2 the second plane and 3 both planes.
+///
+static PLANE_COLORS: [Color; 4] = [
+    BACKGROUND_COLOR,
+    Color::RGB(128, 0, 128),
+    Color::RGB(0, 160, 160),
+    Color::RGB(224, 224, 224),
+];
 
 /// Draws the framebuffer to a canvas.
 ///
@@ -190,22 +282,20 @@ fn draw_vm(canvas: &mut WindowCanvas, buffer: &dyn Chip8FrameBuffer) {
     canvas.set_draw_color(BACKGROUND_COLOR);
     canvas.clear();
 
-    // Set the pixel color.
-    canvas.set_draw_color(FOREGROUND_COLOR);
-
-    let pixels: &[u64] = buffer;
-    let len = pixels.len();
-
-    for i in 0..len {
-        let line = pixels[len - 1 - i];
+    // Scale the display to fill the window in either resolution.
+    let scale = 640 / buffer.width() as u32;
 
-        let y = i as i32;
+    for y in 0..buffer.height() {
+        for x in 0..buffer.width() {
+            let color = buffer.get_color(x, y) as usize;
 
-        for x in 0..64 {
-            let pixel_mask: u64 = 1 << (63 - x);
+            if color != 0 {
+                let (px, py) = (x as u32 * scale, y as u32 * scale);
 
-            if line & pixel_mask > 0 {
-                canvas.fill_rect(Rect::new(10 * x, 10 * y, 10, 10)).unwrap();
+                canvas.set_draw_color(PLANE_COLORS[color & 0b11]);
+                canvas
+                    .fill_rect(Rect::new(px as i32, py as i32, scale, scale))
+                    .unwrap();
             }
         }
     }
@@ -6,14 +6,29 @@ mod utils;
 use data::Byte;
 use model::Chip8FrameBuffer;
 
+/// Width of the display in the low resolution mode.
+const LOW_RES_WIDTH: usize = 64;
+
+/// Number of bitplanes, XO-CHIP uses two for four colors.
+const PLANES: usize = 2;
+
 /// Framebuffer implementation.
 ///
 /// A Framebuffer contains all the data needed to draw a pixel to the screen.
-/// Each pixel is represented by a bit. The height is assumed to be 64 pixels wide.
+/// Each pixel is represented by a bit and each row by a u128, the leftmost
+/// pixel being the highest bit in use. In low resolution the display is 64
+/// pixels wide, in high resolution both dimensions are doubled.
+///
+/// The display is made up of two planes, only the first one is selected
+/// unless an XO-CHIP program asks for more.
 ///
 pub struct FrameBuffer {
+    low_res_height: usize,
+    high_res: bool,
+    width: usize,
     height: usize,
-    pixels: Vec<u64>,
+    selected: u8,
+    planes: [Vec<u128>; PLANES],
 }
 
 impl FrameBuffer {
@@ -22,13 +37,105 @@ impl FrameBuffer {
     ///
     ///### Arguments
     ///
-    ///-** height **- : The height of the buffer.
+    ///-** height **- : The height of the buffer in low resolution.
     ///
     pub fn new(height: usize) -> Self {
         FrameBuffer {
+            low_res_height: height,
+            high_res: false,
+            width: LOW_RES_WIDTH,
             height,
-            pixels: vec![0; height],
+            selected: 1,
+            planes: [vec![0; height], vec![0; height]],
+        }
+    }
+
+    /// Returns the indexes of the selected planes.
+    ///
+    fn selected_indexes(&self) -> Vec<usize> {
+        (0..PLANES)
+            .filter(|p| self.selected & (1 << p) != 0)
+            .collect()
+    }
+
+    /// Draws the rows of a sprite to the selected planes.
+    ///
+    /// The rows for each selected plane follow one another.
+    ///
+    ///###  Arguments
+    ///
+    ///- **x**            : The x coordinate for where to start drawing.
+    ///- **y**            : The y coordinate for where to start drawing.
+    ///- **rows**         : The sprite lines, leftmost pixel in the high bit.
+    ///- **sprite_width** : The number of pixels in each line.
+    ///- **clip**         : Drop pixels past the edge instead of wrapping them.
+    ///
+    fn draw_rows(
+        &mut self,
+        x: Byte,
+        y: Byte,
+        rows: &[u128],
+        sprite_width: usize,
+        clip: bool,
+    ) -> bool {
+        let selected = self.selected_indexes();
+
+        if selected.is_empty() {
+            return false;
+        }
+
+        let rows_per_plane = rows.len() / selected.len();
+        let mut has_collision = false;
+
+        for (n, plane) in selected.into_iter().enumerate() {
+            let plane_rows = &rows[n * rows_per_plane..(n + 1) * rows_per_plane];
+
+            if self.draw_plane(plane, x, y, plane_rows, sprite_width, clip) {
+                has_collision = true;
+            }
+        }
+
+        has_collision
+    }
+
+    /// Draws the rows of a sprite to a single plane.
+    ///
+    fn draw_plane(
+        &mut self,
+        plane: usize,
+        x: Byte,
+        y: Byte,
+        rows: &[u128],
+        sprite_width: usize,
+        clip: bool,
+    ) -> bool {
+        // The starting position always wraps, only the pixels of the sprite
+        // itself are subject to clipping.
+        let x_initial = x.get_raw() as usize % self.width;
+        let y_initial = y.get_raw() as usize % self.height;
+
+        let mut has_collision = false;
+
+        for (y, bits) in rows.iter().enumerate() {
+            if clip && y_initial + y >= self.height {
+                break;
+            }
+
+            let index = utils::compute_index(y_initial, y, self.height);
+
+            let sprite_line =
+                utils::get_sprite_line(*bits, sprite_width, x_initial, self.width, clip);
+
+            let cur = self.planes[plane][index];
+
+            if cur & sprite_line != 0 {
+                has_collision = true;
+            }
+
+            self.planes[plane][index] = cur ^ sprite_line;
         }
+
+        has_collision
     }
 }
 
@@ -40,57 +147,176 @@ impl Chip8FrameBuffer for FrameBuffer {
     ///- **x**        : The x coordinate for where to start drawing.
     ///- **y**        : The y coordinate for where to start drawing.
     ///- **sprite**   : A slice containing the sprite data.
+    ///- **clip**     : Drop pixels past the edge instead of wrapping them.
     ///
-    fn draw(&mut self, x: Byte, y: Byte, sprite: &[Byte]) -> bool {
-        let y_initial = y;
+    fn draw(&mut self, x: Byte, y: Byte, sprite: &[Byte], clip: bool) -> bool {
+        let rows: Vec<u128> = sprite.iter().map(|b| b.get_raw() as u128).collect();
 
-        let mut has_collision = false;
+        self.draw_rows(x, y, &rows, 8, clip)
+    }
+
+    /// Draws a 16x16 sprite to this buffer.
+    ///
+    ///###  Arguments
+    ///
+    ///- **x**        : The x coordinate for where to start drawing.
+    ///- **y**        : The y coordinate for where to start drawing.
+    ///- **sprite**   : A slice containing two bytes per row.
+    ///- **clip**     : Drop pixels past the edge instead of wrapping them.
+    ///
+    fn draw_large(&mut self, x: Byte, y: Byte, sprite: &[Byte], clip: bool) -> bool {
+        let rows: Vec<u128> = sprite
+            .chunks(2)
+            .map(|pair| {
+                let left = pair[0].get_raw() as u128;
+                let right = pair.get(1).map_or(0, |b| b.get_raw() as u128);
+
+                left << 8 | right
+            })
+            .collect();
+
+        self.draw_rows(x, y, &rows, 16, clip)
+    }
+
+    /// Clears the selected planes of the frame buffer.
+    ///
+    fn clear(&mut self) {
+        let height = self.height;
+
+        for plane in self.selected_indexes() {
+            self.planes[plane] = vec![0; height];
+        }
+    }
 
-        for y in 0..sprite.len() as usize {
-            let index = utils::compute_index(y_initial.get_raw(), y, self.height);
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
 
-            let sprite_line = utils::get_sprite_line(sprite, x.get_raw(), y);
+    fn get_pixel(&self, x: usize, y: usize) -> bool {
+        self.get_color(x, y) != 0
+    }
+
+    fn get_color(&self, x: usize, y: usize) -> u8 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
 
-            self.pixels[index] = {
-                let cur = self.pixels[index];
-                let new = cur ^ sprite_line;
+        let shift = self.width - 1 - x;
+
+        self.planes
+            .iter()
+            .enumerate()
+            .map(|(n, plane)| (((plane[y] >> shift) & 1) as u8) << n)
+            .fold(0, |color, bit| color | bit)
+    }
+
+    fn set_color(&mut self, x: usize, y: usize, color: u8) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
 
-                if (new & cur != cur) | (sprite_line & new != sprite_line) {
-                    has_collision = true
-                }
+        let bit = 1u128 << (self.width - 1 - x);
 
-                new
+        for (n, plane) in self.planes.iter_mut().enumerate() {
+            if color & (1 << n) != 0 {
+                plane[y] |= bit;
+            } else {
+                plane[y] &= !bit;
             }
         }
+    }
 
-        has_collision
+    fn select_planes(&mut self, mask: u8) {
+        self.selected = mask & ((1 << PLANES) - 1);
     }
 
-    /// Clears the frame buffer.
-    ///
-    fn clear(&mut self) {
+    fn selected_planes(&self) -> u8 {
+        self.selected
+    }
+
+    fn set_high_res(&mut self, high: bool) {
+        self.high_res = high;
+
+        if high {
+            self.width = LOW_RES_WIDTH * 2;
+            self.height = self.low_res_height * 2;
+        } else {
+            self.width = LOW_RES_WIDTH;
+            self.height = self.low_res_height;
+        }
+
+        // Changing the resolution clears every plane, not just the selected ones.
         let height = self.height;
-        self.pixels = vec![0; height];
+
+        for plane in self.planes.iter_mut() {
+            *plane = vec![0; height];
+        }
+    }
+
+    fn is_high_res(&self) -> bool {
+        self.high_res
+    }
+
+    fn scroll_down(&mut self, n: usize) {
+        let n = n.min(self.height);
+
+        for plane in self.selected_indexes() {
+            let rows = &mut self.planes[plane];
+
+            rows.rotate_right(n);
+            rows[..n].iter_mut().for_each(|row| *row = 0);
+        }
+    }
+
+    fn scroll_up(&mut self, n: usize) {
+        let n = n.min(self.height);
+        let height = self.height;
+
+        for plane in self.selected_indexes() {
+            let rows = &mut self.planes[plane];
+
+            rows.rotate_left(n);
+            rows[height - n..].iter_mut().for_each(|row| *row = 0);
+        }
+    }
+
+    fn scroll_right(&mut self, n: usize) {
+        for plane in self.selected_indexes() {
+            for row in self.planes[plane].iter_mut() {
+                *row = row.checked_shr(n as u32).unwrap_or(0);
+            }
+        }
+    }
+
+    fn scroll_left(&mut self, n: usize) {
+        let mask = utils::row_mask(self.width);
+
+        for plane in self.selected_indexes() {
+            for row in self.planes[plane].iter_mut() {
+                *row = row.checked_shl(n as u32).unwrap_or(0) & mask;
+            }
+        }
     }
 }
 
 use std::fmt;
-use std::{ops::Deref, result::Result};
+use std::result::Result;
 
 impl fmt::Debug for FrameBuffer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        self.pixels
-            .iter()
-            .map(|x| f.write_fmt(format_args!("{:064b}\r\n", x)))
-            .fold(Ok(()), |acc, x| acc.and(x))
-    }
-}
+        for y in 0..self.height {
+            for x in 0..self.width {
+                f.write_fmt(format_args!("{}", self.get_color(x, y)))?;
+            }
 
-impl Deref for FrameBuffer {
-    type Target = [u64];
+            f.write_str("\r\n")?;
+        }
 
-    fn deref(&self) -> &Self::Target {
-        &self.pixels
+        Ok(())
     }
 }
 
@@ -101,7 +327,7 @@ mod tests {
     fn new_should_have_pixels_length_as_height() {
         let fb = FrameBuffer::new(32);
 
-        assert_eq!(fb.pixels.len(), 32);
+        assert_eq!(fb.planes[0].len(), 32);
     }
 
     #[test]
@@ -130,7 +356,7 @@ mod tests {
         let fb = {
             let mut fb = FrameBuffer::new(32);
 
-            let collision = fb.draw(0.into(), 0.into(), &get_sprite()[0..]);
+            let collision = fb.draw(0.into(), 0.into(), &get_sprite()[0..], false);
 
             assert!(!collision);
 
@@ -138,19 +364,19 @@ mod tests {
         };
 
         assert_eq!(
-            fb[31],
+            fb.planes[0][0],
             0b1000000100000000000000000000000000000000000000000000000000000000
         );
         assert_eq!(
-            fb[30],
+            fb.planes[0][1],
             0b0100001000000000000000000000000000000000000000000000000000000000
         );
         assert_eq!(
-            fb[29],
+            fb.planes[0][2],
             0b0010010000000000000000000000000000000000000000000000000000000000
         );
         assert_eq!(
-            fb[28],
+            fb.planes[0][3],
             0b0001100000000000000000000000000000000000000000000000000000000000
         );
     }
@@ -160,9 +386,148 @@ mod tests {
         let mut fb = FrameBuffer::new(32);
         let sprite = get_sprite();
 
-        fb.draw(0.into(), 0.into(), &sprite[0..]);
-        let collision = fb.draw(0.into(), 0.into(), &sprite[0..]);
+        fb.draw(0.into(), 0.into(), &sprite[0..], false);
+        let collision = fb.draw(0.into(), 0.into(), &sprite[0..], false);
 
         assert!(collision);
     }
+
+    #[test]
+    fn draw_wraps_sprites_past_the_edge() {
+        let mut fb = FrameBuffer::new(32);
+
+        fb.draw(60.into(), 30.into(), &get_sprite()[0..], false);
+
+        assert_eq!(fb.planes[0][30], 0b1000u128 | (0b0001u128 << 60));
+        assert_eq!(fb.planes[0][0], 0b0010u128 | (0b0100u128 << 60));
+    }
+
+    #[test]
+    fn draw_clips_sprites_past_the_edge() {
+        let mut fb = FrameBuffer::new(32);
+
+        fb.draw(60.into(), 30.into(), &get_sprite()[0..], true);
+
+        assert_eq!(fb.planes[0][30], 0b1000);
+        assert_eq!(fb.planes[0][31], 0b0100);
+        assert_eq!(fb.planes[0][0], 0);
+        assert_eq!(fb.planes[0][1], 0);
+    }
+
+    #[test]
+    fn draw_wraps_the_starting_position() {
+        let mut fb = FrameBuffer::new(32);
+
+        fb.draw(64.into(), 32.into(), &get_sprite()[0..], true);
+
+        assert_eq!(
+            fb.planes[0][0],
+            0b1000000100000000000000000000000000000000000000000000000000000000
+        );
+    }
+
+    #[test]
+    fn set_high_res_doubles_the_dimensions_and_clears() {
+        let mut fb = FrameBuffer::new(32);
+
+        fb.draw(0.into(), 0.into(), &get_sprite()[0..], false);
+        fb.set_high_res(true);
+
+        assert_eq!((fb.width(), fb.height()), (128, 64));
+        assert!(fb.planes[0].iter().all(|row| *row == 0));
+
+        fb.set_high_res(false);
+
+        assert_eq!((fb.width(), fb.height()), (64, 32));
+    }
+
+    #[test]
+    fn draw_large_draws_16_pixel_rows() {
+        let mut fb = FrameBuffer::new(32);
+        fb.set_high_res(true);
+
+        let sprite: Vec<Byte> = [0xFFu8, 0x01]
+            .iter()
+            .cycle()
+            .take(32)
+            .map(|b| (*b).into())
+            .collect();
+
+        fb.draw_large(112.into(), 0.into(), &sprite, false);
+
+        for y in 0..16 {
+            assert!(fb.get_pixel(112, y));
+            assert!(!fb.get_pixel(126, y));
+            assert!(fb.get_pixel(127, y));
+        }
+        assert!(!fb.get_pixel(112, 16));
+    }
+
+    #[test]
+    fn scrolling_moves_pixels_and_drops_the_ones_past_the_edge() {
+        let mut fb = FrameBuffer::new(32);
+
+        fb.draw(0.into(), 0.into(), &get_sprite()[0..], false);
+
+        fb.scroll_down(2);
+        assert!(fb.get_pixel(0, 2));
+        assert!(!fb.get_pixel(0, 0));
+
+        fb.scroll_right(4);
+        assert!(fb.get_pixel(4, 2));
+        assert!(!fb.get_pixel(0, 2));
+
+        fb.scroll_left(8);
+        assert!(!fb.get_pixel(0, 2));
+        assert!(fb.get_pixel(3, 2));
+    }
+
+    #[test]
+    fn draw_splits_sprite_data_between_the_selected_planes() {
+        let mut fb = FrameBuffer::new(32);
+        let sprite: [Byte; 2] = [0b10000000u8.into(), 0b11000000u8.into()];
+
+        fb.select_planes(0b11);
+        fb.draw(0.into(), 0.into(), &sprite, false);
+
+        assert_eq!(fb.get_color(0, 0), 0b11);
+        assert_eq!(fb.get_color(1, 0), 0b10);
+        assert_eq!(fb.get_color(0, 1), 0);
+    }
+
+    #[test]
+    fn clear_only_clears_the_selected_planes() {
+        let mut fb = FrameBuffer::new(32);
+        let sprite: [Byte; 2] = [0b10000000u8.into(), 0b10000000u8.into()];
+
+        fb.select_planes(0b11);
+        fb.draw(0.into(), 0.into(), &sprite, false);
+
+        fb.select_planes(0b10);
+        fb.clear();
+
+        assert_eq!(fb.get_color(0, 0), 0b01);
+    }
+
+    #[test]
+    fn scroll_up_moves_rows_towards_the_top() {
+        let mut fb = FrameBuffer::new(32);
+
+        fb.draw(0.into(), 3.into(), &get_sprite()[0..1], false);
+        fb.scroll_up(2);
+
+        assert!(fb.get_pixel(0, 1));
+        assert!(!fb.get_pixel(0, 3));
+    }
+
+    #[test]
+    fn set_color_writes_every_plane() {
+        let mut fb = FrameBuffer::new(32);
+
+        fb.set_color(5, 7, 0b10);
+        assert_eq!(fb.get_color(5, 7), 0b10);
+
+        fb.set_color(5, 7, 0b01);
+        assert_eq!(fb.get_color(5, 7), 0b01);
+    }
 }
@@ -1,4 +1,4 @@
-/// Computes an index based on initial y, current y, and a height.
+/// Computes the row index based on initial y and current y.
 ///
 /// The height is used to wrap around.
 ///
@@ -7,20 +7,47 @@ pub fn compute_index<YInitial: Into<usize>, YCurrent: Into<usize>>(
     y_current: YCurrent,
     height: usize,
 ) -> usize {
-    (height - 1)
-        .wrapping_sub(y_initial.into() + y_current.into())
+    (y_initial.into() + y_current.into())
         .checked_rem(height)
         .unwrap_or(0)
 }
 
-/// Gets the sprite_line from the memory slice.
+/// Returns a mask with the lowest `width` bits set.
 ///
-/// The line is returned in the right position as a u64 to be
-/// written to the display.
+pub fn row_mask(width: usize) -> u128 {
+    if width >= 128 {
+        u128::MAX
+    } else {
+        (1u128 << width) - 1
+    }
+}
+
+/// Positions a line of sprite data in a row of the display.
+///
+/// The pixel in column `c` of the display is bit `width - 1 - c` of the row.
+/// When `clip` is set the pixels shifted past the right edge are dropped,
+/// otherwise they wrap around to the left.
+///
+///### Arguments
+///
+///- **bits**         : The sprite line, the leftmost pixel being the high bit.
+///- **sprite_width** : The number of pixels in the sprite line.
+///- **x**            : The column the sprite starts at.
+///- **width**        : The width of the display.
+///- **clip**         : Drop pixels instead of wrapping them.
 ///
-pub fn get_sprite_line<X: Into<u32>, Y: Into<usize>>(mem_slice: &[data::Byte], x: X, y: Y) -> u64 {
-    let raw: u8 = mem_slice[y.into()].into();
-    let line = raw as u64;
+pub fn get_sprite_line(
+    bits: u128,
+    sprite_width: usize,
+    x: usize,
+    width: usize,
+    clip: bool,
+) -> u128 {
+    let line = bits << (width - sprite_width);
 
-    line.rotate_right(8).rotate_right(x.into())
+    if clip || x == 0 {
+        line >> x
+    } else {
+        ((line >> x) | (line << (width - x))) & row_mask(width)
+    }
 }
@@ -0,0 +1,183 @@
+//! Turns instructions back into the words they are decoded from.
+//!
+
+use super::Instruction;
+use data::Address;
+use model::Register;
+use std::fmt;
+
+/// An instruction that can not be written as an opcode.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EncodeError {
+    /// The address does not fit in the 12 bits of the instruction.
+    AddressTooLarge(Instruction),
+
+    /// The instruction encodes to a word that decodes as a different
+    /// instruction, e.g. `Sys(0x0E0)` is `00E0`, which is `Cls`, or
+    /// `Invalid(0x1200)`, which is a jump.
+    Ambiguous(Instruction, u16),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::AddressTooLarge(instruction) => {
+                write!(f, "the address of {} does not fit in 12 bits", instruction)
+            }
+            EncodeError::Ambiguous(instruction, word) => write!(
+                f,
+                "{} encodes to {:#06X}, which is {}",
+                instruction,
+                word,
+                Instruction::new(*word)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl Instruction {
+    /// Returns the bytes of the instruction, as they are laid out in memory.
+    ///
+    /// This is the inverse of `Instruction::from_words`: the result is 4
+    /// bytes for `LoadLongInstr` and 2 bytes otherwise.
+    ///
+    pub fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        use Instruction::*;
+
+        let x = |r: Register| (r as u16) << 8;
+        let y = |r: Register| (r as u16) << 4;
+        let nnn = |addr: Address| {
+            if addr.get_raw() > 0x0FFF {
+                Err(EncodeError::AddressTooLarge(*self))
+            } else {
+                Ok(addr.get_raw())
+            }
+        };
+
+        let word = match *self {
+            Invalid(raw) => raw,
+            Sys(addr) => nnn(addr)?,
+            Cls => 0x00E0,
+            Return => 0x00EE,
+            ScrollDown(n) => 0x00C0 | n.get_raw() as u16,
+            ScrollUp(n) => 0x00D0 | n.get_raw() as u16,
+            ScrollRight => 0x00FB,
+            ScrollLeft => 0x00FC,
+            Exit => 0x00FD,
+            LowRes => 0x00FE,
+            HighRes => 0x00FF,
+            Jump(addr) => 0x1000 | nnn(addr)?,
+            Call(addr) => 0x2000 | nnn(addr)?,
+            SkipEqualByte(vx, b) => 0x3000 | x(vx) | b.get_raw() as u16,
+            SkipNotEqualByte(vx, b) => 0x4000 | x(vx) | b.get_raw() as u16,
+            SkipEqualReg(vx, vy) => 0x5000 | x(vx) | y(vy),
+            SaveRange(vx, vy) => 0x5002 | x(vx) | y(vy),
+            LoadRange(vx, vy) => 0x5003 | x(vx) | y(vy),
+            Load(vx, b) => 0x6000 | x(vx) | b.get_raw() as u16,
+            Add(vx, b) => 0x7000 | x(vx) | b.get_raw() as u16,
+            LoadFromReg(vx, vy) => 0x8000 | x(vx) | y(vy),
+            Or(vx, vy) => 0x8001 | x(vx) | y(vy),
+            And(vx, vy) => 0x8002 | x(vx) | y(vy),
+            XOr(vx, vy) => 0x8003 | x(vx) | y(vy),
+            AddReg(vx, vy) => 0x8004 | x(vx) | y(vy),
+            SubReg(vx, vy) => 0x8005 | x(vx) | y(vy),
+            ShiftRight(vx, vy) => 0x8006 | x(vx) | y(vy),
+            ReverseSub(vx, vy) => 0x8007 | x(vx) | y(vy),
+            ShiftLeft(vx, vy) => 0x800E | x(vx) | y(vy),
+            SkipNotEqualReg(vx, vy) => 0x9000 | x(vx) | y(vy),
+            LoadInstr(addr) => 0xA000 | nnn(addr)?,
+            LoadLongInstr(addr) => {
+                let [high, low] = addr.get_raw().to_be_bytes();
+                return Ok(vec![0xF0, 0x00, high, low]);
+            }
+            JumpPlus(addr) => 0xB000 | nnn(addr)?,
+            Rand(vx, b) => 0xC000 | x(vx) | b.get_raw() as u16,
+            Draw(vx, vy, n) => 0xD000 | x(vx) | y(vy) | n.get_raw() as u16,
+            SkipPressed(vx) => 0xE09E | x(vx),
+            SkipNotPressed(vx) => 0xE0A1 | x(vx),
+            SelectPlanes(n) => 0xF001 | (n.get_raw() as u16) << 8,
+            LoadAudioPattern => 0xF002,
+            LoadDelayTimer(vx) => 0xF007 | x(vx),
+            WaitForKey(vx) => 0xF00A | x(vx),
+            SetDelayTimer(vx) => 0xF015 | x(vx),
+            SetSoundTimer(vx) => 0xF018 | x(vx),
+            IncrementAddress(vx) => 0xF01E | x(vx),
+            LoadSpriteAddress(vx) => 0xF029 | x(vx),
+            LoadBigSpriteAddress(vx) => 0xF030 | x(vx),
+            LoadBCD(vx) => 0xF033 | x(vx),
+            SetPitch(vx) => 0xF03A | x(vx),
+            CopyToRam(vx) => 0xF055 | x(vx),
+            CopyToRegisters(vx) => 0xF065 | x(vx),
+            SaveFlags(vx) => 0xF075 | x(vx),
+            LoadFlags(vx) => 0xF085 | x(vx),
+        };
+
+        // Only Sys and Invalid can hold a word meaning something else.
+        if Instruction::new(word) != *self {
+            return Err(EncodeError::Ambiguous(*self, word));
+        }
+
+        Ok(word.to_be_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Instruction::*;
+
+    #[test]
+    fn every_word_survives_a_round_trip() {
+        for word in 0..=u16::MAX {
+            let instruction = Instruction::new(word);
+            let bytes = match instruction.encode() {
+                Ok(bytes) => bytes,
+                Err(e) => panic!("{:#06X}: {}", word, e),
+            };
+
+            assert_eq!(u16::from_be_bytes([bytes[0], bytes[1]]), word);
+        }
+    }
+
+    #[test]
+    fn every_long_load_survives_a_round_trip() {
+        for addr in 0..=u16::MAX {
+            let instruction = Instruction::from_words(0xF000, addr);
+            let bytes = instruction.encode().unwrap();
+
+            assert_eq!(bytes.len(), instruction.size() as usize);
+            assert_eq!(
+                Instruction::from_words(
+                    u16::from_be_bytes([bytes[0], bytes[1]]),
+                    u16::from_be_bytes([bytes[2], bytes[3]])
+                ),
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn encode_rejects_addresses_beyond_12_bits() {
+        let jump = Jump(Address::new(0x1000));
+
+        assert_eq!(jump.encode(), Err(EncodeError::AddressTooLarge(jump)));
+        assert!(LoadInstr(Address::new(0xFFFF)).encode().is_err());
+        assert_eq!(Call(Address::new(0xFFF)).encode(), Ok(vec![0x2F, 0xFF]));
+    }
+
+    #[test]
+    fn encode_rejects_words_that_decode_differently() {
+        assert_eq!(
+            Sys(Address::new(0x0E0)).encode(),
+            Err(EncodeError::Ambiguous(Sys(Address::new(0x0E0)), 0x00E0))
+        );
+        assert_eq!(
+            Invalid(0x1200).encode(),
+            Err(EncodeError::Ambiguous(Invalid(0x1200), 0x1200))
+        );
+        assert_eq!(Invalid(0x5121).encode(), Ok(vec![0x51, 0x21]));
+    }
+}
@@ -11,14 +11,33 @@
 //!
 //! Otherwise nibble 4 is used to identify the instruction.
 //!
+//! Along with the original instruction set the SUPER-CHIP 1.1 extensions
+//! (scrolling, resolution switching, large sprites and RPL flags) are decoded.
+//!
+//! The XO-CHIP extensions are decoded as well. Note that `F000 NNNN` is the
+//! only instruction that is 4 bytes long, use `Instruction::from_words` to
+//! decode it along with its address.
+//!
+//! `Instruction::encode` goes the other way and returns the bytes of an
+//! instruction.
+//!
+//! `Rom::walk` finds the code of a ROM, for the tools that work on a whole
+//! program rather than on one instruction at a time.
+//!
 
 extern crate data;
 extern crate model;
 
+mod encode;
 mod utils;
+mod walk;
+
+pub use encode::EncodeError;
+pub use walk::{Rom, Step, START};
 
 use data::{Address, Byte, Nibble};
 use model::Register;
+use std::fmt;
 
 /// First instruction register argument.
 type Vx = Register;
@@ -45,6 +64,27 @@ pub enum Instruction {
     /// Return from a subroutine
     Return, // 00EE - RET
 
+    /// Scrolls the display down by n pixels.
+    ScrollDown(Nibble), // 00Cn - SCD nibble
+
+    /// Scrolls the display up by n pixels.
+    ScrollUp(Nibble), // 00Dn - SCU nibble
+
+    /// Scrolls the display right by 4 pixels.
+    ScrollRight, // 00FB - SCR
+
+    /// Scrolls the display left by 4 pixels.
+    ScrollLeft, // 00FC - SCL
+
+    /// Exits the interpreter.
+    Exit, // 00FD - EXIT
+
+    /// Switches the display to the 64x32 low resolution mode.
+    LowRes, // 00FE - LOW
+
+    /// Switches the display to the 128x64 high resolution mode.
+    HighRes, // 00FF - HIGH
+
     /// Jumps to location nnn.
     Jump(Address), // 1nnn - JP addr
 
@@ -60,6 +100,14 @@ pub enum Instruction {
     /// Skips the next isntruction if Vx == Vy
     SkipEqualReg(Vx, Vy), // 5xy0 - SE Vx, Vy
 
+    /// Stores the registers Vx through Vy in memory starting at I.
+    /// The range is walked backwards when x > y. I is not changed.
+    SaveRange(Vx, Vy), // 5xy2 - SAVE Vx - Vy
+
+    /// Loads the registers Vx through Vy from memory starting at I.
+    /// The range is walked backwards when x > y. I is not changed.
+    LoadRange(Vx, Vy), // 5xy3 - LOAD Vx - Vy
+
     /// Loads the value Byte into Register Vx.
     Load(Vx, Byte), // 6xkk - LD Vx, byte
 
@@ -87,17 +135,18 @@ pub enum Instruction {
     /// otherwise 0
     SubReg(Vx, Vy), // 8xy5 - SUB Vx, Vy
 
-    /// Sets the register Vx to the result of shifting Vx right 1.
-    /// If the LSB of Vx is 1, VF = 1.
-    ShiftRight(Vx), // 8xy6 - SHR Vx {, Vy}
+    /// Sets the register Vx to the result of shifting Vx (or Vy, depending
+    /// on the quirk) right 1. VF is set to the bit shifted out.
+    ShiftRight(Vx, Vy), // 8xy6 - SHR Vx {, Vy}
 
     /// Sets the register Vx to the difference of Vx from Vy
     /// If Vy > Vx then VF is set to 1,
     /// otherwise 0
     ReverseSub(Vx, Vy), // 8xy7 - SUBN Vx, Vy
 
-    /// Sets the register Vx to the result of shfit the contents of Vx left 1.
-    ShiftLeft(Vx), // 8xy8 - SHL Vx {, Vy}
+    /// Sets the register Vx to the result of shifting the contents of Vx
+    /// (or Vy, depending on the quirk) left 1. VF is set to the bit shifted out.
+    ShiftLeft(Vx, Vy), // 8xyE - SHL Vx {, Vy}
 
     /// Skip the next instruction if the contents of Vx and Vy are not equal.
     SkipNotEqualReg(Vx, Vy), // 9xy0 - SNE Vx, Vy
@@ -105,15 +154,30 @@ pub enum Instruction {
     /// Stores the provided address in the address register.
     LoadInstr(Address), // Annn - LD I, Addr
 
+    /// Stores the 16 bit address following the instruction in the
+    /// address register.
+    LoadLongInstr(Address), // F000 nnnn - LD I, long addr
+
+    /// Selects the bitplanes that drawing, clearing and scrolling affect.
+    SelectPlanes(Nibble), // Fn01 - PLANE n
+
+    /// Loads the 16 byte audio pattern starting at I.
+    LoadAudioPattern, // F002 - AUDIO
+
+    /// Sets the playback pitch of the audio pattern to Vx.
+    SetPitch(Vx), // Fx3A - PITCH Vx
+
     /// Jump to the location determined by V0 + Addr.
+    /// With the jump quirk the register is Vx, x being the high nibble of addr.
     JumpPlus(Address), // Bnnn - JP V0, addr
 
     /// Sets Vx to the bitwise and of a random number between 0 and 255
     /// and Byte.
-    Rand(Vx, Byte), // Cxkk - RNX Vx, byte
+    Rand(Vx, Byte), // Cxkk - RND Vx, byte
 
     /// Draws an n-byte sprite starting at memory location I at position
     /// (Vx, Vy). Sets VF to 1 if there is a collision.
+    /// When n is 0 a 16x16 sprite (32 bytes) is drawn instead.
     Draw(Vx, Vy, Nibble), // Dxyn - DRW Vx, Vy, nibble
 
     /// Skips the next instruction if the key with value Vx is pressed.
@@ -141,6 +205,10 @@ pub enum Instruction {
     /// corresponding to the value of Vx.
     LoadSpriteAddress(Vx), // Fx29 - LD F, Vx
 
+    /// Sets the address register to the location of the 10 byte high
+    /// resolution sprite corresponding to the value of Vx.
+    LoadBigSpriteAddress(Vx), // Fx30 - LD HF, Vx
+
     /// Loads the BCD represtation of Vx in memory locations I, I + 1, I + 2
     LoadBCD(Vx), // Fx33 - LD B, Vx
 
@@ -151,6 +219,70 @@ pub enum Instruction {
     /// Copies values from memory starting at I
     /// into registers V0 through Vx.
     CopyToRegisters(Vx), // Fx65 - LD Vx, [I]
+
+    /// Stores registers V0 through Vx in the RPL user flags.
+    SaveFlags(Vx), // Fx75 - LD R, Vx
+
+    /// Loads registers V0 through Vx from the RPL user flags.
+    LoadFlags(Vx), // Fx85 - LD Vx, R
+}
+
+/// The kind of an instruction, without its arguments.
+///
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum InstructionKind {
+    Invalid,
+    Sys,
+    Cls,
+    Return,
+    ScrollDown,
+    ScrollUp,
+    ScrollRight,
+    ScrollLeft,
+    Exit,
+    LowRes,
+    HighRes,
+    Jump,
+    Call,
+    SkipEqualByte,
+    SkipNotEqualByte,
+    SkipEqualReg,
+    SaveRange,
+    LoadRange,
+    Load,
+    Add,
+    LoadFromReg,
+    Or,
+    And,
+    XOr,
+    AddReg,
+    SubReg,
+    ShiftRight,
+    ReverseSub,
+    ShiftLeft,
+    SkipNotEqualReg,
+    LoadInstr,
+    LoadLongInstr,
+    SelectPlanes,
+    LoadAudioPattern,
+    SetPitch,
+    JumpPlus,
+    Rand,
+    Draw,
+    SkipPressed,
+    SkipNotPressed,
+    LoadDelayTimer,
+    WaitForKey,
+    SetDelayTimer,
+    SetSoundTimer,
+    IncrementAddress,
+    LoadSpriteAddress,
+    LoadBigSpriteAddress,
+    LoadBCD,
+    CopyToRam,
+    CopyToRegisters,
+    SaveFlags,
+    LoadFlags,
 }
 
 impl Instruction {
@@ -163,6 +295,17 @@ impl Instruction {
         match instr {
             0x00E0 => Cls,
             0x00EE => Return,
+            0x00FB => ScrollRight,
+            0x00FC => ScrollLeft,
+            0x00FD => Exit,
+            0x00FE => LowRes,
+            0x00FF => HighRes,
+
+            _ if instr & 0xFFF0 == 0x00C0 => ScrollDown(nibble),
+            _ if instr & 0xFFF0 == 0x00D0 => ScrollUp(nibble),
+
+            0xF000 => LoadLongInstr(Address::new(0)),
+            0xF002 => LoadAudioPattern,
 
             _ => match instr & 0xF000 {
                 0x0000 => Sys(addr),
@@ -170,7 +313,12 @@ impl Instruction {
                 0x2000 => Call(addr),
                 0x3000 => SkipEqualByte(vx, byte),
                 0x4000 => SkipNotEqualByte(vx, byte),
-                0x5000 => SkipEqualReg(vx, vy),
+                0x5000 => match instr & 0x000F {
+                    0x0000 => SkipEqualReg(vx, vy),
+                    0x0002 => SaveRange(vx, vy),
+                    0x0003 => LoadRange(vx, vy),
+                    _ => Invalid(instr),
+                },
                 0x6000 => Load(vx, byte),
                 0x7000 => Add(vx, byte),
                 0x8000 => match instr & 0x000F {
@@ -180,12 +328,15 @@ impl Instruction {
                     0x0003 => XOr(vx, vy),
                     0x0004 => AddReg(vx, vy),
                     0x0005 => SubReg(vx, vy),
-                    0x0006 => ShiftRight(vx),
+                    0x0006 => ShiftRight(vx, vy),
                     0x0007 => ReverseSub(vx, vy),
-                    0x000E => ShiftLeft(vx),
+                    0x000E => ShiftLeft(vx, vy),
+                    _ => Invalid(instr),
+                },
+                0x9000 => match instr & 0x000F {
+                    0x0000 => SkipNotEqualReg(vx, vy),
                     _ => Invalid(instr),
                 },
-                0x9000 => SkipNotEqualReg(vx, vy),
                 0xA000 => LoadInstr(addr),
                 0xB000 => JumpPlus(addr),
                 0xC000 => Rand(vx, byte),
@@ -196,19 +347,320 @@ impl Instruction {
                     _ => Invalid(instr),
                 },
                 0xF000 => match instr & 0x00FF {
+                    0x0001 => SelectPlanes(Nibble::new(vx as u8)),
                     0x0007 => LoadDelayTimer(vx),
                     0x000A => WaitForKey(vx),
                     0x0015 => SetDelayTimer(vx),
                     0x0018 => SetSoundTimer(vx),
                     0x001E => IncrementAddress(vx),
                     0x0029 => LoadSpriteAddress(vx),
+                    0x0030 => LoadBigSpriteAddress(vx),
+                    0x003A => SetPitch(vx),
                     0x0033 => LoadBCD(vx),
                     0x0055 => CopyToRam(vx),
                     0x0065 => CopyToRegisters(vx),
+                    0x0075 => SaveFlags(vx),
+                    0x0085 => LoadFlags(vx),
                     _ => Invalid(instr),
                 },
                 _ => Invalid(instr),
             },
         }
     }
+
+    /// Creates a new instruction from the raw word at the program counter
+    /// and the word that follows it.
+    ///
+    /// The second word is only used by `F000 NNNN`, which takes it as its
+    /// address.
+    ///
+    ///### Arguments
+    ///
+    ///- **instr** : The raw instruction.
+    ///- **next**  : The raw word following the instruction.
+    ///
+    pub fn from_words(instr: RawInstruction, next: RawInstruction) -> Instruction {
+        match Instruction::new(instr) {
+            Instruction::LoadLongInstr(_) => Instruction::LoadLongInstr(Address::new(next)),
+            instruction => instruction,
+        }
+    }
+
+    /// Returns the number of bytes the instruction takes up in memory.
+    ///
+    pub fn size(&self) -> u16 {
+        match self {
+            Instruction::LoadLongInstr(_) => 4,
+            _ => 2,
+        }
+    }
+
+    /// Returns a flag indicating if the instruction is the last of a block of
+    /// straight-line code.
+    ///
+    /// Besides the branches, `Draw` and the instructions that write memory end
+    /// blocks, so a block never runs past a write to its own code.
+    ///
+    pub fn ends_block(&self) -> bool {
+        use Instruction::*;
+
+        matches!(
+            self,
+            Return
+                | Exit
+                | Jump(_)
+                | Call(_)
+                | JumpPlus(_)
+                | SkipEqualByte(..)
+                | SkipNotEqualByte(..)
+                | SkipEqualReg(..)
+                | SkipNotEqualReg(..)
+                | SkipPressed(_)
+                | SkipNotPressed(_)
+                | Draw(..)
+                | WaitForKey(_)
+                | LoadBCD(_)
+                | CopyToRam(_)
+                | SaveRange(..)
+                | Invalid(_)
+        )
+    }
+
+    /// Returns the kind of the instruction.
+    ///
+    pub fn kind(&self) -> InstructionKind {
+        use Instruction::*;
+
+        match self {
+            Invalid(..) => InstructionKind::Invalid,
+            Sys(..) => InstructionKind::Sys,
+            Cls => InstructionKind::Cls,
+            Return => InstructionKind::Return,
+            ScrollDown(..) => InstructionKind::ScrollDown,
+            ScrollUp(..) => InstructionKind::ScrollUp,
+            ScrollRight => InstructionKind::ScrollRight,
+            ScrollLeft => InstructionKind::ScrollLeft,
+            Exit => InstructionKind::Exit,
+            LowRes => InstructionKind::LowRes,
+            HighRes => InstructionKind::HighRes,
+            Jump(..) => InstructionKind::Jump,
+            Call(..) => InstructionKind::Call,
+            SkipEqualByte(..) => InstructionKind::SkipEqualByte,
+            SkipNotEqualByte(..) => InstructionKind::SkipNotEqualByte,
+            SkipEqualReg(..) => InstructionKind::SkipEqualReg,
+            SaveRange(..) => InstructionKind::SaveRange,
+            LoadRange(..) => InstructionKind::LoadRange,
+            Load(..) => InstructionKind::Load,
+            Add(..) => InstructionKind::Add,
+            LoadFromReg(..) => InstructionKind::LoadFromReg,
+            Or(..) => InstructionKind::Or,
+            And(..) => InstructionKind::And,
+            XOr(..) => InstructionKind::XOr,
+            AddReg(..) => InstructionKind::AddReg,
+            SubReg(..) => InstructionKind::SubReg,
+            ShiftRight(..) => InstructionKind::ShiftRight,
+            ReverseSub(..) => InstructionKind::ReverseSub,
+            ShiftLeft(..) => InstructionKind::ShiftLeft,
+            SkipNotEqualReg(..) => InstructionKind::SkipNotEqualReg,
+            LoadInstr(..) => InstructionKind::LoadInstr,
+            LoadLongInstr(..) => InstructionKind::LoadLongInstr,
+            SelectPlanes(..) => InstructionKind::SelectPlanes,
+            LoadAudioPattern => InstructionKind::LoadAudioPattern,
+            SetPitch(..) => InstructionKind::SetPitch,
+            JumpPlus(..) => InstructionKind::JumpPlus,
+            Rand(..) => InstructionKind::Rand,
+            Draw(..) => InstructionKind::Draw,
+            SkipPressed(..) => InstructionKind::SkipPressed,
+            SkipNotPressed(..) => InstructionKind::SkipNotPressed,
+            LoadDelayTimer(..) => InstructionKind::LoadDelayTimer,
+            WaitForKey(..) => InstructionKind::WaitForKey,
+            SetDelayTimer(..) => InstructionKind::SetDelayTimer,
+            SetSoundTimer(..) => InstructionKind::SetSoundTimer,
+            IncrementAddress(..) => InstructionKind::IncrementAddress,
+            LoadSpriteAddress(..) => InstructionKind::LoadSpriteAddress,
+            LoadBigSpriteAddress(..) => InstructionKind::LoadBigSpriteAddress,
+            LoadBCD(..) => InstructionKind::LoadBCD,
+            CopyToRam(..) => InstructionKind::CopyToRam,
+            CopyToRegisters(..) => InstructionKind::CopyToRegisters,
+            SaveFlags(..) => InstructionKind::SaveFlags,
+            LoadFlags(..) => InstructionKind::LoadFlags,
+        }
+    }
+}
+
+/// Formats the instruction as its mnemonic, e.g. `LD V3, 0x1F` or
+/// `DRW V0, V1, 5`.
+///
+/// Addresses are printed as 3 hex digits (4 for `LD I, long`), bytes as 2
+/// and nibbles in decimal.
+///
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Instruction::*;
+
+        let v = |r: Register| format!("V{:X}", r as u8);
+        let addr = |a: Address| format!("0x{:03X}", a.get_raw());
+        let byte = |b: Byte| format!("0x{:02X}", b.get_raw());
+
+        match *self {
+            Invalid(raw) => write!(f, "DW 0x{:04X}", raw),
+            Sys(a) => write!(f, "SYS {}", addr(a)),
+            Cls => write!(f, "CLS"),
+            Return => write!(f, "RET"),
+            ScrollDown(n) => write!(f, "SCD {}", n.get_raw()),
+            ScrollUp(n) => write!(f, "SCU {}", n.get_raw()),
+            ScrollRight => write!(f, "SCR"),
+            ScrollLeft => write!(f, "SCL"),
+            Exit => write!(f, "EXIT"),
+            LowRes => write!(f, "LOW"),
+            HighRes => write!(f, "HIGH"),
+            Jump(a) => write!(f, "JP {}", addr(a)),
+            Call(a) => write!(f, "CALL {}", addr(a)),
+            SkipEqualByte(x, b) => write!(f, "SE {}, {}", v(x), byte(b)),
+            SkipNotEqualByte(x, b) => write!(f, "SNE {}, {}", v(x), byte(b)),
+            SkipEqualReg(x, y) => write!(f, "SE {}, {}", v(x), v(y)),
+            SaveRange(x, y) => write!(f, "SAVE {} - {}", v(x), v(y)),
+            LoadRange(x, y) => write!(f, "LOAD {} - {}", v(x), v(y)),
+            Load(x, b) => write!(f, "LD {}, {}", v(x), byte(b)),
+            Add(x, b) => write!(f, "ADD {}, {}", v(x), byte(b)),
+            LoadFromReg(x, y) => write!(f, "LD {}, {}", v(x), v(y)),
+            Or(x, y) => write!(f, "OR {}, {}", v(x), v(y)),
+            And(x, y) => write!(f, "AND {}, {}", v(x), v(y)),
+            XOr(x, y) => write!(f, "XOR {}, {}", v(x), v(y)),
+            AddReg(x, y) => write!(f, "ADD {}, {}", v(x), v(y)),
+            SubReg(x, y) => write!(f, "SUB {}, {}", v(x), v(y)),
+            ShiftRight(x, y) => write!(f, "SHR {}, {}", v(x), v(y)),
+            ReverseSub(x, y) => write!(f, "SUBN {}, {}", v(x), v(y)),
+            ShiftLeft(x, y) => write!(f, "SHL {}, {}", v(x), v(y)),
+            SkipNotEqualReg(x, y) => write!(f, "SNE {}, {}", v(x), v(y)),
+            LoadInstr(a) => write!(f, "LD I, {}", addr(a)),
+            LoadLongInstr(a) => write!(f, "LD I, long 0x{:04X}", a.get_raw()),
+            SelectPlanes(n) => write!(f, "PLANE {}", n.get_raw()),
+            LoadAudioPattern => write!(f, "AUDIO"),
+            SetPitch(x) => write!(f, "PITCH {}", v(x)),
+            JumpPlus(a) => write!(f, "JP V0, {}", addr(a)),
+            Rand(x, b) => write!(f, "RND {}, {}", v(x), byte(b)),
+            Draw(x, y, n) => write!(f, "DRW {}, {}, {}", v(x), v(y), n.get_raw()),
+            SkipPressed(x) => write!(f, "SKP {}", v(x)),
+            SkipNotPressed(x) => write!(f, "SKNP {}", v(x)),
+            LoadDelayTimer(x) => write!(f, "LD {}, DT", v(x)),
+            WaitForKey(x) => write!(f, "LD {}, K", v(x)),
+            SetDelayTimer(x) => write!(f, "LD DT, {}", v(x)),
+            SetSoundTimer(x) => write!(f, "LD ST, {}", v(x)),
+            IncrementAddress(x) => write!(f, "ADD I, {}", v(x)),
+            LoadSpriteAddress(x) => write!(f, "LD F, {}", v(x)),
+            LoadBigSpriteAddress(x) => write!(f, "LD HF, {}", v(x)),
+            LoadBCD(x) => write!(f, "LD B, {}", v(x)),
+            CopyToRam(x) => write!(f, "LD [I], {}", v(x)),
+            CopyToRegisters(x) => write!(f, "LD {}, [I]", v(x)),
+            SaveFlags(x) => write!(f, "LD R, {}", v(x)),
+            LoadFlags(x) => write!(f, "LD {}, R", v(x)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Instruction::*;
+
+    #[test]
+    fn new_decodes_super_chip_display_instructions() {
+        assert_eq!(Instruction::new(0x00C5), ScrollDown(0x5.into()));
+        assert_eq!(Instruction::new(0x00FB), ScrollRight);
+        assert_eq!(Instruction::new(0x00FC), ScrollLeft);
+        assert_eq!(Instruction::new(0x00FD), Exit);
+        assert_eq!(Instruction::new(0x00FE), LowRes);
+        assert_eq!(Instruction::new(0x00FF), HighRes);
+    }
+
+    #[test]
+    fn new_decodes_super_chip_register_instructions() {
+        assert_eq!(Instruction::new(0xF330), LoadBigSpriteAddress(Register::V3));
+        assert_eq!(Instruction::new(0xF775), SaveFlags(Register::V7));
+        assert_eq!(Instruction::new(0xF285), LoadFlags(Register::V2));
+    }
+
+    #[test]
+    fn new_decodes_xo_chip_instructions() {
+        assert_eq!(Instruction::new(0x00D3), ScrollUp(0x3.into()));
+        assert_eq!(
+            Instruction::new(0x5122),
+            SaveRange(Register::V1, Register::V2)
+        );
+        assert_eq!(
+            Instruction::new(0x5213),
+            LoadRange(Register::V2, Register::V1)
+        );
+        assert_eq!(Instruction::new(0xF201), SelectPlanes(0x2.into()));
+        assert_eq!(Instruction::new(0xF002), LoadAudioPattern);
+        assert_eq!(Instruction::new(0xF43A), SetPitch(Register::V4));
+    }
+
+    #[test]
+    fn from_words_decodes_long_loads() {
+        let instruction = Instruction::from_words(0xF000, 0xABCD);
+
+        assert_eq!(instruction, LoadLongInstr(0xABCD.into()));
+        assert_eq!(instruction.size(), 4);
+        assert_eq!(Instruction::from_words(0x00E0, 0xF000), Cls);
+    }
+
+    #[test]
+    fn new_rejects_unknown_5xyn_variants() {
+        assert_eq!(Instruction::new(0x5121), Invalid(0x5121));
+    }
+
+    #[test]
+    fn new_rejects_9xyn_variants() {
+        assert_eq!(
+            Instruction::new(0x9120),
+            SkipNotEqualReg(Register::V1, Register::V2)
+        );
+        assert_eq!(Instruction::new(0x9121), Invalid(0x9121));
+    }
+
+    #[test]
+    fn new_decodes_large_sprites_as_draw_with_zero_height() {
+        assert_eq!(
+            Instruction::new(0xD120),
+            Draw(Register::V1, Register::V2, 0.into())
+        );
+    }
+
+    #[test]
+    fn kind_ignores_the_arguments() {
+        assert_eq!(Instruction::new(0xD125).kind(), InstructionKind::Draw);
+        assert_eq!(Instruction::new(0xF30A).kind(), InstructionKind::WaitForKey);
+        assert_eq!(Instruction::new(0x00E0).kind(), InstructionKind::Cls);
+        assert_ne!(Instruction::new(0x2300).kind(), InstructionKind::Jump);
+    }
+
+    #[test]
+    fn display_prints_the_mnemonic() {
+        let cases = [
+            (0x00E0, "CLS"),
+            (0x00C4, "SCD 4"),
+            (0x1208, "JP 0x208"),
+            (0x2ABC, "CALL 0xABC"),
+            (0x631F, "LD V3, 0x1F"),
+            (0x5A32, "SAVE VA - V3"),
+            (0x8AB6, "SHR VA, VB"),
+            (0xB300, "JP V0, 0x300"),
+            (0xD015, "DRW V0, V1, 5"),
+            (0xF20A, "LD V2, K"),
+            (0xFF65, "LD VF, [I]"),
+            (0xF201, "PLANE 2"),
+            (0x5121, "DW 0x5121"),
+        ];
+
+        for (raw, text) in cases.iter() {
+            assert_eq!(Instruction::new(*raw).to_string(), *text);
+        }
+
+        assert_eq!(
+            Instruction::from_words(0xF000, 0x1234).to_string(),
+            "LD I, long 0x1234"
+        );
+    }
 }
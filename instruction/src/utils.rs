@@ -48,18 +48,10 @@ fn get_address(inst: u16) -> Address {
 ///- **inst** : The raw instruction.
 ///
 fn get_registers(inst: u16) -> (Register, Register) {
-    let reg_bits = ((inst & 0x00F0) >> 4) as u8;
-    let vy = Register::new(reg_bits);
+    let vy = Register::from_nibble(((inst & 0x00F0) >> 4) as u8);
+    let vx = Register::from_nibble(((inst & 0x0F00) >> 8) as u8);
 
-    let reg_bits = ((inst & 0x0F00) >> 8) as u8;
-    let vx = Register::new(reg_bits);
-
-    if let (Ok(vx), Ok(vy)) = (vx, vy) {
-        (vx, vy)
-    } else {
-        // Todo: Need to handle errors properly here.
-        panic!("Error");
-    }
+    (vx, vy)
 }
 
 /// Gets the byte value from an instruction.
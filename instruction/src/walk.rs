@@ -0,0 +1,231 @@
+//! Static traversal of a ROM's code.
+//!
+//! Data and code share the address space, so a ROM is not decoded from start
+//! to end. Instead the paths execution can take are followed from `START`,
+//! which leaves the sprites and tables a program embeds out of the code.
+//!
+
+use super::Instruction;
+use std::collections::BTreeSet;
+
+/// The address ROMs are loaded at.
+///
+pub const START: u16 = 0x200;
+
+/// An instruction reached by `Rom::walk`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub addr: u16,
+
+    /// The raw words, the second one is only used by `F000 nnnn`.
+    pub opcode: (u16, u16),
+
+    pub instruction: Instruction,
+
+    /// The addresses execution can go on at, empty for `RET`, `EXIT` and
+    /// invalid opcodes.
+    pub successors: Vec<u16>,
+}
+
+/// A rom as seen from the machine's address space.
+///
+pub struct Rom<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Rom<'a> {
+    /// Creates a new Rom.
+    ///
+    ///### Arguments
+    ///
+    ///- **bytes** : The program, as loaded at 0x200.
+    ///
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Rom { bytes }
+    }
+
+    /// Returns the bytes of the rom.
+    ///
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the offset of the address into the rom.
+    ///
+    pub fn offset(&self, addr: u16) -> Option<usize> {
+        let offset = addr.checked_sub(START)? as usize;
+
+        if offset < self.bytes.len() {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the word at the address.
+    ///
+    fn word(&self, addr: u16) -> Option<u16> {
+        let offset = self.offset(addr)?;
+        let low = *self.bytes.get(offset + 1)?;
+
+        Some(u16::from_be_bytes([self.bytes[offset], low]))
+    }
+
+    /// Returns the raw words at the address, if all of the instruction is in
+    /// the rom.
+    ///
+    pub fn opcode(&self, addr: u16) -> Option<(u16, u16)> {
+        let raw = self.word(addr)?;
+
+        let next = if raw == 0xF000 {
+            self.word(addr.checked_add(2)?)?
+        } else {
+            0
+        };
+
+        Some((raw, next))
+    }
+
+    /// Returns the size of the instruction at the address, for skips.
+    ///
+    fn size_at(&self, addr: u16) -> u16 {
+        match self.word(addr) {
+            Some(0xF000) => 4,
+            _ => 2,
+        }
+    }
+
+    /// Walks the code reachable from 0x200 like the machine would run it.
+    ///
+    /// Jumps and calls are followed, skips continue at both the next
+    /// instruction and the one after it, and `RET`, `EXIT` and invalid opcodes
+    /// end a path. `JP V0, addr` can not be followed, the address is assumed
+    /// to be the start of a jump table and is walked as code.
+    ///
+    /// Every address is visited at most once.
+    ///
+    ///### Arguments
+    ///
+    ///- **visit** : Called with every instruction reached, the path stops
+    ///  there when it returns false.
+    ///
+    pub fn walk<F>(&self, mut visit: F)
+    where
+        F: FnMut(&Step) -> bool,
+    {
+        use Instruction::*;
+
+        let mut seen = BTreeSet::new();
+        let mut pending = vec![START];
+
+        while let Some(addr) = pending.pop() {
+            if !seen.insert(addr) {
+                continue;
+            }
+
+            let opcode = match self.opcode(addr) {
+                Some(opcode) => opcode,
+                None => continue,
+            };
+
+            let instruction = Instruction::from_words(opcode.0, opcode.1);
+            let next = addr.wrapping_add(instruction.size());
+
+            let successors = match instruction {
+                Jump(target) | JumpPlus(target) => vec![target.get_raw()],
+
+                Call(target) => vec![target.get_raw(), next],
+
+                Return | Exit | Invalid(_) => vec![],
+
+                SkipEqualByte(..) | SkipNotEqualByte(..) | SkipEqualReg(..)
+                | SkipNotEqualReg(..) | SkipPressed(_) | SkipNotPressed(_) => {
+                    vec![next.wrapping_add(self.size_at(next)), next]
+                }
+
+                _ => vec![next],
+            };
+
+            let step = Step {
+                addr,
+                opcode,
+                instruction,
+                successors,
+            };
+
+            if visit(&step) {
+                pending.extend_from_slice(&step.successors);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walked(rom: &[u8]) -> Vec<(u16, Vec<u16>)> {
+        let mut steps = Vec::new();
+
+        Rom::new(rom).walk(|step| {
+            steps.push((step.addr, step.successors.clone()));
+            true
+        });
+
+        steps
+    }
+
+    #[test]
+    fn follows_branches_and_both_sides_of_skips() {
+        let rom = [
+            0x30, 0x01, // skip if V0 == 1
+            0x22, 0x08, // call 0x208
+            0x12, 0x06, // jump 0x206
+            0x00, 0xFD, // 0x206: exit
+            0x00, 0xEE, // 0x208: return
+        ];
+
+        assert_eq!(
+            walked(&rom),
+            [
+                (0x200, vec![0x204, 0x202]),
+                (0x202, vec![0x208, 0x204]),
+                (0x204, vec![0x206]),
+                (0x206, vec![]),
+                (0x208, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_step_over_long_loads_and_paths_stop_when_refused() {
+        let rom = [
+            0x30, 0x00, // skip if V0 == 0
+            0xF0, 0x00, 0x12, 0x34, // I = 0x1234
+            0x00, 0xFD, // exit
+        ];
+        let mut steps = Vec::new();
+
+        Rom::new(&rom).walk(|step| {
+            steps.push((step.addr, step.opcode));
+            step.addr != 0x202
+        });
+
+        assert_eq!(
+            steps,
+            [
+                (0x200, (0x3000, 0)),
+                (0x202, (0xF000, 0x1234)),
+                (0x206, (0x00FD, 0))
+            ]
+        );
+    }
+
+    #[test]
+    fn addresses_outside_the_rom_are_not_walked() {
+        // jump 0x300
+        assert_eq!(walked(&[0x13, 0x00]), [(0x200, vec![0x300])]);
+        assert!(walked(&[0x12]).is_empty());
+    }
+}
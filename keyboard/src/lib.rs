@@ -4,43 +4,72 @@ extern crate model;
 use data::Nibble;
 use model::Chip8Keyboard;
 
+/// The state of the 16 keys, bit n being set while key n is pressed.
+///
 #[derive(Debug)]
 pub struct Keyboard {
-    pressed: Option<Nibble>,
+    pressed: u16,
 }
 
 impl Keyboard {
     pub fn new() -> Self {
-        Keyboard { pressed: None }
+        Keyboard { pressed: 0 }
     }
 }
 
 impl Chip8Keyboard for Keyboard {
     fn press(&mut self, key: Nibble) {
-        self.pressed = Some(key);
+        self.pressed |= get_key_code(key);
     }
 
-    fn release(&mut self, _key: Nibble) {
-        self.clear();
+    fn release(&mut self, key: Nibble) {
+        self.pressed &= !get_key_code(key);
     }
 
     fn is_pressed(&self, key: Nibble) -> bool {
-        if let Some(n) = self.pressed {
-            n == key
-        } else {
-            false
-        }
+        self.pressed & get_key_code(key) != 0
     }
 
     fn clear(&mut self) {
-        self.pressed = None;
+        self.pressed = 0;
     }
 }
 
-/*
 /// Creates the key code for a key.
 ///
 fn get_key_code(key: Nibble) -> u16 {
-    2u16.pow(key.get_raw() as u32)
+    1 << (key.get_raw() & 0x0F)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_pressed_independently() {
+        let mut keyboard = Keyboard::new();
+
+        keyboard.press(Nibble::new(0x4));
+        keyboard.press(Nibble::new(0x6));
+
+        assert!(keyboard.is_pressed(Nibble::new(0x4)));
+        assert!(keyboard.is_pressed(Nibble::new(0x6)));
+        assert!(!keyboard.is_pressed(Nibble::new(0x5)));
+    }
+
+    #[test]
+    fn release_only_releases_that_key() {
+        let mut keyboard = Keyboard::new();
+
+        keyboard.press(Nibble::new(0x0));
+        keyboard.press(Nibble::new(0xF));
+        keyboard.release(Nibble::new(0x0));
+
+        assert!(!keyboard.is_pressed(Nibble::new(0x0)));
+        assert!(keyboard.is_pressed(Nibble::new(0xF)));
+
+        keyboard.clear();
+
+        assert!(!keyboard.is_pressed(Nibble::new(0xF)));
+    }
 }
-*/
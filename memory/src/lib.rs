@@ -1,43 +1,70 @@
 extern crate data;
 extern crate model;
 
-use model::Chip8Memory;
+use model::{Chip8Error, Chip8Memory};
 
-use data::{Address, Byte, Nibble};
+use data::{Address, Byte};
+
+/// The size of the memory of the original Chip-8.
+pub const DEFAULT_SIZE: usize = 0x1000;
+
+/// The size of the memory of XO-CHIP.
+pub const XO_CHIP_SIZE: usize = 0x10000;
 
 pub struct Memory {
-    store: [Byte; 4096],
+    store: Vec<Byte>,
 }
 
 impl Memory {
-    /// Creates a new Chip8Memory.
+    /// Creates a new Chip8Memory with the 4 KiB of the original Chip-8.
     ///
     pub fn new() -> Self {
+        Memory::with_size(DEFAULT_SIZE)
+    }
+
+    /// Creates a new Chip8Memory.
+    ///
+    ///### Arguments
+    ///
+    ///- **size** : The number of bytes available, at most 64 KiB.
+    ///
+    pub fn with_size(size: usize) -> Self {
         Memory {
-            store: [0.into(); 4096],
+            store: vec![0.into(); size.min(XO_CHIP_SIZE)],
         }
     }
 }
 
 impl Chip8Memory for Memory {
-    fn get(&self, addr: Address) -> Byte {
-        let addr = addr.get_raw() as usize;
-
-        self.store[addr]
+    fn get(&self, addr: Address) -> Result<Byte, Chip8Error> {
+        self.store
+            .get(addr.get_raw() as usize)
+            .copied()
+            .ok_or(Chip8Error::OutOfBounds(addr))
     }
 
-    fn set(&mut self, addr: Address, byte: Byte) {
-        let addr = addr.get_raw() as usize;
+    fn set(&mut self, addr: Address, byte: Byte) -> Result<(), Chip8Error> {
+        let cell = self
+            .store
+            .get_mut(addr.get_raw() as usize)
+            .ok_or(Chip8Error::OutOfBounds(addr))?;
+
+        *cell = byte;
 
-        self.store[addr] = byte;
+        Ok(())
     }
 
-    fn get_slice(&self, address: Address, length: Nibble) -> &[Byte] {
+    fn get_slice(&self, address: Address, length: usize) -> Result<&[Byte], Chip8Error> {
         let start: usize = address.get_raw().into();
-        let length: usize = length.get_raw().into();
         let end = start + length;
 
-        &self.store[start..end]
+        self.store
+            .get(start..end)
+            .ok_or(Chip8Error::OutOfBounds(address))
+    }
+
+    fn size(&self) -> usize {
+        self.store.len()
     }
 }
 
@@ -50,7 +77,7 @@ mod tests {
     fn new_works() {
         let mut mem = Memory::new();
 
-        mem.set(0u16.into(), 1.into());
+        mem.set(0u16.into(), 1.into()).unwrap();
     }
 
     #[test]
@@ -59,16 +86,44 @@ mod tests {
 
         mem.store[0] = 1.into();
 
-        assert_eq!(mem.get(0.into()), 1.into());
+        assert_eq!(mem.get(0.into()).unwrap(), 1.into());
     }
 
     #[test]
     fn set_works() {
         let mut mem = Memory::new();
 
-        mem.set(0.into(), 1.into());
-        let byte = mem.get(0.into());
+        mem.set(0.into(), 1.into()).unwrap();
+        let byte = mem.get(0.into()).unwrap();
 
         assert_eq!(byte, 1.into());
     }
+
+    #[test]
+    fn with_size_allows_addressing_64k() {
+        let mut mem = Memory::with_size(XO_CHIP_SIZE);
+
+        mem.set(0xFFFF.into(), 1.into()).unwrap();
+
+        assert_eq!(mem.size(), 0x10000);
+        assert_eq!(mem.get(0xFFFF.into()).unwrap(), 1.into());
+    }
+
+    #[test]
+    fn accesses_past_the_end_are_out_of_bounds() {
+        let mut mem = Memory::new();
+
+        assert_eq!(
+            mem.get(0x1000.into()),
+            Err(Chip8Error::OutOfBounds(0x1000.into()))
+        );
+        assert_eq!(
+            mem.set(0x1000.into(), 1.into()),
+            Err(Chip8Error::OutOfBounds(0x1000.into()))
+        );
+        assert_eq!(
+            mem.get_slice(0xFFE.into(), 4),
+            Err(Chip8Error::OutOfBounds(0xFFE.into()))
+        );
+    }
 }
@@ -0,0 +1,85 @@
+//! Errors raised while running the virtual machine.
+//!
+
+use data::Address;
+use std::fmt;
+
+/// An error raised by a component of the virtual machine.
+///
+/// When one of these is returned from `execute` the program counter is left
+/// pointing at the instruction that failed.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Chip8Error {
+    /// A subroutine call at the address overflowed the call stack.
+    StackOverflow(Address),
+
+    /// A return at the address was executed with an empty call stack.
+    StackUnderflow(Address),
+
+    /// The address lies outside of the memory.
+    OutOfBounds(Address),
+
+    /// The opcode at the address is not a valid instruction.
+    InvalidOpcode(Address, u16),
+
+    /// The rom does not fit into the memory following the start address.
+    RomTooLarge { size: usize, capacity: usize },
+
+    /// The save state was written by an unsupported format version.
+    IncompatibleSaveState(u16),
+
+    /// The save state is truncated or does not fit this machine.
+    CorruptSaveState(String),
+
+    /// The movie is truncated, damaged or of an unsupported version.
+    CorruptMovie(String),
+
+    /// The movie was recorded with a different rom.
+    RomMismatch { expected: u64, found: u64 },
+
+    /// The movie was recorded on a machine set up differently. Holds the
+    /// setting that differs.
+    MovieMismatch(String),
+
+    /// Playing the movie back diverged from the recording. Holds the first
+    /// checked frame whose state differs, the divergence may have started up
+    /// to a checksum interval earlier.
+    MovieDesync { frame: u64 },
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Chip8Error::*;
+
+        match self {
+            StackOverflow(addr) => write!(f, "stack overflow calling a subroutine at {}", addr),
+            StackUnderflow(addr) => write!(f, "stack underflow returning at {}", addr),
+            OutOfBounds(addr) => write!(f, "memory access out of bounds at {}", addr),
+            InvalidOpcode(addr, opcode) => {
+                write!(f, "invalid opcode {:#06X} at {}", opcode, addr)
+            }
+            RomTooLarge { size, capacity } => write!(
+                f,
+                "rom of {} bytes does not fit in the {} bytes available",
+                size, capacity
+            ),
+            IncompatibleSaveState(version) => {
+                write!(f, "save state version {} is not supported", version)
+            }
+            CorruptSaveState(reason) => write!(f, "save state is corrupt: {}", reason),
+            CorruptMovie(reason) => write!(f, "movie is corrupt: {}", reason),
+            RomMismatch { expected, found } => write!(
+                f,
+                "movie was recorded with rom {:016x} but rom {:016x} is loaded",
+                expected, found
+            ),
+            MovieMismatch(setting) => {
+                write!(f, "movie was recorded with different {}", setting)
+            }
+            MovieDesync { frame } => write!(f, "movie playback diverged at frame {}", frame),
+        }
+    }
+}
+
+impl std::error::Error for Chip8Error {}
@@ -0,0 +1,82 @@
+//! Key events scheduled for an exact point of the emulation.
+//!
+
+/// When an input event takes effect.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputTime {
+    /// Before the instruction with this number, counting from 0.
+    Cycle(u64),
+
+    /// At the start of the frame with this number, that is after the timers
+    /// have ticked this many times.
+    Frame(u64),
+}
+
+/// A key press or release.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InputEvent {
+    pub time: InputTime,
+    pub key: u8,
+    pub pressed: bool,
+}
+
+impl InputEvent {
+    /// Creates an event pressing the key.
+    ///
+    ///### Arguments
+    ///
+    ///- **time** : When the key goes down.
+    ///- **key**  : The key, a number between 0 and 15.
+    ///
+    pub fn press(time: InputTime, key: u8) -> Self {
+        InputEvent {
+            time,
+            key,
+            pressed: true,
+        }
+    }
+
+    /// Creates an event releasing the key.
+    ///
+    ///### Arguments
+    ///
+    ///- **time** : When the key goes up.
+    ///- **key**  : The key, a number between 0 and 15.
+    ///
+    pub fn release(time: InputTime, key: u8) -> Self {
+        InputEvent {
+            time,
+            key,
+            pressed: false,
+        }
+    }
+
+    /// Returns a flag indicating if the event takes effect at this point.
+    ///
+    ///### Arguments
+    ///
+    ///- **cycles** : The number of instructions executed so far.
+    ///- **frames** : The number of frames completed so far.
+    ///
+    pub fn is_due(&self, cycles: u64, frames: u64) -> bool {
+        match self.time {
+            InputTime::Cycle(cycle) => cycles >= cycle,
+            InputTime::Frame(frame) => frames >= frame,
+        }
+    }
+}
+
+#[test]
+fn events_are_due_once_their_time_is_reached() {
+    let event = InputEvent::press(InputTime::Cycle(10), 0x5);
+
+    assert!(!event.is_due(9, 100));
+    assert!(event.is_due(10, 0));
+
+    let event = InputEvent::release(InputTime::Frame(2), 0x5);
+
+    assert!(!event.is_due(100, 1));
+    assert!(event.is_due(0, 2));
+}
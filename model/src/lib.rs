@@ -1,8 +1,12 @@
 //! This crate contains traits that represent the pieces of a chip-8 machine.
 
 extern crate data;
+mod error;
+mod input;
 mod register;
 
+pub use error::Chip8Error;
+pub use input::{InputEvent, InputTime};
 pub use register::*;
 
 use data::{ Address, Byte, Nibble };
@@ -20,11 +24,32 @@ pub trait Chip8VirtualMachine {
     ///
     ///- **n** : The numbers of cycles to be executed.
     ///
-    fn execute_cycles(&mut self, n: usize);
+    ///### Returns
+    ///
+    /// The first error raised, execution stops at the failing instruction.
+    ///
+    fn execute_cycles(&mut self, n: usize) -> Result<(), Chip8Error>;
 
     /// Executes a single cycle.
     ///
-    fn execute(&mut self);
+    fn execute(&mut self) -> Result<(), Chip8Error>;
+
+    /// Decrements the delay and sound timers. Called at 60 Hz.
+    ///
+    fn tick_timers(&mut self);
+
+    /// Runs a 60 Hz frame: executes the cycles, then ticks the timers once.
+    ///
+    ///###  Arguments
+    ///
+    ///- **cycles** : The number of cycles executed during the frame.
+    ///
+    fn run_frame(&mut self, cycles: usize) -> Result<(), Chip8Error> {
+        self.execute_cycles(cycles)?;
+        self.tick_timers();
+
+        Ok(())
+    }
 
     /// Loads a rom into memory
     ///
@@ -32,7 +57,7 @@ pub trait Chip8VirtualMachine {
     ///
     ///- **data** : The bytes of the rom file.
     ///
-    fn load_rom(&mut self, data: &[u8]);
+    fn load_rom(&mut self, data: &[u8]) -> Result<(), Chip8Error>;
 
     /// Returns the FrameBuffer.
     ///
@@ -46,9 +71,63 @@ pub trait Chip8VirtualMachine {
     ///
     fn press_key(&mut self, key: u8);
 
-    /// Releases pressed keys.
+    /// Set the key to released, leaving the other keys as they are.
+    ///
+    ///### Arguments
+    ///
+    ///- **key** : The key to release. Converts to a number between 0 and 15.
+    ///
+    fn release_key(&mut self, key: u8);
+
+    /// Releases all pressed keys.
     ///
     fn release_keys(&mut self);
+
+    /// Schedules a key press or release.
+    ///
+    /// Events are applied between instructions once they are due, those due
+    /// at the same point in the order they were queued.
+    ///
+    ///### Arguments
+    ///
+    ///- **event** : The event to apply.
+    ///
+    fn queue_input(&mut self, event: InputEvent);
+
+    /// Drops the queued input events and releases all pressed keys.
+    ///
+    /// Used when the machine jumps to another point in time, where the
+    /// queued events and the held keys no longer apply.
+    ///
+    fn clear_input(&mut self);
+
+    /// Returns the number of instructions executed so far.
+    ///
+    fn cycles(&self) -> u64;
+
+    /// Returns the number of frames completed so far.
+    ///
+    fn frames(&self) -> u64;
+
+    /// Captures the complete state of the machine.
+    ///
+    /// The snapshot is a versioned binary blob that can be handed back to
+    /// `restore`, even on a different instance.
+    ///
+    fn snapshot(&self) -> Vec<u8>;
+
+    /// Restores a state captured by `snapshot`.
+    ///
+    ///### Arguments
+    ///
+    ///- **snapshot** : The bytes returned by `snapshot`.
+    ///
+    ///### Returns
+    ///
+    /// An error if the snapshot was written by an incompatible version or is
+    /// damaged. The machine is left untouched in that case.
+    ///
+    fn restore(&mut self, snapshot: &[u8]) -> Result<(), Chip8Error>;
 }
 
 /// Represents a collection of Registers.
@@ -93,7 +172,7 @@ pub trait Chip8Memory {
     ///
     ///- **address** : The address to the data.
     ///
-    fn get(&self, address: Address) -> Byte;
+    fn get(&self, address: Address) -> Result<Byte, Chip8Error>;
 
     /// Sets the value at an address.
     ///
@@ -104,19 +183,28 @@ pub trait Chip8Memory {
     ///- **address**    : The Address of the memory we setting.
     ///- **b**          : The byte.
     ///
-    fn set(&mut self, address: Address, byte: Byte);
+    fn set(&mut self, address: Address, byte: Byte) -> Result<(), Chip8Error>;
 
     /// Gets a slice of memory starting.
     ///
     ///### Arguments
     ///
     ///- **address**    : The starting address for the slice.
-    ///- **nibble**     : The size of the slice.
+    ///- **length**     : The size of the slice.
+    ///
+    fn get_slice(&self, address: Address, length: usize) -> Result<&[Byte], Chip8Error>;
+
+    /// Returns the number of addressable bytes.
     ///
-    fn get_slice(&self, address: Address, nibble: Nibble) -> &[Byte];
+    fn size(&self) -> usize;
 }
 
-pub trait Chip8FrameBuffer: std::ops::Deref<Target = [u64]> + std::fmt::Debug {
+/// Represents the display of the virtual machine.
+///
+/// The display starts out in the 64x32 low resolution mode and can be
+/// switched to the 128x64 high resolution mode used by SUPER-CHIP.
+///
+pub trait Chip8FrameBuffer: std::fmt::Debug {
     /// Draws a sprite to this buffer.
     ///
     ///###  Arguments
@@ -124,19 +212,136 @@ pub trait Chip8FrameBuffer: std::ops::Deref<Target = [u64]> + std::fmt::Debug {
     ///- **x**      : The x coordinate ofr where to start drawing.
     ///- **y**      : The y coordinate for where to start drawing.
     ///- **sprite** : A slice containing the sprite data.
+    ///- **clip**   : Drops pixels past the edge instead of wrapping them.
     ///
     ///### Returns
     /// 
     /// A boolean flag indicating if there was a collision.
     /// 
-    fn draw(&mut self, x: Byte, y: Byte, sprite: &[Byte]) -> bool;
+    fn draw(&mut self, x: Byte, y: Byte, sprite: &[Byte], clip: bool) -> bool;
+
+    /// Draws a 16x16 sprite to this buffer.
+    ///
+    /// Each row of the sprite is made up of two bytes.
+    ///
+    ///###  Arguments
+    ///
+    ///- **x**      : The x coordinate for where to start drawing.
+    ///- **y**      : The y coordinate for where to start drawing.
+    ///- **sprite** : A slice containing the 32 bytes of sprite data.
+    ///- **clip**   : Drops pixels past the edge instead of wrapping them.
+    ///
+    ///### Returns
+    ///
+    /// A boolean flag indicating if there was a collision.
+    ///
+    fn draw_large(&mut self, x: Byte, y: Byte, sprite: &[Byte], clip: bool) -> bool;
 
     /// Clears the buffer.
     ///
-    /// Clears the buffer by setting all pixels back
+    /// Clears the buffer by setting all pixels of the selected planes back
     /// to zero.
     ///
     fn clear(&mut self);
+
+    /// Returns the width of the display in pixels.
+    ///
+    fn width(&self) -> usize;
+
+    /// Returns the height of the display in pixels.
+    ///
+    fn height(&self) -> usize;
+
+    /// Returns a flag indicating if the pixel is set in any plane.
+    ///
+    ///###  Arguments
+    ///
+    ///- **x** : The column, counted from the left.
+    ///- **y** : The row, counted from the top.
+    ///
+    fn get_pixel(&self, x: usize, y: usize) -> bool;
+
+    /// Returns the color of a pixel.
+    ///
+    /// Bit n of the color is set if the pixel is set in plane n.
+    ///
+    ///###  Arguments
+    ///
+    ///- **x** : The column, counted from the left.
+    ///- **y** : The row, counted from the top.
+    ///
+    fn get_color(&self, x: usize, y: usize) -> u8;
+
+    /// Sets the color of a pixel in every plane, regardless of the selection.
+    ///
+    ///###  Arguments
+    ///
+    ///- **x**     : The column, counted from the left.
+    ///- **y**     : The row, counted from the top.
+    ///- **color** : Bit n sets the pixel in plane n.
+    ///
+    fn set_color(&mut self, x: usize, y: usize, color: u8);
+
+    /// Selects the planes that drawing, clearing and scrolling affect.
+    ///
+    /// When more than one plane is selected, `draw` expects the sprite data
+    /// for each plane to follow one another, starting with the lowest plane.
+    ///
+    ///###  Arguments
+    ///
+    ///- **mask** : Bit n selects plane n.
+    ///
+    fn select_planes(&mut self, mask: u8);
+
+    /// Returns the mask of the selected planes.
+    ///
+    fn selected_planes(&self) -> u8;
+
+    /// Switches between the low and high resolution modes.
+    ///
+    /// Switching modes clears the display.
+    ///
+    ///###  Arguments
+    ///
+    ///- **high** : True for 128x64, false for 64x32.
+    ///
+    fn set_high_res(&mut self, high: bool);
+
+    /// Returns a flag indicating if the display is in high resolution mode.
+    ///
+    fn is_high_res(&self) -> bool;
+
+    /// Scrolls the display down.
+    ///
+    ///###  Arguments
+    ///
+    ///- **n** : The number of pixels to scroll by.
+    ///
+    fn scroll_down(&mut self, n: usize);
+
+    /// Scrolls the display up.
+    ///
+    ///###  Arguments
+    ///
+    ///- **n** : The number of pixels to scroll by.
+    ///
+    fn scroll_up(&mut self, n: usize);
+
+    /// Scrolls the display right.
+    ///
+    ///###  Arguments
+    ///
+    ///- **n** : The number of pixels to scroll by.
+    ///
+    fn scroll_right(&mut self, n: usize);
+
+    /// Scrolls the display left.
+    ///
+    ///###  Arguments
+    ///
+    ///- **n** : The number of pixels to scroll by.
+    ///
+    fn scroll_left(&mut self, n: usize);
 }
 
 pub trait Chip8ProgramCounter {
@@ -154,11 +359,19 @@ pub trait Chip8ProgramCounter {
     ///
     ///- **addr** : The address of the subroutine.
     ///
-    fn to_subroutine(&mut self, addr: Address);
+    ///### Returns
+    ///
+    /// A StackOverflow error if the call stack is full.
+    ///
+    fn to_subroutine(&mut self, addr: Address) -> Result<(), Chip8Error>;
 
     /// Returns from the current routine.
     ///
-    fn rtrn(&mut self);
+    ///### Returns
+    ///
+    /// A StackUnderflow error if there is no routine to return from.
+    ///
+    fn rtrn(&mut self) -> Result<(), Chip8Error>;
 
     /// Increments the program counter by the provided amount.
     ///
@@ -175,6 +388,25 @@ pub trait Chip8ProgramCounter {
     /// Sets the current executing instruction.
     ///
     fn set(&mut self, addr: Address);
+
+    /// Returns the call stack, from the main routine up to the current one.
+    ///
+    /// The last address is the one returned by `current`.
+    ///
+    fn stack(&self) -> Vec<Address>;
+
+    /// Replaces the call stack.
+    ///
+    ///### Arguments
+    ///
+    ///- **stack** : The addresses, from the main routine up to the current one.
+    ///
+    ///### Returns
+    ///
+    /// A StackOverflow error if the stack does not fit, or a StackUnderflow
+    /// error if it is empty.
+    ///
+    fn restore_stack(&mut self, stack: &[Address]) -> Result<(), Chip8Error>;
 }
 
 pub trait Chip8Keyboard {
@@ -207,6 +439,84 @@ pub trait Chip8Keyboard {
     fn clear(&mut self);
 }
 
+/// Something that makes the buzzer heard.
+///
+/// The machine calls `start` when the sound timer becomes non zero and `stop`
+/// when it runs out, so implementations only need to track the transitions.
+///
+pub trait Chip8Audio {
+    /// Starts the buzzer.
+    ///
+    fn start(&mut self);
+
+    /// Stops the buzzer.
+    ///
+    fn stop(&mut self);
+
+    /// Called at the end of every 60 Hz frame, after the timers ticked.
+    ///
+    /// Implementations that render audio offline use this to keep time.
+    ///
+    fn end_frame(&mut self) {}
+
+    /// Sets the XO-CHIP audio pattern, which is played instead of the tone
+    /// of the buzzer.
+    ///
+    /// The pattern holds 128 1-bit samples played back at
+    /// `4000 * 2 ^ ((pitch - 64) / 48)` Hz. The machine calls this when `F002`
+    /// or `Fx3A` run and when a save state with a pattern is restored.
+    ///
+    ///### Arguments
+    ///
+    ///- **pattern** : The samples, the first one in the top bit of byte 0.
+    ///- **pitch**   : The playback pitch.
+    ///
+    fn set_pattern(&mut self, _pattern: &[Byte; 16], _pitch: Byte) {}
+
+    /// Goes back to the tone of the buzzer.
+    ///
+    /// The machine calls this when a save state without a pattern is
+    /// restored.
+    ///
+    fn clear_pattern(&mut self) {}
+}
+
+/// No sound at all.
+///
+impl Chip8Audio for () {
+    fn start(&mut self) {}
+
+    fn stop(&mut self) {}
+}
+
+pub trait Chip8Random {
+    /// Returns the next random byte.
+    ///
+    fn next_byte(&mut self) -> Byte;
+
+    /// Returns the internal state, so a save state can resume the sequence.
+    ///
+    fn state(&self) -> Vec<u8>;
+
+    /// Restores a state returned by `state`.
+    ///
+    ///### Arguments
+    ///
+    ///- **state** : The bytes returned by `state`.
+    ///
+    ///### Returns
+    ///
+    /// A CorruptSaveState error if the state does not belong to this source.
+    ///
+    fn set_state(&mut self, state: &[u8]) -> Result<(), Chip8Error>;
+
+    /// Called at the end of every 60 Hz frame, after the timers ticked.
+    ///
+    /// Sources that follow the display interrupt use this to keep time.
+    ///
+    fn end_frame(&mut self) {}
+}
+
 pub trait Compute {
     fn compute(
         pc: &mut dyn Chip8ProgramCounter,
@@ -57,6 +57,17 @@ impl Register {
         RegisterIterator::new(to)
     }
 
+    /// Gets the register from the low 4 bits of a byte.
+    ///
+    /// Unlike `new` this cannot fail, the high bits are ignored.
+    ///
+    pub fn from_nibble(byte: u8) -> Self {
+        match Register::new(byte & 0x0F) {
+            Ok(r) => r,
+            Err(_) => unreachable!("every 4 bit value names a register"),
+        }
+    }
+
     /// Gets the register from a byte address.
     ///
     pub fn new(byte: u8) -> Result<Self, String> {
@@ -93,7 +104,7 @@ impl Into<usize> for Register {
 ///
 pub struct RegisterIterator {
     last: Register,
-    current: Register,
+    current: Option<Register>,
 }
 
 impl RegisterIterator {
@@ -106,7 +117,7 @@ impl RegisterIterator {
     pub fn new(last: Register) -> Self {
         RegisterIterator {
             last,
-            current: Register::V0,
+            current: Some(Register::V0),
         }
     }
 }
@@ -115,12 +126,13 @@ impl Iterator for RegisterIterator {
     type Item = Register;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current.compare(self.last) == 1 {
+        let cur = self.current?;
+
+        if cur.compare(self.last) == 1 {
             None
         } else {
-            let cur = self.current;
-
-            self.current = Register::new((cur as u8) + 1).unwrap();
+            // There is no register after VF, which ends the iteration.
+            self.current = Register::new((cur as u8) + 1).ok();
 
             Some(cur)
         }
@@ -142,6 +154,10 @@ fn register_iterator_test() {
 
     assert_eq!(iter.next(), Some(V0));
     assert_eq!(iter.next(), None);
+
+    let iter = RegisterIterator::new(VF);
+
+    assert_eq!(iter.count(), 16);
 }
 
 #[test]
@@ -164,6 +180,11 @@ fn register_new_generates_correct_register_name() {
     assert_eq!(Register::new(0xF).unwrap(), Register::VF);
 }
 
+#[test]
+fn register_from_nibble_ignores_the_high_bits() {
+    assert_eq!(Register::from_nibble(0x3A), Register::VA);
+}
+
 #[test]
 fn register_compare() {
     let reg1 = Register::V0;
@@ -3,7 +3,7 @@ extern crate model;
 
 use data::{Address, Byte};
 
-use model::Chip8ProgramCounter;
+use model::{Chip8Error, Chip8ProgramCounter};
 
 const STACK_SIZE: usize = 16;
 
@@ -44,17 +44,26 @@ impl Chip8ProgramCounter for ProgramCounter {
         self.items[self.pointer] += 1.into();
     }
 
-    fn to_subroutine(&mut self, new_ptr: Address) {
+    fn to_subroutine(&mut self, new_ptr: Address) -> Result<(), Chip8Error> {
+        if self.pointer + 1 >= STACK_SIZE {
+            return Err(Chip8Error::StackOverflow(self.current()));
+        }
+
         self.pointer += 1;
         self.items[self.pointer] = new_ptr;
+
+        Ok(())
     }
 
-    fn rtrn(&mut self) {
+    fn rtrn(&mut self) -> Result<(), Chip8Error> {
+        // Popping the last address would empty the stack.
         if self.pointer == 0 {
-            panic!("Popping the CallStack will empty the stack.")
+            return Err(Chip8Error::StackUnderflow(self.current()));
         }
 
         self.pointer -= 1;
+
+        Ok(())
     }
 
     fn inc_by(&mut self, amt: Byte) {
@@ -69,6 +78,23 @@ impl Chip8ProgramCounter for ProgramCounter {
     fn set(&mut self, new_pc: Address) {
         self.items[self.pointer] = new_pc;
     }
+
+    fn stack(&self) -> Vec<Address> {
+        self.items[..=self.pointer].to_vec()
+    }
+
+    fn restore_stack(&mut self, stack: &[Address]) -> Result<(), Chip8Error> {
+        match stack.last() {
+            None => Err(Chip8Error::StackUnderflow(self.current())),
+            Some(last) if stack.len() > STACK_SIZE => Err(Chip8Error::StackOverflow(*last)),
+            Some(_) => {
+                self.items[..stack.len()].copy_from_slice(stack);
+                self.pointer = stack.len() - 1;
+
+                Ok(())
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -102,10 +128,47 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
-    fn returning_when_only_1_address_panics() {
+    fn returning_when_only_1_address_is_an_underflow() {
+        let mut pc = ProgramCounter::new(0x200.into());
+
+        assert_eq!(pc.rtrn(), Err(Chip8Error::StackUnderflow(0x200.into())));
+    }
+
+    #[test]
+    fn calling_past_the_stack_size_is_an_overflow() {
+        let mut pc = ProgramCounter::new(0x200.into());
+
+        for _ in 1..STACK_SIZE {
+            pc.to_subroutine(0x300.into()).unwrap();
+        }
+
+        assert_eq!(
+            pc.to_subroutine(0x300.into()),
+            Err(Chip8Error::StackOverflow(0x300.into()))
+        );
+        assert_eq!(pc.depth(), STACK_SIZE);
+    }
+
+    #[test]
+    fn restore_stack_replaces_the_call_stack() {
+        let mut pc = ProgramCounter::new(0x200.into());
+        let stack: Vec<Address> = vec![0x202.into(), 0x300.into(), 0x400.into()];
+
+        pc.restore_stack(&stack).unwrap();
+
+        assert_eq!(pc.stack(), stack);
+        assert_eq!(pc.current(), 0x400.into());
+        assert_eq!(pc.depth(), 3);
+    }
+
+    #[test]
+    fn restore_stack_rejects_empty_and_oversized_stacks() {
         let mut pc = ProgramCounter::new(0x200.into());
 
-        pc.rtrn();
+        assert!(pc.restore_stack(&[]).is_err());
+        assert!(pc
+            .restore_stack(&[Address::new(0x200); STACK_SIZE + 1])
+            .is_err());
+        assert_eq!(pc.stack(), vec![Address::new(0x200)]);
     }
 }
@@ -0,0 +1,21 @@
+//! Random number sources for the `Cxkk` instruction.
+//!
+
+extern crate data;
+extern crate model;
+
+mod scripted;
+mod seeded;
+mod vip;
+
+pub use scripted::ScriptedRandom;
+pub use seeded::SeededRandom;
+pub use vip::VipRandom;
+
+use model::Chip8Error;
+
+/// Creates the error returned when a state does not belong to a source.
+///
+fn invalid_state(source: &str) -> Chip8Error {
+    Chip8Error::CorruptSaveState(format!("invalid {} random state", source))
+}
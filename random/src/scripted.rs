@@ -0,0 +1,98 @@
+use super::invalid_state;
+use data::Byte;
+use model::{Chip8Error, Chip8Random};
+
+/// Returns a fixed sequence of bytes, starting over when it runs out.
+///
+/// Meant for tests that need to know what `Cxkk` will produce.
+///
+#[derive(Debug)]
+pub struct ScriptedRandom {
+    values: Vec<u8>,
+    position: usize,
+}
+
+impl ScriptedRandom {
+    /// Creates a new ScriptedRandom.
+    ///
+    ///### Arguments
+    ///
+    ///- **values** : The bytes to return in order. An empty script returns 0.
+    ///
+    pub fn new(values: Vec<u8>) -> Self {
+        ScriptedRandom {
+            values,
+            position: 0,
+        }
+    }
+}
+
+impl Chip8Random for ScriptedRandom {
+    fn next_byte(&mut self) -> Byte {
+        if self.values.is_empty() {
+            return 0.into();
+        }
+
+        let value = self.values[self.position];
+        self.position = (self.position + 1) % self.values.len();
+
+        value.into()
+    }
+
+    fn state(&self) -> Vec<u8> {
+        (self.position as u32).to_be_bytes().to_vec()
+    }
+
+    fn set_state(&mut self, state: &[u8]) -> Result<(), Chip8Error> {
+        let mut bytes = [0; 4];
+
+        if state.len() != bytes.len() {
+            return Err(invalid_state("scripted"));
+        }
+
+        bytes.copy_from_slice(state);
+        let position = u32::from_be_bytes(bytes) as usize;
+
+        if position >= self.values.len().max(1) {
+            return Err(invalid_state("scripted"));
+        }
+
+        self.position = position;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_the_script_in_order_and_repeats() {
+        let mut rng = ScriptedRandom::new(vec![1, 2, 3]);
+
+        let values: Vec<u8> = (0..5).map(|_| rng.next_byte().get_raw()).collect();
+
+        assert_eq!(values, vec![1, 2, 3, 1, 2]);
+    }
+
+    #[test]
+    fn empty_script_returns_zero() {
+        let mut rng = ScriptedRandom::new(vec![]);
+
+        assert_eq!(rng.next_byte().get_raw(), 0);
+    }
+
+    #[test]
+    fn state_is_the_position() {
+        let mut rng = ScriptedRandom::new(vec![1, 2, 3]);
+        rng.next_byte();
+
+        let state = rng.state();
+        rng.next_byte();
+        rng.set_state(&state).unwrap();
+
+        assert_eq!(rng.next_byte().get_raw(), 2);
+        assert!(rng.set_state(&[0, 0, 0, 3]).is_err());
+    }
+}
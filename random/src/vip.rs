@@ -0,0 +1,158 @@
+use super::invalid_state;
+use data::Byte;
+use model::{Chip8Error, Chip8Random};
+
+/// The number of bytes the VIP interpreter reads its randomness from.
+pub const PAGE_SIZE: usize = 256;
+
+/// The second page of the VIP interpreter, 0x0100 to 0x01FF, which holds the
+/// random number routine itself.
+///
+pub const INTERPRETER_PAGE: [u8; PAGE_SIZE] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x45, 0xA3, 0x98, 0x56, 0xD4, 0xF8, 0x81, 0xBC, 0xF8, 0x95,
+    0xAC, 0x22, 0xDC, 0x12, 0x56, 0xD4, 0x06, 0xB8, 0xD4, 0x06, 0xA8, 0xD4, 0x64, 0x0A, 0x01, 0xE6,
+    0x8A, 0xF4, 0xAA, 0x3B, 0x28, 0x9A, 0xFC, 0x01, 0xBA, 0xD4, 0xF8, 0x81, 0xBA, 0x06, 0xFA, 0x0F,
+    0xAA, 0x0A, 0xAA, 0xD4, 0xE6, 0x06, 0xBF, 0x93, 0xBE, 0xF8, 0x1B, 0xAE, 0x2A, 0x1A, 0xF8, 0x00,
+    0x5A, 0x0E, 0xF5, 0x3B, 0x4B, 0x56, 0x0A, 0xFC, 0x01, 0x5A, 0x30, 0x40, 0x4E, 0xF6, 0x3B, 0x3C,
+    0x9F, 0x56, 0x2A, 0x2A, 0xD4, 0x00, 0x22, 0x86, 0x52, 0xF8, 0xF0, 0xA7, 0x07, 0x5A, 0x87, 0xF3,
+    0x17, 0x1A, 0x3A, 0x5B, 0x12, 0xD4, 0x22, 0x86, 0x52, 0xF8, 0xF0, 0xA7, 0x0A, 0x57, 0x87, 0xF3,
+    0x17, 0x1A, 0x3A, 0x6B, 0x12, 0xD4, 0x15, 0x85, 0x22, 0x73, 0x95, 0x52, 0x25, 0x45, 0xA5, 0x86,
+    0xFA, 0x0F, 0xB5, 0xD4, 0x45, 0xE6, 0xF3, 0x3A, 0x82, 0x15, 0x15, 0xD4, 0x45, 0xE6, 0xF3, 0x3A,
+    0x88, 0xD4, 0x45, 0x07, 0x30, 0x8C, 0x45, 0x07, 0x30, 0x84, 0xE6, 0x62, 0x26, 0x45, 0xA3, 0x36,
+    0x88, 0xD4, 0x3E, 0x88, 0xD4, 0xF8, 0xF0, 0xA7, 0xE7, 0x45, 0xF4, 0xA5, 0x86, 0xFA, 0x0F, 0x3B,
+    0xB2, 0xFC, 0x01, 0xB5, 0xD4, 0x45, 0x56, 0xD4, 0x45, 0xE6, 0xF4, 0x56, 0xD4, 0x45, 0xFA, 0x0F,
+    0x3A, 0xC4, 0x07, 0x56, 0xD4, 0xAF, 0x22, 0xF8, 0xD3, 0x73, 0x8F, 0xF9, 0xF0, 0x52, 0xE6, 0x07,
+    0xD2, 0x56, 0xF8, 0xFF, 0xA6, 0xF8, 0x00, 0x7E, 0x56, 0xD4, 0x19, 0x89, 0xAE, 0x93, 0xBE, 0x99,
+    0xEE, 0xF4, 0x56, 0x76, 0xE6, 0xF4, 0xB9, 0x56, 0x45, 0xF2, 0x56, 0xD4, 0x45, 0xAA, 0x86, 0xFA,
+    0x0F, 0xBA, 0xD4, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xE0, 0x00, 0x4B,
+];
+
+/// A source modelled on the random number routine of the COSMAC VIP interpreter.
+///
+/// The VIP kept a 16 bit counter in R9 that the display interrupt incremented
+/// every frame. `Cxkk` incremented it once more, read a byte from the
+/// interpreter's own code page at the low byte of the counter and added it to
+/// the high byte, which became the random number.
+///
+/// The sequence only matches real hardware when the page holds the code of
+/// the interpreter at 0x0100, as `cosmac_vip` sets it up.
+///
+#[derive(Debug)]
+pub struct VipRandom {
+    page: Vec<u8>,
+    counter: u16,
+}
+
+impl VipRandom {
+    /// Creates a new VipRandom.
+    ///
+    ///### Arguments
+    ///
+    ///- **page**    : The bytes the random numbers are read from. Only the
+    ///  first 256 are used and missing bytes read as 0.
+    ///- **counter** : The initial value of the counter.
+    ///
+    pub fn new(page: &[u8], counter: u16) -> Self {
+        let mut bytes = vec![0; PAGE_SIZE];
+        let len = page.len().min(PAGE_SIZE);
+        bytes[..len].copy_from_slice(&page[..len]);
+
+        VipRandom {
+            page: bytes,
+            counter,
+        }
+    }
+
+    /// Creates a VipRandom reading from the interpreter page, with the counter
+    /// cleared like at power on.
+    ///
+    pub fn cosmac_vip() -> Self {
+        VipRandom::new(&INTERPRETER_PAGE, 0)
+    }
+
+    /// Advances the counter like the display interrupt does.
+    ///
+    pub fn tick(&mut self) {
+        self.counter = self.counter.wrapping_add(1);
+    }
+}
+
+impl Chip8Random for VipRandom {
+    fn next_byte(&mut self) -> Byte {
+        self.tick();
+
+        let [high, low] = self.counter.to_be_bytes();
+        let high = high.wrapping_add(self.page[low as usize]);
+        self.counter = u16::from_be_bytes([high, low]);
+
+        high.into()
+    }
+
+    fn end_frame(&mut self) {
+        self.tick();
+    }
+
+    fn state(&self) -> Vec<u8> {
+        self.counter.to_be_bytes().to_vec()
+    }
+
+    fn set_state(&mut self, state: &[u8]) -> Result<(), Chip8Error> {
+        match state {
+            [high, low] => {
+                self.counter = u16::from_be_bytes([*high, *low]);
+
+                Ok(())
+            }
+            _ => Err(invalid_state("vip")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_the_page_bytes_into_the_high_byte() {
+        let page: Vec<u8> = (0..=255).collect();
+        let mut rng = VipRandom::new(&page, 0x0000);
+
+        // Reads page[1], then page[2], then page[3].
+        assert_eq!(rng.next_byte().get_raw(), 1);
+        assert_eq!(rng.next_byte().get_raw(), 3);
+        assert_eq!(rng.next_byte().get_raw(), 6);
+    }
+
+    #[test]
+    fn tick_moves_the_read_position() {
+        let page: Vec<u8> = (0..=255).collect();
+        let mut rng = VipRandom::new(&page, 0x0000);
+
+        rng.tick();
+        rng.tick();
+
+        assert_eq!(rng.next_byte().get_raw(), 3);
+    }
+
+    #[test]
+    fn cosmac_vip_reads_the_interpreter_page() {
+        let mut rng = VipRandom::cosmac_vip();
+
+        for _ in 0..6 {
+            rng.end_frame();
+        }
+
+        assert_eq!(rng.next_byte().get_raw(), INTERPRETER_PAGE[7]);
+    }
+
+    #[test]
+    fn state_is_the_counter() {
+        let mut rng = VipRandom::new(&[5; 256], 0x1234);
+
+        assert_eq!(rng.state(), vec![0x12, 0x34]);
+
+        rng.set_state(&[0, 0xFF]).unwrap();
+        assert_eq!(rng.next_byte().get_raw(), 6);
+        assert!(rng.set_state(&[]).is_err());
+    }
+}
@@ -0,0 +1,2166 @@
+//! `test_opcode` translated to Rust by the recompiler, do not edit.
+//!
+//! 96 blocks, 207 instructions. Load `ROM` and run it with
+//! `VirtualMachine::run_compiled_frame`.
+//!
+
+use cpu::{CompiledProgram, Handler, VirtualMachine};
+use data::{Address, Byte};
+use instruction::Instruction;
+use model::{
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank, Register,
+};
+
+/// The rom, as loaded at 0x200.
+///
+pub const ROM: [u8; 478] = [
+    0x12, 0x4E, 0xEA, 0xAC, 0xAA, 0xEA, 0xCE, 0xAA, 0xAA, 0xAE, 0xE0, 0xA0, 0xA0, 0xE0, 0xC0, 0x40,
+    0x40, 0xE0, 0xE0, 0x20, 0xC0, 0xE0, 0xE0, 0x60, 0x20, 0xE0, 0xA0, 0xE0, 0x20, 0x20, 0x60, 0x40,
+    0x20, 0x40, 0xE0, 0x80, 0xE0, 0xE0, 0xE0, 0x20, 0x20, 0x20, 0xE0, 0xE0, 0xA0, 0xE0, 0xE0, 0xE0,
+    0x20, 0xE0, 0x40, 0xA0, 0xE0, 0xA0, 0xE0, 0xC0, 0x80, 0xE0, 0xE0, 0x80, 0xC0, 0x80, 0xA0, 0x40,
+    0xA0, 0xA0, 0xA2, 0x02, 0xDA, 0xB4, 0x00, 0xEE, 0xA2, 0x02, 0xDA, 0xB4, 0x13, 0xDC, 0x68, 0x01,
+    0x69, 0x05, 0x6A, 0x0A, 0x6B, 0x01, 0x65, 0x2A, 0x66, 0x2B, 0xA2, 0x16, 0xD8, 0xB4, 0xA2, 0x3E,
+    0xD9, 0xB4, 0xA2, 0x02, 0x36, 0x2B, 0xA2, 0x06, 0xDA, 0xB4, 0x6B, 0x06, 0xA2, 0x1A, 0xD8, 0xB4,
+    0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x45, 0x2A, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x0B, 0xA2, 0x1E,
+    0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x55, 0x60, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x10,
+    0xA2, 0x26, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x76, 0xFF, 0x46, 0x2A, 0xA2, 0x02,
+    0xDA, 0xB4, 0x6B, 0x15, 0xA2, 0x2E, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0xA2, 0x06, 0x95, 0x60,
+    0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x1A, 0xA2, 0x32, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0x22, 0x42,
+    0x68, 0x17, 0x69, 0x1B, 0x6A, 0x20, 0x6B, 0x01, 0xA2, 0x0A, 0xD8, 0xB4, 0xA2, 0x36, 0xD9, 0xB4,
+    0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x06, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x0A, 0xD9, 0xB4, 0xA2, 0x06,
+    0x87, 0x50, 0x47, 0x2A, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x0B, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x0E,
+    0xD9, 0xB4, 0xA2, 0x06, 0x67, 0x2A, 0x87, 0xB1, 0x47, 0x2B, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x10,
+    0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x12, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x78, 0x67, 0x1F, 0x87, 0x62,
+    0x47, 0x18, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x15, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x16, 0xD9, 0xB4,
+    0xA2, 0x06, 0x66, 0x78, 0x67, 0x1F, 0x87, 0x63, 0x47, 0x67, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x1A,
+    0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x1A, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x8C, 0x67, 0x8C, 0x87, 0x64,
+    0x47, 0x18, 0xA2, 0x02, 0xDA, 0xB4, 0x68, 0x2C, 0x69, 0x30, 0x6A, 0x34, 0x6B, 0x01, 0xA2, 0x2A,
+    0xD8, 0xB4, 0xA2, 0x1E, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x8C, 0x67, 0x78, 0x87, 0x65, 0x47, 0xEC,
+    0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x06, 0xA2, 0x2A, 0xD8, 0xB4, 0xA2, 0x22, 0xD9, 0xB4, 0xA2, 0x06,
+    0x66, 0xE0, 0x86, 0x6E, 0x46, 0xC0, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x0B, 0xA2, 0x2A, 0xD8, 0xB4,
+    0xA2, 0x36, 0xD9, 0xB4, 0xA2, 0x06, 0x66, 0x0F, 0x86, 0x66, 0x46, 0x07, 0xA2, 0x02, 0xDA, 0xB4,
+    0x6B, 0x10, 0xA2, 0x3A, 0xD8, 0xB4, 0xA2, 0x1E, 0xD9, 0xB4, 0xA3, 0xE8, 0x60, 0x00, 0x61, 0x30,
+    0xF1, 0x55, 0xA3, 0xE9, 0xF0, 0x65, 0xA2, 0x06, 0x40, 0x30, 0xA2, 0x02, 0xDA, 0xB4, 0x6B, 0x15,
+    0xA2, 0x3A, 0xD8, 0xB4, 0xA2, 0x16, 0xD9, 0xB4, 0xA3, 0xE8, 0x66, 0x89, 0xF6, 0x33, 0xF2, 0x65,
+    0xA2, 0x02, 0x30, 0x01, 0xA2, 0x06, 0x31, 0x03, 0xA2, 0x06, 0x32, 0x07, 0xA2, 0x06, 0xDA, 0xB4,
+    0x6B, 0x1A, 0xA2, 0x0E, 0xD8, 0xB4, 0xA2, 0x3E, 0xD9, 0xB4, 0x12, 0x48, 0x13, 0xDC,
+];
+
+/// The raw words of the translated instructions, block by block.
+///
+const OPCODES: [(u16, u16); 207] = [
+    (0x124E, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x00EE, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x13DC, 0x0000),
+    (0x6801, 0x0000),
+    (0x6905, 0x0000),
+    (0x6A0A, 0x0000),
+    (0x6B01, 0x0000),
+    (0x652A, 0x0000),
+    (0x662B, 0x0000),
+    (0xA216, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA23E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA202, 0x0000),
+    (0x362B, 0x0000),
+    (0xA206, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B06, 0x0000),
+    (0xA21A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA23E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x452A, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B0B, 0x0000),
+    (0xA21E, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA23E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x5560, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B10, 0x0000),
+    (0xA226, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA23E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x76FF, 0x0000),
+    (0x462A, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B15, 0x0000),
+    (0xA22E, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA23E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x9560, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B1A, 0x0000),
+    (0xA232, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA23E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0x2242, 0x0000),
+    (0x6817, 0x0000),
+    (0x691B, 0x0000),
+    (0x6A20, 0x0000),
+    (0x6B01, 0x0000),
+    (0xA20A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA236, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B06, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA20A, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x8750, 0x0000),
+    (0x472A, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B0B, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA20E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x672A, 0x0000),
+    (0x87B1, 0x0000),
+    (0x472B, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B10, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA212, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x6678, 0x0000),
+    (0x671F, 0x0000),
+    (0x8762, 0x0000),
+    (0x4718, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B15, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA216, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x6678, 0x0000),
+    (0x671F, 0x0000),
+    (0x8763, 0x0000),
+    (0x4767, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B1A, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA21A, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x668C, 0x0000),
+    (0x678C, 0x0000),
+    (0x8764, 0x0000),
+    (0x4718, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x682C, 0x0000),
+    (0x6930, 0x0000),
+    (0x6A34, 0x0000),
+    (0x6B01, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA21E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x668C, 0x0000),
+    (0x6778, 0x0000),
+    (0x8765, 0x0000),
+    (0x47EC, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B06, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA222, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x66E0, 0x0000),
+    (0x866E, 0x0000),
+    (0x46C0, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B0B, 0x0000),
+    (0xA22A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA236, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA206, 0x0000),
+    (0x660F, 0x0000),
+    (0x8666, 0x0000),
+    (0x4607, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B10, 0x0000),
+    (0xA23A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA21E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA3E8, 0x0000),
+    (0x6000, 0x0000),
+    (0x6130, 0x0000),
+    (0xF155, 0x0000),
+    (0xA3E9, 0x0000),
+    (0xF065, 0x0000),
+    (0xA206, 0x0000),
+    (0x4030, 0x0000),
+    (0xA202, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B15, 0x0000),
+    (0xA23A, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA216, 0x0000),
+    (0xD9B4, 0x0000),
+    (0xA3E8, 0x0000),
+    (0x6689, 0x0000),
+    (0xF633, 0x0000),
+    (0xF265, 0x0000),
+    (0xA202, 0x0000),
+    (0x3001, 0x0000),
+    (0xA206, 0x0000),
+    (0x3103, 0x0000),
+    (0xA206, 0x0000),
+    (0x3207, 0x0000),
+    (0xA206, 0x0000),
+    (0xDAB4, 0x0000),
+    (0x6B1A, 0x0000),
+    (0xA20E, 0x0000),
+    (0xD8B4, 0x0000),
+    (0xA23E, 0x0000),
+    (0xD9B4, 0x0000),
+    (0x1248, 0x0000),
+    (0x13DC, 0x0000),
+];
+
+/// The translated blocks.
+///
+pub struct Program {
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// Decodes the translated instructions.
+    ///
+    pub fn new() -> Self {
+        Program {
+            instructions: OPCODES
+                .iter()
+                .map(|(raw, next)| Instruction::from_words(*raw, *next))
+                .collect(),
+        }
+    }
+}
+
+impl Default for Program {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> CompiledProgram<VirtualMachine<M, PC, R, FB, KB, RNG, AU>>
+    for Program
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    fn source(&self, address: Address) -> Option<&[u8]> {
+        let (start, end) = match address.get_raw() {
+            0x200 => (0x000, 0x002),
+            0x242 => (0x042, 0x046),
+            0x246 => (0x046, 0x048),
+            0x248 => (0x048, 0x04C),
+            0x24C => (0x04C, 0x04E),
+            0x24E => (0x04E, 0x05E),
+            0x25E => (0x05E, 0x062),
+            0x262 => (0x062, 0x066),
+            0x266 => (0x066, 0x068),
+            0x268 => (0x068, 0x06A),
+            0x26A => (0x06A, 0x070),
+            0x270 => (0x070, 0x074),
+            0x274 => (0x074, 0x078),
+            0x278 => (0x078, 0x07A),
+            0x27A => (0x07A, 0x07C),
+            0x27C => (0x07C, 0x082),
+            0x282 => (0x082, 0x086),
+            0x286 => (0x086, 0x08A),
+            0x28A => (0x08A, 0x08C),
+            0x28C => (0x08C, 0x08E),
+            0x28E => (0x08E, 0x094),
+            0x294 => (0x094, 0x098),
+            0x298 => (0x098, 0x09E),
+            0x29E => (0x09E, 0x0A0),
+            0x2A0 => (0x0A0, 0x0A2),
+            0x2A2 => (0x0A2, 0x0A8),
+            0x2A8 => (0x0A8, 0x0AC),
+            0x2AC => (0x0AC, 0x0B0),
+            0x2B0 => (0x0B0, 0x0B2),
+            0x2B2 => (0x0B2, 0x0B4),
+            0x2B4 => (0x0B4, 0x0BA),
+            0x2BA => (0x0BA, 0x0BE),
+            0x2BE => (0x0BE, 0x0C0),
+            0x2C0 => (0x0C0, 0x0CC),
+            0x2CC => (0x0CC, 0x0D0),
+            0x2D0 => (0x0D0, 0x0D4),
+            0x2D4 => (0x0D4, 0x0DA),
+            0x2DA => (0x0DA, 0x0DE),
+            0x2DE => (0x0DE, 0x0E4),
+            0x2E4 => (0x0E4, 0x0E6),
+            0x2E6 => (0x0E6, 0x0E8),
+            0x2E8 => (0x0E8, 0x0EE),
+            0x2EE => (0x0EE, 0x0F2),
+            0x2F2 => (0x0F2, 0x0FA),
+            0x2FA => (0x0FA, 0x0FC),
+            0x2FC => (0x0FC, 0x0FE),
+            0x2FE => (0x0FE, 0x104),
+            0x304 => (0x104, 0x108),
+            0x308 => (0x108, 0x112),
+            0x312 => (0x112, 0x114),
+            0x314 => (0x114, 0x116),
+            0x316 => (0x116, 0x11C),
+            0x31C => (0x11C, 0x120),
+            0x320 => (0x120, 0x12A),
+            0x32A => (0x12A, 0x12C),
+            0x32C => (0x12C, 0x12E),
+            0x32E => (0x12E, 0x134),
+            0x334 => (0x134, 0x138),
+            0x338 => (0x138, 0x142),
+            0x342 => (0x142, 0x144),
+            0x344 => (0x144, 0x146),
+            0x346 => (0x146, 0x152),
+            0x352 => (0x152, 0x156),
+            0x356 => (0x156, 0x160),
+            0x360 => (0x160, 0x162),
+            0x362 => (0x162, 0x164),
+            0x364 => (0x164, 0x16A),
+            0x36A => (0x16A, 0x16E),
+            0x36E => (0x16E, 0x176),
+            0x376 => (0x176, 0x178),
+            0x378 => (0x178, 0x17A),
+            0x37A => (0x17A, 0x180),
+            0x380 => (0x180, 0x184),
+            0x384 => (0x184, 0x18C),
+            0x38C => (0x18C, 0x18E),
+            0x38E => (0x18E, 0x190),
+            0x390 => (0x190, 0x196),
+            0x396 => (0x196, 0x19A),
+            0x39A => (0x19A, 0x1A2),
+            0x3A2 => (0x1A2, 0x1AA),
+            0x3AA => (0x1AA, 0x1AC),
+            0x3AC => (0x1AC, 0x1AE),
+            0x3AE => (0x1AE, 0x1B4),
+            0x3B4 => (0x1B4, 0x1B8),
+            0x3B8 => (0x1B8, 0x1BE),
+            0x3BE => (0x1BE, 0x1C4),
+            0x3C4 => (0x1C4, 0x1C6),
+            0x3C6 => (0x1C6, 0x1C8),
+            0x3C8 => (0x1C8, 0x1CA),
+            0x3CA => (0x1CA, 0x1CC),
+            0x3CC => (0x1CC, 0x1CE),
+            0x3CE => (0x1CE, 0x1D0),
+            0x3D0 => (0x1D0, 0x1D6),
+            0x3D6 => (0x1D6, 0x1DA),
+            0x3DA => (0x1DA, 0x1DC),
+            0x3DC => (0x1DC, 0x1DE),
+            _ => return None,
+        };
+
+        Some(&ROM[start..end])
+    }
+
+    fn run(
+        &self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        address: Address,
+        budget: usize,
+    ) -> Result<u32, Chip8Error> {
+        let ops = &self.instructions;
+        let interpret: Handler<VirtualMachine<M, PC, R, FB, KB, RNG, AU>> =
+            VirtualMachine::interpret_instruction;
+
+        match address.get_raw() {
+            0x200 => {
+                let mut spent = 0;
+                // 0x200: JP 0x24E
+                spent += vm.execute_decoded(OPCODES[0], ops[0], |vm, _| {
+                    vm.pc.set(Address::new(0x24E));
+                    Ok(true)
+                })?;
+
+                Ok(spent)
+            }
+            0x242 => {
+                let mut spent = 0;
+                // 0x242: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[1], ops[1], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x244: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[2], ops[2], interpret)?;
+
+                Ok(spent)
+            }
+            0x246 => {
+                let mut spent = 0;
+                // 0x246: RET
+                spent += vm.execute_decoded(OPCODES[3], ops[3], interpret)?;
+
+                Ok(spent)
+            }
+            0x248 => {
+                let mut spent = 0;
+                // 0x248: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[4], ops[4], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x24A: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[5], ops[5], interpret)?;
+
+                Ok(spent)
+            }
+            0x24C => {
+                let mut spent = 0;
+                // 0x24C: JP 0x3DC
+                spent += vm.execute_decoded(OPCODES[6], ops[6], |vm, _| {
+                    vm.pc.set(Address::new(0x3DC));
+                    Ok(true)
+                })?;
+
+                Ok(spent)
+            }
+            0x24E => {
+                let mut spent = 0;
+                // 0x24E: LD V8, 0x01
+                spent += vm.execute_decoded(OPCODES[7], ops[7], |vm, _| {
+                    vm.registers.set_v(Register::V8, Byte::new(0x01));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x250: LD V9, 0x05
+                spent += vm.execute_decoded(OPCODES[8], ops[8], |vm, _| {
+                    vm.registers.set_v(Register::V9, Byte::new(0x05));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x252: LD VA, 0x0A
+                spent += vm.execute_decoded(OPCODES[9], ops[9], |vm, _| {
+                    vm.registers.set_v(Register::VA, Byte::new(0x0A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x254: LD VB, 0x01
+                spent += vm.execute_decoded(OPCODES[10], ops[10], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x01));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x256: LD V5, 0x2A
+                spent += vm.execute_decoded(OPCODES[11], ops[11], |vm, _| {
+                    vm.registers.set_v(Register::V5, Byte::new(0x2A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x258: LD V6, 0x2B
+                spent += vm.execute_decoded(OPCODES[12], ops[12], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0x2B));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x25A: LD I, 0x216
+                spent += vm.execute_decoded(OPCODES[13], ops[13], |vm, _| {
+                    vm.registers.set_i(Address::new(0x216));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x25C: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[14], ops[14], interpret)?;
+
+                Ok(spent)
+            }
+            0x25E => {
+                let mut spent = 0;
+                // 0x25E: LD I, 0x23E
+                spent += vm.execute_decoded(OPCODES[15], ops[15], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x260: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[16], ops[16], interpret)?;
+
+                Ok(spent)
+            }
+            0x262 => {
+                let mut spent = 0;
+                // 0x262: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[17], ops[17], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x264: SE V6, 0x2B
+                spent += vm.execute_decoded(OPCODES[18], ops[18], interpret)?;
+
+                Ok(spent)
+            }
+            0x266 => {
+                let mut spent = 0;
+                // 0x266: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[19], ops[19], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x268 => {
+                let mut spent = 0;
+                // 0x268: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[20], ops[20], interpret)?;
+
+                Ok(spent)
+            }
+            0x26A => {
+                let mut spent = 0;
+                // 0x26A: LD VB, 0x06
+                spent += vm.execute_decoded(OPCODES[21], ops[21], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x06));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x26C: LD I, 0x21A
+                spent += vm.execute_decoded(OPCODES[22], ops[22], |vm, _| {
+                    vm.registers.set_i(Address::new(0x21A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x26E: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[23], ops[23], interpret)?;
+
+                Ok(spent)
+            }
+            0x270 => {
+                let mut spent = 0;
+                // 0x270: LD I, 0x23E
+                spent += vm.execute_decoded(OPCODES[24], ops[24], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x272: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[25], ops[25], interpret)?;
+
+                Ok(spent)
+            }
+            0x274 => {
+                let mut spent = 0;
+                // 0x274: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[26], ops[26], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x276: SNE V5, 0x2A
+                spent += vm.execute_decoded(OPCODES[27], ops[27], interpret)?;
+
+                Ok(spent)
+            }
+            0x278 => {
+                let mut spent = 0;
+                // 0x278: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[28], ops[28], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x27A => {
+                let mut spent = 0;
+                // 0x27A: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[29], ops[29], interpret)?;
+
+                Ok(spent)
+            }
+            0x27C => {
+                let mut spent = 0;
+                // 0x27C: LD VB, 0x0B
+                spent += vm.execute_decoded(OPCODES[30], ops[30], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x0B));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x27E: LD I, 0x21E
+                spent += vm.execute_decoded(OPCODES[31], ops[31], |vm, _| {
+                    vm.registers.set_i(Address::new(0x21E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x280: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[32], ops[32], interpret)?;
+
+                Ok(spent)
+            }
+            0x282 => {
+                let mut spent = 0;
+                // 0x282: LD I, 0x23E
+                spent += vm.execute_decoded(OPCODES[33], ops[33], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x284: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[34], ops[34], interpret)?;
+
+                Ok(spent)
+            }
+            0x286 => {
+                let mut spent = 0;
+                // 0x286: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[35], ops[35], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x288: SE V5, V6
+                spent += vm.execute_decoded(OPCODES[36], ops[36], interpret)?;
+
+                Ok(spent)
+            }
+            0x28A => {
+                let mut spent = 0;
+                // 0x28A: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[37], ops[37], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x28C => {
+                let mut spent = 0;
+                // 0x28C: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[38], ops[38], interpret)?;
+
+                Ok(spent)
+            }
+            0x28E => {
+                let mut spent = 0;
+                // 0x28E: LD VB, 0x10
+                spent += vm.execute_decoded(OPCODES[39], ops[39], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x10));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x290: LD I, 0x226
+                spent += vm.execute_decoded(OPCODES[40], ops[40], |vm, _| {
+                    vm.registers.set_i(Address::new(0x226));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x292: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[41], ops[41], interpret)?;
+
+                Ok(spent)
+            }
+            0x294 => {
+                let mut spent = 0;
+                // 0x294: LD I, 0x23E
+                spent += vm.execute_decoded(OPCODES[42], ops[42], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x296: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[43], ops[43], interpret)?;
+
+                Ok(spent)
+            }
+            0x298 => {
+                let mut spent = 0;
+                // 0x298: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[44], ops[44], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x29A: ADD V6, 0xFF
+                spent += vm.execute_decoded(OPCODES[45], ops[45], |vm, _| {
+                    let x = vm.registers.get_v(Register::V6);
+                    let sum = x.wrapping_add(Byte::new(0xFF));
+                    vm.registers.set_v(Register::V6, sum);
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x29C: SNE V6, 0x2A
+                spent += vm.execute_decoded(OPCODES[46], ops[46], interpret)?;
+
+                Ok(spent)
+            }
+            0x29E => {
+                let mut spent = 0;
+                // 0x29E: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[47], ops[47], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x2A0 => {
+                let mut spent = 0;
+                // 0x2A0: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[48], ops[48], interpret)?;
+
+                Ok(spent)
+            }
+            0x2A2 => {
+                let mut spent = 0;
+                // 0x2A2: LD VB, 0x15
+                spent += vm.execute_decoded(OPCODES[49], ops[49], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x15));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2A4: LD I, 0x22E
+                spent += vm.execute_decoded(OPCODES[50], ops[50], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2A6: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[51], ops[51], interpret)?;
+
+                Ok(spent)
+            }
+            0x2A8 => {
+                let mut spent = 0;
+                // 0x2A8: LD I, 0x23E
+                spent += vm.execute_decoded(OPCODES[52], ops[52], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2AA: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[53], ops[53], interpret)?;
+
+                Ok(spent)
+            }
+            0x2AC => {
+                let mut spent = 0;
+                // 0x2AC: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[54], ops[54], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2AE: SNE V5, V6
+                spent += vm.execute_decoded(OPCODES[55], ops[55], interpret)?;
+
+                Ok(spent)
+            }
+            0x2B0 => {
+                let mut spent = 0;
+                // 0x2B0: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[56], ops[56], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x2B2 => {
+                let mut spent = 0;
+                // 0x2B2: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[57], ops[57], interpret)?;
+
+                Ok(spent)
+            }
+            0x2B4 => {
+                let mut spent = 0;
+                // 0x2B4: LD VB, 0x1A
+                spent += vm.execute_decoded(OPCODES[58], ops[58], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x1A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2B6: LD I, 0x232
+                spent += vm.execute_decoded(OPCODES[59], ops[59], |vm, _| {
+                    vm.registers.set_i(Address::new(0x232));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2B8: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[60], ops[60], interpret)?;
+
+                Ok(spent)
+            }
+            0x2BA => {
+                let mut spent = 0;
+                // 0x2BA: LD I, 0x23E
+                spent += vm.execute_decoded(OPCODES[61], ops[61], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2BC: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[62], ops[62], interpret)?;
+
+                Ok(spent)
+            }
+            0x2BE => {
+                let mut spent = 0;
+                // 0x2BE: CALL 0x242
+                spent += vm.execute_decoded(OPCODES[63], ops[63], |vm, _| {
+                    vm.pc.to_subroutine(Address::new(0x242))?;
+                    Ok(true)
+                })?;
+
+                Ok(spent)
+            }
+            0x2C0 => {
+                let mut spent = 0;
+                // 0x2C0: LD V8, 0x17
+                spent += vm.execute_decoded(OPCODES[64], ops[64], |vm, _| {
+                    vm.registers.set_v(Register::V8, Byte::new(0x17));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2C2: LD V9, 0x1B
+                spent += vm.execute_decoded(OPCODES[65], ops[65], |vm, _| {
+                    vm.registers.set_v(Register::V9, Byte::new(0x1B));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2C4: LD VA, 0x20
+                spent += vm.execute_decoded(OPCODES[66], ops[66], |vm, _| {
+                    vm.registers.set_v(Register::VA, Byte::new(0x20));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2C6: LD VB, 0x01
+                spent += vm.execute_decoded(OPCODES[67], ops[67], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x01));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2C8: LD I, 0x20A
+                spent += vm.execute_decoded(OPCODES[68], ops[68], |vm, _| {
+                    vm.registers.set_i(Address::new(0x20A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2CA: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[69], ops[69], interpret)?;
+
+                Ok(spent)
+            }
+            0x2CC => {
+                let mut spent = 0;
+                // 0x2CC: LD I, 0x236
+                spent += vm.execute_decoded(OPCODES[70], ops[70], |vm, _| {
+                    vm.registers.set_i(Address::new(0x236));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2CE: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[71], ops[71], interpret)?;
+
+                Ok(spent)
+            }
+            0x2D0 => {
+                let mut spent = 0;
+                // 0x2D0: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[72], ops[72], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2D2: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[73], ops[73], interpret)?;
+
+                Ok(spent)
+            }
+            0x2D4 => {
+                let mut spent = 0;
+                // 0x2D4: LD VB, 0x06
+                spent += vm.execute_decoded(OPCODES[74], ops[74], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x06));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2D6: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[75], ops[75], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2D8: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[76], ops[76], interpret)?;
+
+                Ok(spent)
+            }
+            0x2DA => {
+                let mut spent = 0;
+                // 0x2DA: LD I, 0x20A
+                spent += vm.execute_decoded(OPCODES[77], ops[77], |vm, _| {
+                    vm.registers.set_i(Address::new(0x20A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2DC: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[78], ops[78], interpret)?;
+
+                Ok(spent)
+            }
+            0x2DE => {
+                let mut spent = 0;
+                // 0x2DE: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[79], ops[79], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2E0: LD V7, V5
+                spent += vm.execute_decoded(OPCODES[80], ops[80], |vm, _| {
+                    let y = vm.registers.get_v(Register::V5);
+                    vm.registers.set_v(Register::V7, y);
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2E2: SNE V7, 0x2A
+                spent += vm.execute_decoded(OPCODES[81], ops[81], interpret)?;
+
+                Ok(spent)
+            }
+            0x2E4 => {
+                let mut spent = 0;
+                // 0x2E4: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[82], ops[82], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x2E6 => {
+                let mut spent = 0;
+                // 0x2E6: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[83], ops[83], interpret)?;
+
+                Ok(spent)
+            }
+            0x2E8 => {
+                let mut spent = 0;
+                // 0x2E8: LD VB, 0x0B
+                spent += vm.execute_decoded(OPCODES[84], ops[84], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x0B));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2EA: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[85], ops[85], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2EC: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[86], ops[86], interpret)?;
+
+                Ok(spent)
+            }
+            0x2EE => {
+                let mut spent = 0;
+                // 0x2EE: LD I, 0x20E
+                spent += vm.execute_decoded(OPCODES[87], ops[87], |vm, _| {
+                    vm.registers.set_i(Address::new(0x20E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2F0: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[88], ops[88], interpret)?;
+
+                Ok(spent)
+            }
+            0x2F2 => {
+                let mut spent = 0;
+                // 0x2F2: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[89], ops[89], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2F4: LD V7, 0x2A
+                spent += vm.execute_decoded(OPCODES[90], ops[90], |vm, _| {
+                    vm.registers.set_v(Register::V7, Byte::new(0x2A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2F6: OR V7, VB
+                spent += vm.execute_decoded(OPCODES[91], ops[91], |vm, _| {
+                    let x = vm.registers.get_v(Register::V7);
+                    let y = vm.registers.get_v(Register::VB);
+                    vm.registers.set_v(Register::V7, x | y);
+                    if vm.quirks.logic_resets_vf {
+                        vm.registers.set_v(Register::VF, Byte::new(0));
+                    }
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x2F8: SNE V7, 0x2B
+                spent += vm.execute_decoded(OPCODES[92], ops[92], interpret)?;
+
+                Ok(spent)
+            }
+            0x2FA => {
+                let mut spent = 0;
+                // 0x2FA: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[93], ops[93], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x2FC => {
+                let mut spent = 0;
+                // 0x2FC: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[94], ops[94], interpret)?;
+
+                Ok(spent)
+            }
+            0x2FE => {
+                let mut spent = 0;
+                // 0x2FE: LD VB, 0x10
+                spent += vm.execute_decoded(OPCODES[95], ops[95], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x10));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x300: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[96], ops[96], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x302: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[97], ops[97], interpret)?;
+
+                Ok(spent)
+            }
+            0x304 => {
+                let mut spent = 0;
+                // 0x304: LD I, 0x212
+                spent += vm.execute_decoded(OPCODES[98], ops[98], |vm, _| {
+                    vm.registers.set_i(Address::new(0x212));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x306: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[99], ops[99], interpret)?;
+
+                Ok(spent)
+            }
+            0x308 => {
+                let mut spent = 0;
+                // 0x308: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[100], ops[100], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x30A: LD V6, 0x78
+                spent += vm.execute_decoded(OPCODES[101], ops[101], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0x78));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x30C: LD V7, 0x1F
+                spent += vm.execute_decoded(OPCODES[102], ops[102], |vm, _| {
+                    vm.registers.set_v(Register::V7, Byte::new(0x1F));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x30E: AND V7, V6
+                spent += vm.execute_decoded(OPCODES[103], ops[103], |vm, _| {
+                    let x = vm.registers.get_v(Register::V7);
+                    let y = vm.registers.get_v(Register::V6);
+                    vm.registers.set_v(Register::V7, x & y);
+                    if vm.quirks.logic_resets_vf {
+                        vm.registers.set_v(Register::VF, Byte::new(0));
+                    }
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x310: SNE V7, 0x18
+                spent += vm.execute_decoded(OPCODES[104], ops[104], interpret)?;
+
+                Ok(spent)
+            }
+            0x312 => {
+                let mut spent = 0;
+                // 0x312: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[105], ops[105], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x314 => {
+                let mut spent = 0;
+                // 0x314: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[106], ops[106], interpret)?;
+
+                Ok(spent)
+            }
+            0x316 => {
+                let mut spent = 0;
+                // 0x316: LD VB, 0x15
+                spent += vm.execute_decoded(OPCODES[107], ops[107], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x15));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x318: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[108], ops[108], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x31A: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[109], ops[109], interpret)?;
+
+                Ok(spent)
+            }
+            0x31C => {
+                let mut spent = 0;
+                // 0x31C: LD I, 0x216
+                spent += vm.execute_decoded(OPCODES[110], ops[110], |vm, _| {
+                    vm.registers.set_i(Address::new(0x216));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x31E: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[111], ops[111], interpret)?;
+
+                Ok(spent)
+            }
+            0x320 => {
+                let mut spent = 0;
+                // 0x320: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[112], ops[112], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x322: LD V6, 0x78
+                spent += vm.execute_decoded(OPCODES[113], ops[113], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0x78));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x324: LD V7, 0x1F
+                spent += vm.execute_decoded(OPCODES[114], ops[114], |vm, _| {
+                    vm.registers.set_v(Register::V7, Byte::new(0x1F));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x326: XOR V7, V6
+                spent += vm.execute_decoded(OPCODES[115], ops[115], |vm, _| {
+                    let x = vm.registers.get_v(Register::V7);
+                    let y = vm.registers.get_v(Register::V6);
+                    vm.registers.set_v(Register::V7, x ^ y);
+                    if vm.quirks.logic_resets_vf {
+                        vm.registers.set_v(Register::VF, Byte::new(0));
+                    }
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x328: SNE V7, 0x67
+                spent += vm.execute_decoded(OPCODES[116], ops[116], interpret)?;
+
+                Ok(spent)
+            }
+            0x32A => {
+                let mut spent = 0;
+                // 0x32A: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[117], ops[117], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x32C => {
+                let mut spent = 0;
+                // 0x32C: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[118], ops[118], interpret)?;
+
+                Ok(spent)
+            }
+            0x32E => {
+                let mut spent = 0;
+                // 0x32E: LD VB, 0x1A
+                spent += vm.execute_decoded(OPCODES[119], ops[119], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x1A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x330: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[120], ops[120], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x332: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[121], ops[121], interpret)?;
+
+                Ok(spent)
+            }
+            0x334 => {
+                let mut spent = 0;
+                // 0x334: LD I, 0x21A
+                spent += vm.execute_decoded(OPCODES[122], ops[122], |vm, _| {
+                    vm.registers.set_i(Address::new(0x21A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x336: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[123], ops[123], interpret)?;
+
+                Ok(spent)
+            }
+            0x338 => {
+                let mut spent = 0;
+                // 0x338: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[124], ops[124], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x33A: LD V6, 0x8C
+                spent += vm.execute_decoded(OPCODES[125], ops[125], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0x8C));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x33C: LD V7, 0x8C
+                spent += vm.execute_decoded(OPCODES[126], ops[126], |vm, _| {
+                    vm.registers.set_v(Register::V7, Byte::new(0x8C));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x33E: ADD V7, V6
+                spent += vm.execute_decoded(OPCODES[127], ops[127], |vm, _| {
+                    let x = vm.registers.get_v(Register::V7);
+                    let y = vm.registers.get_v(Register::V6);
+                    let (sum, carry) = x.get_raw().overflowing_add(y.get_raw());
+                    vm.registers.set_v(Register::V7, Byte::new(sum));
+                    vm.registers.set_v(Register::VF, Byte::new(carry as u8));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x340: SNE V7, 0x18
+                spent += vm.execute_decoded(OPCODES[128], ops[128], interpret)?;
+
+                Ok(spent)
+            }
+            0x342 => {
+                let mut spent = 0;
+                // 0x342: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[129], ops[129], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x344 => {
+                let mut spent = 0;
+                // 0x344: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[130], ops[130], interpret)?;
+
+                Ok(spent)
+            }
+            0x346 => {
+                let mut spent = 0;
+                // 0x346: LD V8, 0x2C
+                spent += vm.execute_decoded(OPCODES[131], ops[131], |vm, _| {
+                    vm.registers.set_v(Register::V8, Byte::new(0x2C));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x348: LD V9, 0x30
+                spent += vm.execute_decoded(OPCODES[132], ops[132], |vm, _| {
+                    vm.registers.set_v(Register::V9, Byte::new(0x30));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x34A: LD VA, 0x34
+                spent += vm.execute_decoded(OPCODES[133], ops[133], |vm, _| {
+                    vm.registers.set_v(Register::VA, Byte::new(0x34));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x34C: LD VB, 0x01
+                spent += vm.execute_decoded(OPCODES[134], ops[134], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x01));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x34E: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[135], ops[135], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x350: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[136], ops[136], interpret)?;
+
+                Ok(spent)
+            }
+            0x352 => {
+                let mut spent = 0;
+                // 0x352: LD I, 0x21E
+                spent += vm.execute_decoded(OPCODES[137], ops[137], |vm, _| {
+                    vm.registers.set_i(Address::new(0x21E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x354: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[138], ops[138], interpret)?;
+
+                Ok(spent)
+            }
+            0x356 => {
+                let mut spent = 0;
+                // 0x356: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[139], ops[139], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x358: LD V6, 0x8C
+                spent += vm.execute_decoded(OPCODES[140], ops[140], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0x8C));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x35A: LD V7, 0x78
+                spent += vm.execute_decoded(OPCODES[141], ops[141], |vm, _| {
+                    vm.registers.set_v(Register::V7, Byte::new(0x78));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x35C: SUB V7, V6
+                spent += vm.execute_decoded(OPCODES[142], ops[142], |vm, _| {
+                    let x = vm.registers.get_v(Register::V7);
+                    let y = vm.registers.get_v(Register::V6);
+                    vm.registers.set_v(Register::V7, x.wrapping_sub(y));
+                    vm.registers.set_v(Register::VF, Byte::new((x >= y) as u8));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x35E: SNE V7, 0xEC
+                spent += vm.execute_decoded(OPCODES[143], ops[143], interpret)?;
+
+                Ok(spent)
+            }
+            0x360 => {
+                let mut spent = 0;
+                // 0x360: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[144], ops[144], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x362 => {
+                let mut spent = 0;
+                // 0x362: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[145], ops[145], interpret)?;
+
+                Ok(spent)
+            }
+            0x364 => {
+                let mut spent = 0;
+                // 0x364: LD VB, 0x06
+                spent += vm.execute_decoded(OPCODES[146], ops[146], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x06));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x366: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[147], ops[147], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x368: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[148], ops[148], interpret)?;
+
+                Ok(spent)
+            }
+            0x36A => {
+                let mut spent = 0;
+                // 0x36A: LD I, 0x222
+                spent += vm.execute_decoded(OPCODES[149], ops[149], |vm, _| {
+                    vm.registers.set_i(Address::new(0x222));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x36C: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[150], ops[150], interpret)?;
+
+                Ok(spent)
+            }
+            0x36E => {
+                let mut spent = 0;
+                // 0x36E: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[151], ops[151], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x370: LD V6, 0xE0
+                spent += vm.execute_decoded(OPCODES[152], ops[152], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0xE0));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x372: SHL V6, V6
+                spent += vm.execute_decoded(OPCODES[153], ops[153], interpret)?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x374: SNE V6, 0xC0
+                spent += vm.execute_decoded(OPCODES[154], ops[154], interpret)?;
+
+                Ok(spent)
+            }
+            0x376 => {
+                let mut spent = 0;
+                // 0x376: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[155], ops[155], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x378 => {
+                let mut spent = 0;
+                // 0x378: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[156], ops[156], interpret)?;
+
+                Ok(spent)
+            }
+            0x37A => {
+                let mut spent = 0;
+                // 0x37A: LD VB, 0x0B
+                spent += vm.execute_decoded(OPCODES[157], ops[157], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x0B));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x37C: LD I, 0x22A
+                spent += vm.execute_decoded(OPCODES[158], ops[158], |vm, _| {
+                    vm.registers.set_i(Address::new(0x22A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x37E: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[159], ops[159], interpret)?;
+
+                Ok(spent)
+            }
+            0x380 => {
+                let mut spent = 0;
+                // 0x380: LD I, 0x236
+                spent += vm.execute_decoded(OPCODES[160], ops[160], |vm, _| {
+                    vm.registers.set_i(Address::new(0x236));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x382: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[161], ops[161], interpret)?;
+
+                Ok(spent)
+            }
+            0x384 => {
+                let mut spent = 0;
+                // 0x384: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[162], ops[162], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x386: LD V6, 0x0F
+                spent += vm.execute_decoded(OPCODES[163], ops[163], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0x0F));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x388: SHR V6, V6
+                spent += vm.execute_decoded(OPCODES[164], ops[164], interpret)?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x38A: SNE V6, 0x07
+                spent += vm.execute_decoded(OPCODES[165], ops[165], interpret)?;
+
+                Ok(spent)
+            }
+            0x38C => {
+                let mut spent = 0;
+                // 0x38C: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[166], ops[166], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x38E => {
+                let mut spent = 0;
+                // 0x38E: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[167], ops[167], interpret)?;
+
+                Ok(spent)
+            }
+            0x390 => {
+                let mut spent = 0;
+                // 0x390: LD VB, 0x10
+                spent += vm.execute_decoded(OPCODES[168], ops[168], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x10));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x392: LD I, 0x23A
+                spent += vm.execute_decoded(OPCODES[169], ops[169], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x394: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[170], ops[170], interpret)?;
+
+                Ok(spent)
+            }
+            0x396 => {
+                let mut spent = 0;
+                // 0x396: LD I, 0x21E
+                spent += vm.execute_decoded(OPCODES[171], ops[171], |vm, _| {
+                    vm.registers.set_i(Address::new(0x21E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x398: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[172], ops[172], interpret)?;
+
+                Ok(spent)
+            }
+            0x39A => {
+                let mut spent = 0;
+                // 0x39A: LD I, 0x3E8
+                spent += vm.execute_decoded(OPCODES[173], ops[173], |vm, _| {
+                    vm.registers.set_i(Address::new(0x3E8));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x39C: LD V0, 0x00
+                spent += vm.execute_decoded(OPCODES[174], ops[174], |vm, _| {
+                    vm.registers.set_v(Register::V0, Byte::new(0x00));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x39E: LD V1, 0x30
+                spent += vm.execute_decoded(OPCODES[175], ops[175], |vm, _| {
+                    vm.registers.set_v(Register::V1, Byte::new(0x30));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3A0: LD [I], V1
+                spent += vm.execute_decoded(OPCODES[176], ops[176], interpret)?;
+
+                Ok(spent)
+            }
+            0x3A2 => {
+                let mut spent = 0;
+                // 0x3A2: LD I, 0x3E9
+                spent += vm.execute_decoded(OPCODES[177], ops[177], |vm, _| {
+                    vm.registers.set_i(Address::new(0x3E9));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3A4: LD V0, [I]
+                spent += vm.execute_decoded(OPCODES[178], ops[178], interpret)?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3A6: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[179], ops[179], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3A8: SNE V0, 0x30
+                spent += vm.execute_decoded(OPCODES[180], ops[180], interpret)?;
+
+                Ok(spent)
+            }
+            0x3AA => {
+                let mut spent = 0;
+                // 0x3AA: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[181], ops[181], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x3AC => {
+                let mut spent = 0;
+                // 0x3AC: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[182], ops[182], interpret)?;
+
+                Ok(spent)
+            }
+            0x3AE => {
+                let mut spent = 0;
+                // 0x3AE: LD VB, 0x15
+                spent += vm.execute_decoded(OPCODES[183], ops[183], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x15));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3B0: LD I, 0x23A
+                spent += vm.execute_decoded(OPCODES[184], ops[184], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3B2: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[185], ops[185], interpret)?;
+
+                Ok(spent)
+            }
+            0x3B4 => {
+                let mut spent = 0;
+                // 0x3B4: LD I, 0x216
+                spent += vm.execute_decoded(OPCODES[186], ops[186], |vm, _| {
+                    vm.registers.set_i(Address::new(0x216));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3B6: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[187], ops[187], interpret)?;
+
+                Ok(spent)
+            }
+            0x3B8 => {
+                let mut spent = 0;
+                // 0x3B8: LD I, 0x3E8
+                spent += vm.execute_decoded(OPCODES[188], ops[188], |vm, _| {
+                    vm.registers.set_i(Address::new(0x3E8));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3BA: LD V6, 0x89
+                spent += vm.execute_decoded(OPCODES[189], ops[189], |vm, _| {
+                    vm.registers.set_v(Register::V6, Byte::new(0x89));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3BC: LD B, V6
+                spent += vm.execute_decoded(OPCODES[190], ops[190], interpret)?;
+
+                Ok(spent)
+            }
+            0x3BE => {
+                let mut spent = 0;
+                // 0x3BE: LD V2, [I]
+                spent += vm.execute_decoded(OPCODES[191], ops[191], interpret)?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3C0: LD I, 0x202
+                spent += vm.execute_decoded(OPCODES[192], ops[192], |vm, _| {
+                    vm.registers.set_i(Address::new(0x202));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3C2: SE V0, 0x01
+                spent += vm.execute_decoded(OPCODES[193], ops[193], interpret)?;
+
+                Ok(spent)
+            }
+            0x3C4 => {
+                let mut spent = 0;
+                // 0x3C4: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[194], ops[194], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x3C6 => {
+                let mut spent = 0;
+                // 0x3C6: SE V1, 0x03
+                spent += vm.execute_decoded(OPCODES[195], ops[195], interpret)?;
+
+                Ok(spent)
+            }
+            0x3C8 => {
+                let mut spent = 0;
+                // 0x3C8: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[196], ops[196], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x3CA => {
+                let mut spent = 0;
+                // 0x3CA: SE V2, 0x07
+                spent += vm.execute_decoded(OPCODES[197], ops[197], interpret)?;
+
+                Ok(spent)
+            }
+            0x3CC => {
+                let mut spent = 0;
+                // 0x3CC: LD I, 0x206
+                spent += vm.execute_decoded(OPCODES[198], ops[198], |vm, _| {
+                    vm.registers.set_i(Address::new(0x206));
+                    Ok(false)
+                })?;
+
+                Ok(spent)
+            }
+            0x3CE => {
+                let mut spent = 0;
+                // 0x3CE: DRW VA, VB, 4
+                spent += vm.execute_decoded(OPCODES[199], ops[199], interpret)?;
+
+                Ok(spent)
+            }
+            0x3D0 => {
+                let mut spent = 0;
+                // 0x3D0: LD VB, 0x1A
+                spent += vm.execute_decoded(OPCODES[200], ops[200], |vm, _| {
+                    vm.registers.set_v(Register::VB, Byte::new(0x1A));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3D2: LD I, 0x20E
+                spent += vm.execute_decoded(OPCODES[201], ops[201], |vm, _| {
+                    vm.registers.set_i(Address::new(0x20E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3D4: DRW V8, VB, 4
+                spent += vm.execute_decoded(OPCODES[202], ops[202], interpret)?;
+
+                Ok(spent)
+            }
+            0x3D6 => {
+                let mut spent = 0;
+                // 0x3D6: LD I, 0x23E
+                spent += vm.execute_decoded(OPCODES[203], ops[203], |vm, _| {
+                    vm.registers.set_i(Address::new(0x23E));
+                    Ok(false)
+                })?;
+
+                if spent as usize >= budget {
+                    return Ok(spent);
+                }
+                // 0x3D8: DRW V9, VB, 4
+                spent += vm.execute_decoded(OPCODES[204], ops[204], interpret)?;
+
+                Ok(spent)
+            }
+            0x3DA => {
+                let mut spent = 0;
+                // 0x3DA: JP 0x248
+                spent += vm.execute_decoded(OPCODES[205], ops[205], |vm, _| {
+                    vm.pc.set(Address::new(0x248));
+                    Ok(true)
+                })?;
+
+                Ok(spent)
+            }
+            0x3DC => {
+                let mut spent = 0;
+                // 0x3DC: JP 0x3DC
+                spent += vm.execute_decoded(OPCODES[206], ops[206], |vm, _| {
+                    vm.pc.set(Address::new(0x3DC));
+                    Ok(true)
+                })?;
+
+                Ok(spent)
+            }
+            _ => Ok(0),
+        }
+    }
+}
@@ -0,0 +1,474 @@
+//! Writes the Rust source of the translated blocks.
+//!
+//! The module holds a copy of the ROM, the raw words of every translated
+//! instruction and a `Program` implementing `cpu::CompiledProgram` for any
+//! `VirtualMachine`. Each block becomes an arm of `Program::run` that hands
+//! its instructions to `VirtualMachine::execute_decoded` one after another,
+//! checking the budget in between.
+//!
+//! The common register, timer and branch instructions are translated into
+//! closures. The rest, including everything that draws, skips, touches the
+//! keyboard or writes memory, is handed to `interpret_instruction`.
+//!
+
+use super::flow::{find_blocks, Block};
+use data::{Address, Byte};
+use instruction::{Instruction, START};
+use model::Register;
+use std::fmt::Write;
+
+/// The widest line rustfmt leaves alone.
+const MAX_WIDTH: usize = 100;
+
+/// The bytes of the ROM per line.
+const BYTES_PER_LINE: usize = 16;
+
+/// The type parameters of the machine, and their bounds.
+const PARAMETERS: &str = "M, PC, R, FB, KB, RNG, AU";
+
+const BOUNDS: [(&str, &str); 7] = [
+    ("M", "Chip8Memory"),
+    ("PC", "Chip8ProgramCounter"),
+    ("R", "Chip8RegisterBank"),
+    ("FB", "Chip8FrameBuffer"),
+    ("KB", "Chip8Keyboard"),
+    ("RNG", "Chip8Random"),
+    ("AU", "Chip8Audio"),
+];
+
+/// A translated instruction: the body of its handler, and whether it sets
+/// the program counter itself.
+///
+struct Translation {
+    body: Vec<String>,
+    branches: bool,
+}
+
+/// What the generated code refers to, so only those names are imported.
+///
+#[derive(Default)]
+struct Names {
+    byte: bool,
+    register: bool,
+    interpret: bool,
+}
+
+/// Returns the Rust name of a register.
+///
+fn v(register: Register) -> String {
+    format!("Register::V{:X}", register as u8)
+}
+
+/// Translates the instruction into the body of a handler, or returns None
+/// if it is left to the interpreter.
+///
+///### Arguments
+///
+///- **instruction** : The instruction.
+///- **names**       : Records the names the body uses.
+///
+fn translate(instruction: Instruction, names: &mut Names) -> Option<Translation> {
+    use Instruction::*;
+
+    let addr = |a: Address| format!("Address::new(0x{:03X})", a.get_raw());
+    let byte = |b: Byte| format!("Byte::new(0x{:02X})", b.get_raw());
+    let get = |r| format!("vm.registers.get_v({})", v(r));
+    let set = |r, value: &str| format!("vm.registers.set_v({}, {});", v(r), value);
+
+    // Mirrors the interpreter, the flag is set after the result.
+    let logic = |x, y, op: &str| {
+        vec![
+            format!("let x = {};", get(x)),
+            format!("let y = {};", get(y)),
+            set(x, &format!("x {} y", op)),
+            "if vm.quirks.logic_resets_vf {".to_string(),
+            format!("    {}", set(Register::VF, "Byte::new(0)")),
+            "}".to_string(),
+        ]
+    };
+    let sub = |x, y, result: &str, flag: &str| {
+        vec![
+            format!("let x = {};", get(x)),
+            format!("let y = {};", get(y)),
+            set(x, result),
+            set(Register::VF, &format!("Byte::new(({}) as u8)", flag)),
+        ]
+    };
+
+    let (body, branches) = match instruction {
+        Cls => (vec!["vm.framebuffer.clear();".to_string()], false),
+        Jump(a) => (vec![format!("vm.pc.set({});", addr(a))], true),
+        Call(a) => (vec![format!("vm.pc.to_subroutine({})?;", addr(a))], true),
+        Load(x, b) => (vec![set(x, &byte(b))], false),
+        Add(x, b) => (
+            vec![
+                format!("let x = {};", get(x)),
+                format!("let sum = x.wrapping_add({});", byte(b)),
+                set(x, "sum"),
+            ],
+            false,
+        ),
+        LoadFromReg(x, y) => (vec![format!("let y = {};", get(y)), set(x, "y")], false),
+        Or(x, y) => (logic(x, y, "|"), false),
+        And(x, y) => (logic(x, y, "&"), false),
+        XOr(x, y) => (logic(x, y, "^"), false),
+        AddReg(x, y) => (
+            vec![
+                format!("let x = {};", get(x)),
+                format!("let y = {};", get(y)),
+                "let (sum, carry) = x.get_raw().overflowing_add(y.get_raw());".to_string(),
+                set(x, "Byte::new(sum)"),
+                set(Register::VF, "Byte::new(carry as u8)"),
+            ],
+            false,
+        ),
+        SubReg(x, y) => (sub(x, y, "x.wrapping_sub(y)", "x >= y"), false),
+        ReverseSub(x, y) => (sub(x, y, "y.wrapping_sub(x)", "y >= x"), false),
+        LoadInstr(a) => (vec![format!("vm.registers.set_i({});", addr(a))], false),
+        Rand(x, b) => (
+            vec![
+                format!("let n = vm.random.next_byte() & {};", byte(b)),
+                set(x, "n"),
+            ],
+            false,
+        ),
+        LoadDelayTimer(x) => (vec![set(x, "Byte::new(vm.delay_timer)")], false),
+        SetDelayTimer(x) => (
+            vec![format!("vm.delay_timer = {}.get_raw();", get(x))],
+            false,
+        ),
+        IncrementAddress(x) => (
+            vec![
+                "let i = vm.registers.get_i();".to_string(),
+                format!("vm.registers.set_i(i + {});", get(x)),
+            ],
+            false,
+        ),
+        _ => return None,
+    };
+
+    names.byte |= body.iter().any(|line| line.contains("Byte::"));
+    names.register |= body.iter().any(|line| line.contains("Register::"));
+
+    Some(Translation { body, branches })
+}
+
+/// Writes a `use` of the names, wrapped the way rustfmt wraps it.
+///
+fn write_use(out: &mut String, path: &str, names: &[&str]) {
+    let line = format!("use {}::{{{}}};", path, names.join(", "));
+
+    if line.len() <= MAX_WIDTH {
+        writeln!(out, "{}", line).unwrap();
+        return;
+    }
+
+    writeln!(out, "use {}::{{", path).unwrap();
+
+    let mut current = String::new();
+    for name in names {
+        let item = format!("{},", name);
+
+        if !current.is_empty() && 4 + current.len() + 1 + item.len() > MAX_WIDTH {
+            writeln!(out, "    {}", current).unwrap();
+            current.clear();
+        }
+
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&item);
+    }
+
+    writeln!(out, "    {}", current).unwrap();
+    writeln!(out, "}};").unwrap();
+}
+
+/// Writes the arm of `run` for the block.
+///
+///### Arguments
+///
+///- **out**   : The source so far.
+///- **block** : The block.
+///- **first** : The index in `OPCODES` of the first instruction.
+///- **names** : Records the names the arm uses.
+///
+fn write_block(out: &mut String, block: &Block, first: usize, names: &mut Names) {
+    writeln!(out, "            0x{:03X} => {{", block.start()).unwrap();
+    writeln!(out, "                let mut spent = 0;").unwrap();
+
+    for (n, op) in block.ops.iter().enumerate() {
+        let k = first + n;
+
+        if n > 0 {
+            writeln!(out).unwrap();
+            writeln!(out, "                if spent as usize >= budget {{").unwrap();
+            writeln!(out, "                    return Ok(spent);").unwrap();
+            writeln!(out, "                }}").unwrap();
+        }
+
+        writeln!(
+            out,
+            "                // 0x{:03X}: {}",
+            op.addr, op.instruction
+        )
+        .unwrap();
+
+        match translate(op.instruction, names) {
+            Some(translation) => {
+                writeln!(
+                    out,
+                    "                spent += vm.execute_decoded(OPCODES[{}], ops[{}], |vm, _| {{",
+                    k, k
+                )
+                .unwrap();
+
+                for line in translation.body.iter() {
+                    writeln!(out, "                    {}", line).unwrap();
+                }
+
+                writeln!(out, "                    Ok({})", translation.branches).unwrap();
+                writeln!(out, "                }})?;").unwrap();
+            }
+            None => {
+                names.interpret = true;
+
+                writeln!(
+                    out,
+                    "                spent += vm.execute_decoded(OPCODES[{}], ops[{}], interpret)?;",
+                    k, k
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "                Ok(spent)").unwrap();
+    writeln!(out, "            }}").unwrap();
+}
+
+/// Translates the reachable code of the rom into the source of a Rust module.
+///
+/// The module needs the `cpu`, `data`, `instruction` and `model` crates.
+///
+///### Arguments
+///
+///- **rom**  : The program, as loaded at 0x200.
+///- **name** : The name of the rom, for the documentation of the module.
+///
+pub fn recompile(rom: &[u8], name: &str) -> String {
+    let blocks = find_blocks(rom);
+    let opcodes: Vec<(u16, u16)> = blocks
+        .iter()
+        .flat_map(|block| block.ops.iter().map(|op| op.opcode))
+        .collect();
+
+    let mut names = Names::default();
+
+    // The arms of `run` come first, they decide what is imported.
+    let mut arms = String::new();
+    let mut first = 0;
+    for block in blocks.iter() {
+        write_block(&mut arms, block, first, &mut names);
+        first += block.ops.len();
+    }
+
+    let multiple = blocks.iter().any(|block| block.ops.len() > 1);
+    let vm = if blocks.is_empty() { "_vm" } else { "vm" };
+    let budget = if multiple { "budget" } else { "_budget" };
+
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "//! `{}` translated to Rust by the recompiler, do not edit.",
+        name
+    )
+    .unwrap();
+    writeln!(out, "//!").unwrap();
+    writeln!(
+        out,
+        "//! {} blocks, {} instructions. Load `ROM` and run it with",
+        blocks.len(),
+        opcodes.len()
+    )
+    .unwrap();
+    writeln!(out, "//! `VirtualMachine::run_compiled_frame`.").unwrap();
+    writeln!(out, "//!").unwrap();
+    writeln!(out).unwrap();
+
+    let mut cpu = vec!["CompiledProgram"];
+    if names.interpret {
+        cpu.push("Handler");
+    }
+    cpu.push("VirtualMachine");
+    write_use(&mut out, "cpu", &cpu);
+
+    let mut data = vec!["Address"];
+    if names.byte {
+        data.push("Byte");
+    }
+    write_use(&mut out, "data", &data);
+    writeln!(out, "use instruction::Instruction;").unwrap();
+
+    let mut model = vec![
+        "Chip8Audio",
+        "Chip8Error",
+        "Chip8FrameBuffer",
+        "Chip8Keyboard",
+        "Chip8Memory",
+        "Chip8ProgramCounter",
+        "Chip8Random",
+        "Chip8RegisterBank",
+    ];
+    if names.register {
+        model.push("Register");
+    }
+    write_use(&mut out, "model", &model);
+    writeln!(out).unwrap();
+
+    writeln!(out, "/// The rom, as loaded at 0x{:03X}.", START).unwrap();
+    writeln!(out, "///").unwrap();
+    writeln!(out, "pub const ROM: [u8; {}] = [", rom.len()).unwrap();
+    for line in rom.chunks(BYTES_PER_LINE) {
+        let bytes: Vec<String> = line.iter().map(|b| format!("0x{:02X},", b)).collect();
+        writeln!(out, "    {}", bytes.join(" ")).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(
+        out,
+        "/// The raw words of the translated instructions, block by block."
+    )
+    .unwrap();
+    writeln!(out, "///").unwrap();
+    writeln!(out, "const OPCODES: [(u16, u16); {}] = [", opcodes.len()).unwrap();
+    for (raw, next) in opcodes.iter() {
+        writeln!(out, "    (0x{:04X}, 0x{:04X}),", raw, next).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+
+    out.push_str(
+        "/// The translated blocks.
+///
+pub struct Program {
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// Decodes the translated instructions.
+    ///
+    pub fn new() -> Self {
+        Program {
+            instructions: OPCODES
+                .iter()
+                .map(|(raw, next)| Instruction::from_words(*raw, *next))
+                .collect(),
+        }
+    }
+}
+
+impl Default for Program {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+",
+    );
+
+    let bounds: Vec<String> = BOUNDS
+        .iter()
+        .map(|(parameter, bound)| format!("    {}: {},\n", parameter, bound))
+        .collect();
+    let bounds = bounds.concat();
+
+    writeln!(
+        out,
+        "impl<{p}> CompiledProgram<VirtualMachine<{p}>>\n    for Program\nwhere\n{}{{",
+        bounds,
+        p = PARAMETERS
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        "    fn source(&self, address: Address) -> Option<&[u8]> {{"
+    )
+    .unwrap();
+    writeln!(out, "        let (start, end) = match address.get_raw() {{").unwrap();
+    for block in blocks.iter() {
+        writeln!(
+            out,
+            "            0x{:03X} => (0x{:03X}, 0x{:03X}),",
+            block.start(),
+            block.start() - START,
+            block.end() - START
+        )
+        .unwrap();
+    }
+    writeln!(out, "            _ => return None,").unwrap();
+    writeln!(out, "        }};").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "        Some(&ROM[start..end])").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(out, "    fn run(").unwrap();
+    writeln!(out, "        &self,").unwrap();
+    writeln!(out, "        {}: &mut VirtualMachine<{}>,", vm, PARAMETERS).unwrap();
+    writeln!(out, "        address: Address,").unwrap();
+    writeln!(out, "        {}: usize,", budget).unwrap();
+    writeln!(out, "    ) -> Result<u32, Chip8Error> {{").unwrap();
+    if !blocks.is_empty() {
+        writeln!(out, "        let ops = &self.instructions;").unwrap();
+    }
+    if names.interpret {
+        writeln!(
+            out,
+            "        let interpret: Handler<VirtualMachine<{}>> =",
+            PARAMETERS
+        )
+        .unwrap();
+        writeln!(out, "            VirtualMachine::interpret_instruction;").unwrap();
+    }
+    if !blocks.is_empty() {
+        writeln!(out).unwrap();
+    }
+    writeln!(out, "        match address.get_raw() {{").unwrap();
+    out.push_str(&arms);
+    writeln!(out, "            _ => Ok(0),").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_instructions_are_translated_and_the_rest_interpreted() {
+        let rom = [
+            0x60, 0x05, // V0 = 5
+            0x80, 0x14, // V0 += V1
+            0xD0, 0x15, // draw
+            0x12, 0x00, // jump 0x200
+        ];
+
+        let source = recompile(&rom, "test");
+
+        assert!(source.contains("use data::{Address, Byte};"));
+        assert!(source.contains("vm.registers.set_v(Register::V0, Byte::new(0x05));"));
+        assert!(source.contains("let (sum, carry) = x.get_raw().overflowing_add(y.get_raw());"));
+        assert!(source.contains("spent += vm.execute_decoded(OPCODES[2], ops[2], interpret)?;"));
+        assert!(source.contains("vm.pc.set(Address::new(0x200));"));
+        assert!(source.contains("0x200 => (0x000, 0x006),"));
+        assert!(source.contains("0x206 => (0x006, 0x008),"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Finds the blocks of a ROM.
+//!
+//! The reachable code is found with `instruction::Rom::walk`, the same walk
+//! the disassembler does.
+//!
+//! Every address that can be reached other than by falling through to it
+//! starts a block. Blocks run to the next such address, or to the first
+//! instruction that ends a block, see `Instruction::ends_block`.
+//!
+
+use instruction::{Instruction, Rom, START};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An instruction of a block.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Op {
+    pub addr: u16,
+
+    /// The raw words, the second one is only used by `F000 nnnn`.
+    pub opcode: (u16, u16),
+
+    pub instruction: Instruction,
+}
+
+/// Straight-line code, only ever entered at its first instruction.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub ops: Vec<Op>,
+}
+
+impl Block {
+    /// Returns the address of the first instruction.
+    ///
+    pub fn start(&self) -> u16 {
+        self.ops[0].addr
+    }
+
+    /// Returns the address right after the last instruction.
+    ///
+    pub fn end(&self) -> u16 {
+        let last = self.ops[self.ops.len() - 1];
+
+        last.addr + last.instruction.size()
+    }
+}
+
+/// Finds the blocks of the reachable code, in address order.
+///
+///### Arguments
+///
+///- **rom** : The program, as loaded at 0x200.
+///
+pub fn find_blocks(rom: &[u8]) -> Vec<Block> {
+    let mut code: BTreeMap<u16, Op> = BTreeMap::new();
+    let mut leaders = BTreeSet::new();
+    leaders.insert(START);
+
+    Rom::new(rom).walk(|step| {
+        code.insert(
+            step.addr,
+            Op {
+                addr: step.addr,
+                opcode: step.opcode,
+                instruction: step.instruction,
+            },
+        );
+
+        // Only falling through to the next instruction stays in the block.
+        if step.instruction.ends_block() {
+            leaders.extend(step.successors.iter().copied());
+        }
+
+        true
+    });
+
+    leaders
+        .iter()
+        .filter(|addr| code.contains_key(addr))
+        .map(|start| {
+            let mut ops = Vec::new();
+            let mut addr = *start;
+
+            while let Some(op) = code.get(&addr) {
+                ops.push(*op);
+                addr = addr.wrapping_add(op.instruction.size());
+
+                if op.instruction.ends_block() || leaders.contains(&addr) {
+                    break;
+                }
+            }
+
+            Block { ops }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn starts(blocks: &[Block]) -> Vec<(u16, u16)> {
+        blocks
+            .iter()
+            .map(|block| (block.start(), block.end()))
+            .collect()
+    }
+
+    #[test]
+    fn blocks_start_at_branch_targets_and_after_branches() {
+        let rom = [
+            0x60, 0x01, // V0 = 1
+            0x22, 0x0A, // call 0x20A
+            0x30, 0x02, // skip if V0 == 2
+            0x70, 0x01, // V0 += 1
+            0x12, 0x00, // jump 0x200
+            0x61, 0x02, // 0x20A: V1 = 2
+            0x00, 0xEE, // return
+        ];
+
+        assert_eq!(
+            starts(&find_blocks(&rom)),
+            [
+                (0x200, 0x204),
+                (0x204, 0x206),
+                (0x206, 0x208),
+                (0x208, 0x20A),
+                (0x20A, 0x20E)
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_end_blocks_and_data_is_left_out() {
+        let rom = [
+            0xA2, 0x08, // I = 0x208
+            0xF0, 0x55, // store V0
+            0x71, 0x01, // V1 += 1
+            0x12, 0x02, // jump 0x202
+            0xFF, 0xFF, // 0x208: data
+        ];
+
+        let blocks = find_blocks(&rom);
+
+        assert_eq!(
+            starts(&blocks),
+            [(0x200, 0x202), (0x202, 0x204), (0x204, 0x208)]
+        );
+        assert_eq!(blocks[2].ops[1].instruction, Instruction::new(0x1202));
+    }
+
+    #[test]
+    fn computed_jumps_are_walked_from_their_base() {
+        let rom = [
+            0xB2, 0x04, // jump 0x204 + V0
+            0x00, 0x00, //
+            0x12, 0x08, // 0x204: jump 0x208
+            0x12, 0x08, // 0x206: jump 0x208
+            0x00, 0xFD, // 0x208: exit
+        ];
+
+        assert_eq!(
+            starts(&find_blocks(&rom)),
+            [(0x200, 0x202), (0x204, 0x206), (0x208, 0x20A)]
+        );
+    }
+
+    #[test]
+    fn long_loads_are_skipped_whole() {
+        let rom = [
+            0x30, 0x00, // skip if V0 == 0
+            0xF0, 0x00, 0x12, 0x34, // I = 0x1234
+            0x00, 0xFD, // exit
+        ];
+
+        let blocks = find_blocks(&rom);
+
+        assert_eq!(
+            starts(&blocks),
+            [(0x200, 0x202), (0x202, 0x206), (0x206, 0x208)]
+        );
+        assert_eq!(blocks[1].ops[0].opcode, (0xF000, 0x1234));
+    }
+}
@@ -0,0 +1,156 @@
+//! ##Chip-8 Recompiler
+//!
+//! Translates a ROM ahead of time into the source of a Rust module, which
+//! the machine runs with `VirtualMachine::run_compiled_frame`.
+//!
+//! The reachable code is found by walking the program from 0x200 and split
+//! into blocks of straight-line code, see `find_blocks`. Each block becomes
+//! Rust code driving the machine through the same component traits the
+//! interpreter uses.
+//!
+//! Not everything can be known ahead of time. `JP V0, addr` lands wherever
+//! V0 says, and programs may write over their own code. The machine only runs
+//! a compiled block when the program counter is at its start and the memory
+//! still holds the bytes it was translated from, and interprets everything
+//! else.
+//!
+//! `recompiler/golden/test_opcode.rs` is the translation of `test_opcode`,
+//! the tests check it against the interpreter. Run them with
+//! `UPDATE_GOLDEN=1` to rewrite it after changing the translation.
+//!
+
+extern crate data;
+extern crate instruction;
+extern crate model;
+
+mod emit;
+mod flow;
+
+pub use emit::recompile;
+pub use flow::{find_blocks, Block, Op};
+
+#[cfg(test)]
+#[rustfmt::skip]
+#[path = "../golden/test_opcode.rs"]
+mod test_opcode;
+
+#[cfg(test)]
+mod tests {
+    extern crate cpu;
+    extern crate rom_library;
+    extern crate runner;
+
+    use super::test_opcode::{Program, ROM};
+    use super::*;
+    use cpu::{Quirks, Timing, VIP_FRAME_CYCLES};
+    use data::{Address, Byte};
+    use model::{Chip8VirtualMachine, InputEvent, InputTime};
+    use runner::{new_vm, VM};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn get_vm(quirks: Quirks, timing: Timing) -> VM {
+        let mut vm = new_vm(quirks, 7);
+
+        vm.timing = timing;
+        vm.load_rom(&ROM).unwrap();
+
+        // Tap every key in turn.
+        for frame in 0..64 {
+            let key = (frame / 4 % 16) as u8;
+            let time = InputTime::Frame(frame);
+
+            match frame % 4 {
+                0 => vm.queue_input(InputEvent::press(time, key)),
+                2 => vm.queue_input(InputEvent::release(time, key)),
+                _ => {}
+            }
+        }
+
+        vm
+    }
+
+    /// Runs the compiled program and the interpreter side by side, comparing
+    /// them after every frame.
+    fn check_frames(mut compiled: VM, mut interpreted: VM, cycles: usize, frames: u64) {
+        let program = Program::new();
+
+        for frame in 0..frames {
+            let expected = interpreted.run_frame(cycles);
+            let actual = compiled.run_compiled_frame(&program, cycles);
+
+            assert_eq!(actual, expected, "frame {}", frame);
+            assert!(
+                compiled.snapshot() == interpreted.snapshot(),
+                "the machines disagree after frame {}",
+                frame
+            );
+        }
+    }
+
+    #[test]
+    fn the_golden_module_is_up_to_date() {
+        let rom = rom_library::load_rom("test_opcode".to_string()).unwrap();
+        let actual = recompile(&rom, "test_opcode");
+
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "golden", "test_opcode.rs"]
+            .iter()
+            .collect();
+
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("{}: {}, run with UPDATE_GOLDEN=1", path.display(), e));
+
+        assert!(
+            expected == actual,
+            "{} is out of date, run with UPDATE_GOLDEN=1",
+            path.display()
+        );
+    }
+
+    #[test]
+    fn compiled_code_matches_the_interpreter() {
+        let configs = [
+            (Quirks::cosmac_vip(), Timing::Instructions, 11, 60),
+            (Quirks::super_chip(), Timing::Instructions, 11, 60),
+            (Quirks::modern(), Timing::Instructions, 1000, 5),
+            (
+                Quirks::cosmac_vip(),
+                Timing::CosmacVip,
+                VIP_FRAME_CYCLES as usize,
+                20,
+            ),
+        ];
+
+        for (quirks, timing, cycles, frames) in configs.iter() {
+            check_frames(
+                get_vm(*quirks, *timing),
+                get_vm(*quirks, *timing),
+                *cycles,
+                *frames,
+            );
+        }
+    }
+
+    #[test]
+    fn modified_blocks_fall_back_to_the_interpreter() {
+        let block = &find_blocks(&ROM)[1];
+        let mut compiled = get_vm(Quirks::modern(), Timing::Instructions);
+        let mut interpreted = get_vm(Quirks::modern(), Timing::Instructions);
+
+        // Turn the first instruction of the block into V0 = 0x42.
+        for vm in [&mut compiled, &mut interpreted].iter_mut() {
+            let start = Address::new(block.start());
+
+            vm.write_memory(start, Byte::new(0x60)).unwrap();
+            vm.write_memory(start + 1u16, Byte::new(0x42)).unwrap();
+        }
+
+        check_frames(compiled, interpreted, 11, 60);
+    }
+}
@@ -0,0 +1,54 @@
+//! Translates a Chip-8 ROM into the source of a Rust module.
+//!
+//! Usage: `recompiler <file or rom name> [--output FILE]`. The source is
+//! printed unless an output file is given.
+//!
+
+extern crate recompiler;
+extern crate rom_library;
+
+use recompiler::recompile;
+use rom_library::{read_rom, ROM_NAMES};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (name, output) = match args.as_slice() {
+        [name] => (name, None),
+        [name, flag, file] if flag == "--output" || flag == "-o" => (name, Some(file)),
+        _ => {
+            eprintln!("usage: recompiler <file or rom name> [--output FILE]");
+            eprintln!("roms: {}", ROM_NAMES.join(", "));
+            process::exit(2);
+        }
+    };
+
+    let rom = match read_rom(name) {
+        Ok(rom) => rom,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    // Paths are named after the file, without the extension.
+    let stem = Path::new(name)
+        .file_stem()
+        .map_or(name.to_string(), |stem| stem.to_string_lossy().into_owned());
+
+    let source = recompile(&rom, &stem);
+
+    match output {
+        Some(file) => {
+            if let Err(e) = fs::write(file, source) {
+                eprintln!("{}: {}", file, e);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", source),
+    }
+}
@@ -1,6 +1,11 @@
 mod roms;
+use std::fs;
 use std::io;
-use roms::{ TETRIS, BREAKOUT, INVADERS };
+use std::path::Path;
+use roms::{ TETRIS, BREAKOUT, INVADERS, PONG, TEST_OPCODE, BC_TEST, KEYPAD_TEST };
+
+/// The names of the bundled roms.
+pub const ROM_NAMES: [&str; 7] = ["tetris", "breakout", "invaders", "pong", "test_opcode", "bc_test", "keypad_test"];
 
 /// Load the rom with the given name
 ///
@@ -15,10 +20,28 @@ pub fn load_rom(name: String) -> Result<Vec<u8>, io::Error> {
         "tetris" => Ok(Vec::from(TETRIS)),
         "breakout" => Ok(Vec::from(BREAKOUT)),
         "invaders" => Ok(Vec::from(INVADERS)),
+        "pong" => Ok(Vec::from(PONG)),
+        "test_opcode" => Ok(Vec::from(TEST_OPCODE)),
+        "bc_test" => Ok(Vec::from(BC_TEST)),
+        "keypad_test" => Ok(Vec::from(KEYPAD_TEST)),
         _ => Err(io::Error::new(io::ErrorKind::NotFound, format!("Could not find rom for {}", name)))
     }
 }
 
+/// Reads the rom from a file, or from the library when there is no such file.
+///
+///### Arguments
+///
+///- **name** : A path or the name of a library rom.
+///
+pub fn read_rom(name: &str) -> Result<Vec<u8>, io::Error> {
+    if Path::new(name).is_file() {
+        fs::read(name).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", name, e)))
+    } else {
+        load_rom(name.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -29,4 +52,20 @@ mod tests {
 
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn every_name_loads() {
+        for name in ROM_NAMES.iter() {
+            assert!(load_rom(name.to_string()).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn reads_files_before_library_roms() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/pong_1_player.ch8");
+
+        assert_eq!(read_rom(path).unwrap(), load_rom("pong".into()).unwrap());
+        assert_eq!(read_rom("tetris").unwrap(), load_rom("tetris".into()).unwrap());
+        assert!(read_rom("missing").is_err());
+    }
 }
@@ -5,4 +5,15 @@ pub const TETRIS: &[u8] = include_bytes!("../tetris.ch8");
 pub const BREAKOUT: &[u8] = include_bytes!("../breakout.ch8");
 
 /// Space Invaders Rom.
-pub const INVADERS: &[u8] = include_bytes!("../invaders.ch8");
\ No newline at end of file
+pub const INVADERS: &[u8] = include_bytes!("../invaders.ch8");
+/// Pong (1 player) Rom.
+pub const PONG: &[u8] = include_bytes!("../pong_1_player.ch8");
+
+/// Opcode test Rom by corax89.
+pub const TEST_OPCODE: &[u8] = include_bytes!("../test_opcode.ch8");
+
+/// BC Chip-8 test Rom.
+pub const BC_TEST: &[u8] = include_bytes!("../bc_test.ch8");
+
+/// Keypad test Rom.
+pub const KEYPAD_TEST: &[u8] = include_bytes!("../keypad_test.ch8");
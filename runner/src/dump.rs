@@ -0,0 +1,165 @@
+//! Dumps of the machine state after a run.
+//!
+
+use super::png::encode_png;
+use super::VM;
+use cpu::VMState;
+use model::{
+    Chip8FrameBuffer, Chip8ProgramCounter, Chip8RegisterBank, Chip8VirtualMachine, Register,
+};
+
+/// The characters of the XO-CHIP plane combinations, from the background to
+/// both planes.
+///
+const PLANE_CHARS: [char; 4] = ['.', '#', '+', '@'];
+
+/// The colors of the plane combinations, the same as the emulator's.
+///
+const PLANE_COLORS: [[u8; 3]; 4] = [[0, 15, 15], [128, 0, 128], [0, 160, 160], [224, 224, 224]];
+
+/// Returns the framebuffer as text, a line per row.
+///
+/// Off pixels are `.` and lit ones `#`. With XO-CHIP planes the second plane
+/// is `+` and both planes are `@`.
+///
+///### Arguments
+///
+///- **buffer** : The framebuffer.
+///
+pub fn framebuffer_ascii(buffer: &dyn Chip8FrameBuffer) -> String {
+    let mut out = String::with_capacity((buffer.width() + 1) * buffer.height());
+
+    for y in 0..buffer.height() {
+        for x in 0..buffer.width() {
+            out.push(PLANE_CHARS[buffer.get_color(x, y) as usize & 0b11]);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Returns the framebuffer as a PNG image.
+///
+///### Arguments
+///
+///- **buffer** : The framebuffer.
+///- **scale**  : The size of a pixel in the image, at least 1.
+///
+pub fn framebuffer_png(buffer: &dyn Chip8FrameBuffer, scale: usize) -> Vec<u8> {
+    let scale = scale.max(1);
+    let (width, height) = (buffer.width() * scale, buffer.height() * scale);
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            pixels.push(buffer.get_color(x / scale, y / scale) & 0b11);
+        }
+    }
+
+    encode_png(width, height, &pixels, &PLANE_COLORS)
+}
+
+/// Returns the registers, timers and counters of the machine as JSON.
+///
+/// `stack` holds the addresses the calling routines continue at, from the
+/// main routine up.
+///
+///### Arguments
+///
+///- **vm** : The machine.
+///
+pub fn registers_json(vm: &VM) -> String {
+    let v: Vec<String> = (0..16)
+        .map(|n| {
+            vm.registers
+                .get_v(Register::from_nibble(n))
+                .get_raw()
+                .to_string()
+        })
+        .collect();
+
+    // The top of the stack is the program counter itself.
+    let mut stack = vm.pc.stack();
+    stack.pop();
+    let stack: Vec<String> = stack
+        .iter()
+        .map(|addr| addr.get_raw().to_string())
+        .collect();
+
+    let state = match vm.state {
+        VMState::Initializing => "initializing",
+        VMState::LoadingROM => "loading_rom",
+        VMState::Executing(_) => "executing",
+        VMState::Paused => "paused",
+        VMState::WaitingForKey(..) => "waiting_for_key",
+        VMState::Exited => "exited",
+    };
+
+    format!(
+        "{{\n  \"pc\": {},\n  \"i\": {},\n  \"v\": [{}],\n  \"stack\": [{}],\n  \
+         \"delay_timer\": {},\n  \"sound_timer\": {},\n  \"cycles\": {},\n  \
+         \"frames\": {},\n  \"state\": \"{}\"\n}}\n",
+        vm.pc.current().get_raw(),
+        vm.registers.get_i().get_raw(),
+        v.join(", "),
+        stack.join(", "),
+        vm.delay_timer,
+        vm.sound_timer,
+        vm.cycles(),
+        vm.frames(),
+        state
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::new_vm;
+    use super::*;
+    use cpu::Quirks;
+
+    /// 200: LD I, 0 (the 0 glyph), 202: DRW V0, V0, 5, 204: JP 204
+    const DRAW_ZERO: [u8; 6] = [0xA0, 0x00, 0xD0, 0x05, 0x12, 0x04];
+
+    fn drawn() -> VM {
+        let mut vm = new_vm(Quirks::default(), 0);
+        vm.load_rom(&DRAW_ZERO).unwrap();
+        vm.execute_cycles(3).unwrap();
+        vm
+    }
+
+    #[test]
+    fn ascii_draws_a_line_per_row() {
+        let vm = drawn();
+        let ascii = framebuffer_ascii(&vm.framebuffer);
+        let lines: Vec<&str> = ascii.lines().collect();
+
+        assert_eq!(lines.len(), 32);
+        assert_eq!(lines[0].len(), 64);
+        assert_eq!(&lines[0][..6], "####..");
+        assert_eq!(&lines[1][..6], "#..#..");
+        assert_eq!(lines[5], ".".repeat(64));
+    }
+
+    #[test]
+    fn png_is_scaled() {
+        let vm = drawn();
+        let png = framebuffer_png(&vm.framebuffer, 2);
+
+        // The width and height in the header.
+        assert_eq!(&png[16..24], &[0, 0, 0, 128, 0, 0, 0, 64]);
+    }
+
+    #[test]
+    fn json_holds_the_registers() {
+        let vm = drawn();
+
+        assert_eq!(
+            registers_json(&vm),
+            "{\n  \"pc\": 516,\n  \"i\": 0,\n  \
+             \"v\": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],\n  \
+             \"stack\": [],\n  \"delay_timer\": 0,\n  \"sound_timer\": 0,\n  \
+             \"cycles\": 3,\n  \"frames\": 0,\n  \"state\": \"executing\"\n}\n"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Compares framebuffer dumps against golden images.
+//!
+//! Golden images are the ASCII dumps of `framebuffer_ascii`, checked in
+//! under `runner/golden`. The tests below boot the test roms of the library
+//! and fail with a visual diff when the screen no longer matches. Run them
+//! with `UPDATE_GOLDEN=1` to rewrite the images after an intended change.
+//!
+
+/// Returns a picture of the differences between two ASCII images, or `None`
+/// when they are the same.
+///
+/// Pixels lit in both are `#`, pixels only in the expected image are `-` and
+/// pixels only in the actual image are `+`.
+///
+///### Arguments
+///
+///- **expected** : The golden image.
+///- **actual**   : The image of the run.
+///
+pub fn diff_images(expected: &str, actual: &str) -> Option<String> {
+    if expected.trim_end() == actual.trim_end() {
+        return None;
+    }
+
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    let mut picture = String::new();
+    let mut differences = 0;
+
+    for y in 0..expected.len().max(actual.len()) {
+        let e: Vec<char> = expected.get(y).unwrap_or(&"").chars().collect();
+        let a: Vec<char> = actual.get(y).unwrap_or(&"").chars().collect();
+
+        for x in 0..e.len().max(a.len()) {
+            let was = e.get(x).is_some_and(|c| *c != '.');
+            let is = a.get(x).is_some_and(|c| *c != '.');
+
+            picture.push(match (was, is) {
+                (true, true) => '#',
+                (true, false) => '-',
+                (false, true) => '+',
+                (false, false) => '.',
+            });
+
+            if was != is || e.get(x) != a.get(x) {
+                differences += 1;
+            }
+        }
+        picture.push('\n');
+    }
+
+    Some(format!(
+        "{} pixels differ (- expected only, + actual only)\n{}",
+        differences, picture
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{framebuffer_ascii, new_vm, parse_script, run, Limit};
+    use super::*;
+    use cpu::Quirks;
+    use model::Chip8VirtualMachine;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// The speed the golden images are taken at.
+    const INSTRUCTIONS_PER_SECOND: u64 = 480;
+
+    /// Runs a library rom and compares the screen to its golden image.
+    ///
+    ///### Arguments
+    ///
+    ///- **rom**    : The name of the rom in the library.
+    ///- **quirks** : The quirks the rom expects.
+    ///- **script** : The input script.
+    ///- **frames** : The number of frames to run.
+    ///
+    fn check_golden(rom: &str, quirks: Quirks, script: &str, frames: u64) {
+        let mut vm = new_vm(quirks, 0);
+        vm.load_rom(&rom_library::load_rom(rom.to_string()).unwrap())
+            .unwrap();
+
+        for event in parse_script(script).unwrap() {
+            vm.queue_input(event);
+        }
+
+        run(&mut vm, Limit::Frames(frames), INSTRUCTIONS_PER_SECOND).unwrap();
+        let actual = framebuffer_ascii(&vm.framebuffer);
+
+        let path: PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "golden",
+            &format!("{}.txt", rom),
+        ]
+        .iter()
+        .collect();
+
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("{}: {}, run with UPDATE_GOLDEN=1", path.display(), e));
+
+        if let Some(diff) = diff_images(&expected, &actual) {
+            panic!("{} does not match {}\n{}", rom, path.display(), diff);
+        }
+    }
+
+    #[test]
+    fn diff_marks_missing_and_extra_pixels() {
+        assert_eq!(diff_images("#.\n.#\n", "#.\n.#"), None);
+
+        assert_eq!(
+            diff_images("##\n..\n", "#.\n.#\n"),
+            Some("2 pixels differ (- expected only, + actual only)\n#-\n.+\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_opcode_matches_its_golden_image() {
+        check_golden("test_opcode", Quirks::cosmac_vip(), "", 120);
+    }
+
+    #[test]
+    fn bc_test_matches_its_golden_image() {
+        // Shifts Vx in place and leaves I alone on load and store.
+        check_golden("bc_test", Quirks::super_chip(), "", 120);
+    }
+
+    #[test]
+    fn keypad_test_matches_its_golden_image() {
+        // The key stays highlighted for 16 frames after it is released.
+        check_golden(
+            "keypad_test",
+            Quirks::super_chip(),
+            "frame 60 press 5\nframe 62 release 5",
+            70,
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! ##Headless Runner
+//!
+//! Runs ROMs without a window, for tests and CI. A run loads a rom, plays an
+//! optional input script for a number of frames or cycles and then dumps the
+//! framebuffer as ASCII or PNG and the registers as JSON.
+//!
+
+extern crate cpu;
+extern crate framebuffer;
+extern crate keyboard;
+extern crate memory;
+extern crate model;
+extern crate program_counter;
+extern crate random;
+extern crate register_bank;
+extern crate rom_library;
+
+mod dump;
+mod golden;
+mod png;
+mod script;
+
+pub use dump::{framebuffer_ascii, framebuffer_png, registers_json};
+pub use golden::diff_images;
+pub use png::encode_png;
+pub use script::{parse_script, ScriptError};
+
+use cpu::{cycles_for_frame, Quirks, VMState, VirtualMachine};
+use framebuffer::FrameBuffer;
+use keyboard::Keyboard;
+use memory::Memory;
+use model::{Chip8Error, Chip8VirtualMachine};
+use program_counter::ProgramCounter;
+use random::SeededRandom;
+use register_bank::RegisterBank;
+
+/// The machine the runner uses, without sound.
+///
+pub type VM =
+    VirtualMachine<Memory, ProgramCounter, RegisterBank, FrameBuffer, Keyboard, SeededRandom, ()>;
+
+/// How long a run lasts.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    /// Runs this many 60 Hz frames.
+    Frames(u64),
+
+    /// Runs this many cycles, ticking the timers at every full frame.
+    Cycles(u64),
+}
+
+/// Creates a VirtualMachine with XO-CHIP sized memory.
+///
+///### Arguments
+///
+///- **quirks** : The behaviour of the contested instructions.
+///- **seed**   : The seed of the random source, so runs can be repeated.
+///
+pub fn new_vm(quirks: Quirks, seed: u64) -> VM {
+    VM::new(
+        Memory::with_size(memory::XO_CHIP_SIZE),
+        ProgramCounter::new(0x200u16.into()),
+        RegisterBank::new(),
+        FrameBuffer::new(32),
+        Keyboard::new(),
+        SeededRandom::new(seed),
+        (),
+        quirks,
+    )
+}
+
+/// Runs the machine until the limit is reached or the program exits.
+///
+///### Arguments
+///
+///- **vm**                      : The machine, with the rom and input loaded.
+///- **limit**                   : When to stop.
+///- **instructions_per_second** : The speed of the CPU.
+///
+pub fn run(vm: &mut VM, limit: Limit, instructions_per_second: u64) -> Result<(), Chip8Error> {
+    while vm.state != VMState::Exited && vm.state != VMState::Paused {
+        let frame = vm.frames();
+        let cycles = cycles_for_frame(instructions_per_second, frame);
+
+        match limit {
+            Limit::Frames(frames) if frame >= frames => break,
+            Limit::Cycles(total) => {
+                let left = total.saturating_sub(vm.cycles());
+
+                if left == 0 {
+                    break;
+                }
+
+                // The last frame is cut short, its timers do not tick.
+                if left < cycles as u64 {
+                    return vm.execute_cycles(left as usize);
+                }
+            }
+            _ => {}
+        }
+
+        vm.run_frame(cycles)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{InputEvent, InputTime};
+
+    /// 200: LD V0, K, 202: ADD V1, 1, 204: JP 202
+    const COUNTER: [u8; 6] = [0xF0, 0x0A, 0x71, 0x01, 0x12, 0x02];
+
+    #[test]
+    fn runs_for_a_number_of_frames() {
+        let mut vm = new_vm(Quirks::default(), 0);
+        vm.load_rom(&[0x12, 0x00]).unwrap();
+
+        run(&mut vm, Limit::Frames(10), 600).unwrap();
+
+        assert_eq!(vm.frames(), 10);
+        assert_eq!(vm.cycles(), 100);
+    }
+
+    #[test]
+    fn runs_for_a_number_of_cycles() {
+        let mut vm = new_vm(Quirks::default(), 0);
+        vm.load_rom(&[0x12, 0x00]).unwrap();
+
+        run(&mut vm, Limit::Cycles(25), 600).unwrap();
+
+        assert_eq!(vm.cycles(), 25);
+        assert_eq!(vm.frames(), 2);
+    }
+
+    #[test]
+    fn scripted_input_reaches_the_program() {
+        let mut vm = new_vm(Quirks::default(), 0);
+        vm.load_rom(&COUNTER).unwrap();
+
+        for event in parse_script("frame 2 press 7\nframe 3 release 7").unwrap() {
+            vm.queue_input(event);
+        }
+        vm.queue_input(InputEvent::press(InputTime::Frame(100), 0));
+
+        run(&mut vm, Limit::Frames(5), 600).unwrap();
+
+        let json = registers_json(&vm);
+        assert!(json.contains("\"v\": [7, "), "{}", json);
+    }
+
+    #[test]
+    fn stops_when_the_program_exits() {
+        let mut vm = new_vm(Quirks::default(), 0);
+        vm.load_rom(&[0x00, 0xFD]).unwrap();
+
+        run(&mut vm, Limit::Frames(1000), 600).unwrap();
+
+        assert_eq!(vm.state, VMState::Exited);
+        assert_eq!(vm.frames(), 1);
+    }
+}
@@ -0,0 +1,212 @@
+//! Runs a Chip-8 ROM without a window.
+//!
+//! ```text
+//! runner <file or rom name> [options]
+//!
+//!   --frames N          run N frames (the default is 60)
+//!   --cycles N          run N cycles instead
+//!   --ips N             instructions per second (the default is 480)
+//!   --quirks NAME       vip, chip48, schip or modern (the default)
+//!   --seed N            seed of the random source (the default is 0)
+//!   --engine NAME       interpreter (the default) or threaded
+//!   --input FILE        scripted input, see `runner::parse_script`
+//!   --ascii FILE        write the framebuffer as text, - for stdout
+//!   --png FILE          write the framebuffer as a PNG image
+//!   --scale N           size of a pixel in the PNG (the default is 1)
+//!   --registers FILE    write the registers as JSON, - for stdout
+//! ```
+//!
+//! Exits with 0 after a clean run, 1 when the machine raised an error (the
+//! dumps are still written), 2 for bad arguments and 3 when a file can not be
+//! read or written.
+//!
+
+extern crate cpu;
+extern crate model;
+extern crate rom_library;
+extern crate runner;
+
+use cpu::{Engine, Quirks};
+use model::Chip8VirtualMachine;
+use rom_library::{read_rom, ROM_NAMES};
+use runner::{
+    framebuffer_ascii, framebuffer_png, new_vm, parse_script, registers_json, run, Limit,
+};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+const EXIT_MACHINE_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_IO: i32 = 3;
+
+/// The parsed command line.
+///
+struct Options {
+    rom: String,
+    limit: Limit,
+    instructions_per_second: u64,
+    quirks: Quirks,
+    seed: u64,
+    engine: Engine,
+    input: Option<String>,
+    ascii: Option<String>,
+    png: Option<String>,
+    scale: usize,
+    registers: Option<String>,
+}
+
+/// Prints the usage and exits.
+///
+fn usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!(
+        "usage: runner <file or rom name> [--frames N | --cycles N] [--ips N] \
+         [--quirks vip|chip48|schip|modern] [--seed N] [--engine interpreter|threaded] \
+         [--input FILE] [--ascii FILE] [--png FILE] [--scale N] [--registers FILE]"
+    );
+    eprintln!("roms: {}", ROM_NAMES.join(", "));
+    process::exit(EXIT_USAGE);
+}
+
+/// Prints the error and exits.
+///
+fn fail(code: i32, message: String) -> ! {
+    eprintln!("{}", message);
+    process::exit(code);
+}
+
+fn parse_args(args: &[String]) -> Options {
+    let mut options = Options {
+        rom: String::new(),
+        limit: Limit::Frames(60),
+        instructions_per_second: 480,
+        quirks: Quirks::default(),
+        seed: 0,
+        engine: Engine::default(),
+        input: None,
+        ascii: None,
+        png: None,
+        scale: 1,
+        registers: None,
+    };
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if !options.rom.is_empty() {
+                usage(&format!("unexpected argument '{}'", arg));
+            }
+            options.rom = arg.clone();
+            continue;
+        }
+
+        let value = match args.next() {
+            Some(value) => value.clone(),
+            None => usage(&format!("{} needs a value", arg)),
+        };
+
+        let number = || -> u64 {
+            value
+                .parse()
+                .unwrap_or_else(|_| usage(&format!("{} needs a number, found '{}'", arg, value)))
+        };
+
+        match arg.as_str() {
+            "--frames" => options.limit = Limit::Frames(number()),
+            "--cycles" => options.limit = Limit::Cycles(number()),
+            "--ips" => options.instructions_per_second = number(),
+            "--seed" => options.seed = number(),
+            "--scale" => options.scale = number() as usize,
+            "--quirks" => {
+                options.quirks = match value.as_str() {
+                    "vip" => Quirks::cosmac_vip(),
+                    "chip48" => Quirks::chip48(),
+                    "schip" => Quirks::super_chip(),
+                    "modern" => Quirks::modern(),
+                    _ => usage(&format!("unknown quirks '{}'", value)),
+                }
+            }
+            "--engine" => {
+                options.engine = match value.as_str() {
+                    "interpreter" => Engine::Interpreter,
+                    "threaded" => Engine::Threaded,
+                    _ => usage(&format!("unknown engine '{}'", value)),
+                }
+            }
+            "--input" => options.input = Some(value),
+            "--ascii" => options.ascii = Some(value),
+            "--png" => options.png = Some(value),
+            "--registers" => options.registers = Some(value),
+            _ => usage(&format!("unknown option '{}'", arg)),
+        }
+    }
+
+    if options.rom.is_empty() {
+        usage("no rom given");
+    }
+
+    options
+}
+
+/// Writes a dump to the file, or to stdout for `-`.
+///
+fn write_output(path: &str, bytes: &[u8]) -> Result<(), String> {
+    let result = if path == "-" {
+        io::stdout().write_all(bytes)
+    } else {
+        fs::write(path, bytes)
+    };
+
+    result.map_err(|e| format!("{}: {}", path, e))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse_args(&args);
+
+    let rom = read_rom(&options.rom).unwrap_or_else(|e| fail(EXIT_IO, e.to_string()));
+
+    let mut vm = new_vm(options.quirks, options.seed);
+    vm.engine = options.engine;
+    if let Err(e) = vm.load_rom(&rom) {
+        fail(EXIT_MACHINE_ERROR, format!("{}: {}", options.rom, e));
+    }
+
+    if let Some(path) = &options.input {
+        let script =
+            fs::read_to_string(path).unwrap_or_else(|e| fail(EXIT_IO, format!("{}: {}", path, e)));
+        let events =
+            parse_script(&script).unwrap_or_else(|e| fail(EXIT_USAGE, format!("{}: {}", path, e)));
+
+        for event in events {
+            vm.queue_input(event);
+        }
+    }
+
+    let result = run(&mut vm, options.limit, options.instructions_per_second);
+
+    let dumps = [
+        (
+            &options.ascii,
+            framebuffer_ascii(&vm.framebuffer).into_bytes(),
+        ),
+        (
+            &options.png,
+            framebuffer_png(&vm.framebuffer, options.scale),
+        ),
+        (&options.registers, registers_json(&vm).into_bytes()),
+    ];
+
+    for (path, bytes) in dumps.iter() {
+        if let Some(path) = path {
+            write_output(path, bytes).unwrap_or_else(|e| fail(EXIT_IO, e));
+        }
+    }
+
+    if let Err(e) = result {
+        fail(EXIT_MACHINE_ERROR, e.to_string());
+    }
+}
@@ -0,0 +1,119 @@
+//! Scripted input for a run.
+//!
+//! Every line of a script presses or releases a key at a frame or a cycle:
+//!
+//! ```text
+//! # wait for the title screen, then tap 5
+//! frame 30 press 5
+//! frame 32 release 5
+//! cycle 5000 press A
+//! ```
+//!
+//! Keys are the hex digits of the keypad. Everything after `#` is a comment.
+//!
+
+use model::{InputEvent, InputTime};
+use std::error::Error;
+use std::fmt;
+
+/// A line of a script that could not be parsed.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptError {
+    /// The line, counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ScriptError {}
+
+/// Parses an input script.
+///
+///### Arguments
+///
+///- **text** : The script.
+///
+///### Returns
+///
+/// The events in the order they appear.
+///
+pub fn parse_script(text: &str) -> Result<Vec<InputEvent>, ScriptError> {
+    let mut events = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        if words.is_empty() {
+            continue;
+        }
+
+        let error = |message: String| ScriptError {
+            line: n + 1,
+            message,
+        };
+
+        if words.len() != 4 {
+            return Err(error(format!(
+                "expected '<frame|cycle> <n> <press|release> <key>', found '{}'",
+                line.trim()
+            )));
+        }
+
+        let count: u64 = words[1]
+            .parse()
+            .map_err(|_| error(format!("'{}' is not a number", words[1])))?;
+
+        let time = match words[0] {
+            "frame" => InputTime::Frame(count),
+            "cycle" => InputTime::Cycle(count),
+            other => return Err(error(format!("unknown time '{}'", other))),
+        };
+
+        let key = u8::from_str_radix(words[3], 16)
+            .ok()
+            .filter(|key| *key < 16)
+            .ok_or_else(|| error(format!("'{}' is not a key", words[3])))?;
+
+        events.push(match words[2] {
+            "press" => InputEvent::press(time, key),
+            "release" => InputEvent::release(time, key),
+            other => return Err(error(format!("unknown action '{}'", other))),
+        });
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_presses_and_releases() {
+        let events = parse_script("# title\nframe 30 press 5\n\ncycle 7 release a # done\n");
+
+        assert_eq!(
+            events,
+            Ok(vec![
+                InputEvent::press(InputTime::Frame(30), 0x5),
+                InputEvent::release(InputTime::Cycle(7), 0xA),
+            ])
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = parse_script("frame 1 press 1\nframe 2 hold 1").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unknown action 'hold'");
+
+        let error = parse_script("frame 1 press 10").unwrap_err();
+        assert_eq!(error.message, "'10' is not a key");
+    }
+}
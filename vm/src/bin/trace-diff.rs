@@ -0,0 +1,46 @@
+//! Finds the first line two execution traces differ in.
+//!
+//! Usage: `trace-diff <left> <right>`. Exits with 0 when the traces are the
+//! same, 1 when they differ and 2 when they can not be read.
+//!
+
+extern crate cpu;
+
+use cpu::diff_traces;
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process;
+
+/// Opens a trace or exits.
+///
+fn open(path: &str) -> BufReader<File> {
+    match File::open(path) {
+        Ok(file) => BufReader::new(file),
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.len() != 2 {
+        eprintln!("usage: trace-diff <left> <right>");
+        process::exit(2);
+    }
+
+    match diff_traces(open(&args[0]), open(&args[1])) {
+        Ok(None) => println!("traces are identical"),
+        Ok(Some(divergence)) => {
+            println!("{}", divergence);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Paces a machine in real or virtual time.
+//!
+//! The delay and sound timers count down at 60 Hz whatever the instruction
+//! rate is, so the machine is driven one frame at a time: the instructions of
+//! the frame are executed and then the timers are decremented once.
+//!
+
+use model::{Chip8Error, Chip8VirtualMachine};
+use std::time::{Duration, Instant};
+
+/// The rate of the delay and sound timers.
+///
+pub const FRAMES_PER_SECOND: u64 = 60;
+
+/// The most frames run by a single call to `Clock::run`. When the host falls
+/// further behind than this the missing frames are dropped instead of being
+/// caught up all at once.
+///
+pub const MAX_CATCH_UP: u64 = 6;
+
+/// Returns the number of instructions executed in a frame.
+///
+/// When the rate is not a multiple of 60 the remainder is spread over the
+/// frames, so every second executes exactly the given number.
+///
+///### Arguments
+///
+///- **instructions_per_second** : The speed of the CPU.
+///- **frame**                   : The number of the frame, counting from 0.
+///
+pub fn cycles_for_frame(instructions_per_second: u64, frame: u64) -> usize {
+    let ips = instructions_per_second;
+    let done = frame * ips / FRAMES_PER_SECOND;
+    let next = (frame + 1) * ips / FRAMES_PER_SECOND;
+
+    (next - done) as usize
+}
+
+/// A source of the time elapsed since some fixed point.
+///
+pub trait TimeSource {
+    /// Returns the time elapsed since the source was created.
+    ///
+    fn now(&self) -> Duration;
+}
+
+/// Wall clock time.
+///
+pub struct RealTime {
+    start: Instant,
+}
+
+impl RealTime {
+    /// Creates a new RealTime starting now.
+    ///
+    pub fn new() -> Self {
+        RealTime {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for RealTime {
+    fn default() -> Self {
+        RealTime::new()
+    }
+}
+
+impl TimeSource for RealTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Time that only moves when told to, for headless runs and tests.
+///
+#[derive(Default)]
+pub struct VirtualTime {
+    now: Duration,
+}
+
+impl VirtualTime {
+    /// Creates a new VirtualTime at zero.
+    ///
+    pub fn new() -> Self {
+        VirtualTime::default()
+    }
+
+    /// Moves the time forward.
+    ///
+    ///### Arguments
+    ///
+    ///- **duration** : The time to add.
+    ///
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+}
+
+impl TimeSource for VirtualTime {
+    fn now(&self) -> Duration {
+        self.now
+    }
+}
+
+/// Runs frames of a machine as time passes.
+///
+pub struct Clock<T: TimeSource> {
+    source: T,
+    instructions_per_second: u64,
+    frames: u64,
+}
+
+impl<T: TimeSource> Clock<T> {
+    /// Creates a new Clock.
+    ///
+    ///### Arguments
+    ///
+    ///- **source**                  : Where the time comes from.
+    ///- **instructions_per_second** : The speed of the CPU.
+    ///
+    pub fn new(source: T, instructions_per_second: u64) -> Self {
+        Clock {
+            source,
+            instructions_per_second,
+            frames: 0,
+        }
+    }
+
+    /// Returns the time source.
+    ///
+    pub fn source(&self) -> &T {
+        &self.source
+    }
+
+    /// Returns the time source, to advance virtual time.
+    ///
+    pub fn source_mut(&mut self) -> &mut T {
+        &mut self.source
+    }
+
+    /// Returns the number of frames run so far.
+    ///
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the number of frames the machine is behind the time source.
+    ///
+    pub fn frames_due(&self) -> u64 {
+        let elapsed = self.source.now().as_nanos();
+        let total = elapsed * FRAMES_PER_SECOND as u128 / 1_000_000_000;
+
+        (total as u64).saturating_sub(self.frames)
+    }
+
+    /// Returns the number of instructions to execute in the next frame.
+    ///
+    pub fn cycles_for_next_frame(&self) -> usize {
+        cycles_for_frame(self.instructions_per_second, self.frames)
+    }
+
+    /// Runs a single frame regardless of the time source.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine to run.
+    ///
+    pub fn step(&mut self, vm: &mut dyn Chip8VirtualMachine) -> Result<(), Chip8Error> {
+        let cycles = self.cycles_for_next_frame();
+        self.frames += 1;
+
+        vm.run_frame(cycles)
+    }
+
+    /// Drops the frames that are due without running them.
+    ///
+    /// Used after the machine was paused, so the time it spent paused is not
+    /// caught up on.
+    ///
+    pub fn skip_due(&mut self) {
+        self.frames += self.frames_due();
+    }
+
+    /// Runs the frames that are due.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine to run.
+    ///
+    ///### Returns
+    ///
+    /// The number of frames run.
+    ///
+    pub fn run(&mut self, vm: &mut dyn Chip8VirtualMachine) -> Result<u64, Chip8Error> {
+        let due = self.frames_due();
+
+        if due > MAX_CATCH_UP {
+            self.frames += due - MAX_CATCH_UP;
+        }
+
+        let due = due.min(MAX_CATCH_UP);
+        for _ in 0..due {
+            self.step(vm)?;
+        }
+
+        Ok(due)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::VmBuilder;
+
+    fn frame() -> Duration {
+        Duration::from_nanos(1_000_000_000 / FRAMES_PER_SECOND) + Duration::from_nanos(1)
+    }
+
+    #[test]
+    fn frames_are_due_at_60_hz() {
+        let mut clock = Clock::new(VirtualTime::new(), 480);
+
+        assert_eq!(clock.frames_due(), 0);
+
+        clock.source_mut().advance(frame() * 3);
+        assert_eq!(clock.frames_due(), 3);
+
+        clock.source_mut().advance(Duration::from_secs(1));
+        assert_eq!(clock.frames_due(), 63);
+    }
+
+    #[test]
+    fn skipped_frames_are_not_run() {
+        let mut vm = VmBuilder::new().program(&[0x12, 0x00]).build();
+        let mut clock = Clock::new(VirtualTime::new(), 480);
+
+        clock.source_mut().advance(frame() * 3);
+        clock.skip_due();
+        assert_eq!(clock.frames_due(), 0);
+
+        clock.source_mut().advance(frame());
+        assert_eq!(clock.run(&mut vm).unwrap(), 1);
+        assert_eq!(vm.frames(), 1);
+    }
+
+    #[test]
+    fn cycles_are_spread_over_the_second() {
+        let mut clock = Clock::new(VirtualTime::new(), 500);
+        let mut total = 0;
+
+        for _ in 0..FRAMES_PER_SECOND {
+            let cycles = clock.cycles_for_next_frame();
+            assert!(cycles == 8 || cycles == 9);
+
+            total += cycles;
+            clock.frames += 1;
+        }
+
+        assert_eq!(total, 500);
+    }
+}
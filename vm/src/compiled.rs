@@ -0,0 +1,225 @@
+//! Runs code translated ahead of time from a ROM.
+//!
+//! The recompiler turns the blocks of a ROM into Rust functions that
+//! implement `CompiledProgram`. The machine runs a block whenever the program
+//! counter reaches the start of one and the memory still holds the bytes it
+//! was translated from. Everything else, computed jumps into the middle of a
+//! block, code written at run time and self-modified blocks, is interpreted.
+//!
+//! Compiled instructions go through `execute_decoded` like the threaded
+//! engine's, so the results are the same as the interpreter's.
+//!
+
+use super::{Handler, VirtualMachine};
+use data::Address;
+use instruction::Instruction;
+use model::{
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank, Chip8VirtualMachine,
+};
+
+/// Code translated ahead of time from a ROM.
+///
+pub trait CompiledProgram<VM> {
+    /// Returns the bytes the block starting at the address was translated
+    /// from, or None if no block starts there.
+    ///
+    ///### Arguments
+    ///
+    ///- **address** : The address of the first instruction.
+    ///
+    fn source(&self, address: Address) -> Option<&[u8]>;
+
+    /// Runs the block starting at the address. Returns the cycles taken.
+    ///
+    /// Stops once the budget is spent, at least one instruction always runs.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm**      : The machine, with the program counter at the address.
+    ///- **address** : The address of the first instruction.
+    ///- **budget**  : The cycles left.
+    ///
+    fn run(&self, vm: &mut VM, address: Address, budget: usize) -> Result<u32, Chip8Error>;
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> VirtualMachine<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Executes an instruction that was decoded ahead of time. Returns the
+    /// machine cycles taken with VIP timing, 1 otherwise.
+    ///
+    /// Applies the input that is due, traces the instruction, performs it with
+    /// the handler, moves the program counter past it and counts the cycle.
+    ///
+    ///### Arguments
+    ///
+    ///- **opcode**      : The raw words of the instruction.
+    ///- **instruction** : The decoded instruction.
+    ///- **handler**     : `interpret_instruction`, or a translation of it.
+    ///
+    pub fn execute_decoded(
+        &mut self,
+        opcode: (u16, u16),
+        instruction: Instruction,
+        handler: Handler<Self>,
+    ) -> Result<u32, Chip8Error> {
+        self.apply_input();
+        self.retire(opcode, instruction, handler)
+    }
+
+    /// Executes the cycles, running the compiled blocks where it can.
+    ///
+    ///### Arguments
+    ///
+    ///- **program** : The code compiled from the loaded ROM.
+    ///- **cycles**  : The number of cycles, as for `execute_cycles`.
+    ///
+    pub fn execute_compiled<P>(&mut self, program: &P, cycles: usize) -> Result<(), Chip8Error>
+    where
+        P: CompiledProgram<Self>,
+    {
+        let mut spent = 0;
+
+        while spent < cycles {
+            let pc = self.pc.current();
+
+            let compiled = !self.is_paused()
+                && !self.is_exited()
+                && !self.is_waiting()
+                && program
+                    .source(pc)
+                    .is_some_and(|source| self.holds(pc, source));
+
+            let taken = if compiled {
+                Some(program.run(self, pc, cycles - spent)?)
+            } else {
+                self.step()?
+            };
+
+            match taken {
+                Some(cost) => spent += cost as usize,
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs a 60 Hz frame with the compiled blocks, then ticks the timers.
+    ///
+    ///### Arguments
+    ///
+    ///- **program** : The code compiled from the loaded ROM.
+    ///- **cycles**  : The number of cycles executed during the frame.
+    ///
+    pub fn run_compiled_frame<P>(&mut self, program: &P, cycles: usize) -> Result<(), Chip8Error>
+    where
+        P: CompiledProgram<Self>,
+    {
+        self.execute_compiled(program, self.frame_budget(cycles))?;
+        self.tick_timers();
+
+        Ok(())
+    }
+
+    /// Returns a flag indicating if the memory at the address holds the bytes.
+    ///
+    fn holds(&self, address: Address, bytes: &[u8]) -> bool {
+        match self.memory.get_slice(address, bytes.len()) {
+            Ok(memory) => memory
+                .iter()
+                .zip(bytes.iter())
+                .all(|(byte, expected)| byte.get_raw() == *expected),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{TestVm, VmBuilder};
+    use super::super::Quirks;
+    use super::*;
+    use data::Byte;
+    use model::Register;
+
+    type VM = TestVm;
+
+    /// V0 = 5, V0 += 1, jump 0x202
+    const PROGRAM: [u8; 6] = [0x60, 0x05, 0x70, 0x01, 0x12, 0x02];
+
+    /// The program with the block at 0x202 translated by hand, the way the
+    /// recompiler would. The add is made to count twice so runs of the
+    /// compiled block can be told apart.
+    struct Counting;
+
+    impl CompiledProgram<VM> for Counting {
+        fn source(&self, address: Address) -> Option<&[u8]> {
+            match address.get_raw() {
+                0x202 => Some(&PROGRAM[2..]),
+                _ => None,
+            }
+        }
+
+        fn run(&self, vm: &mut VM, _address: Address, budget: usize) -> Result<u32, Chip8Error> {
+            let mut spent =
+                vm.execute_decoded((0x7001, 0), Instruction::new(0x7001), |vm, _| {
+                    let x = vm.registers.get_v(Register::V0);
+                    vm.registers
+                        .set_v(Register::V0, x.wrapping_add(Byte::new(2)));
+
+                    Ok(false)
+                })?;
+
+            if spent as usize >= budget {
+                return Ok(spent);
+            }
+
+            spent += vm.execute_decoded(
+                (0x1202, 0),
+                Instruction::new(0x1202),
+                VM::interpret_instruction,
+            )?;
+
+            Ok(spent)
+        }
+    }
+
+    fn get_vm() -> VM {
+        VmBuilder::new()
+            .quirks(Quirks::modern())
+            .program(&PROGRAM)
+            .build()
+    }
+
+    #[test]
+    fn blocks_run_where_they_start_and_the_rest_is_interpreted() {
+        let mut vm = get_vm();
+
+        // 0x200 is interpreted, then the block runs twice.
+        vm.execute_compiled(&Counting, 5).unwrap();
+
+        assert_eq!(vm.cycles(), 5);
+        assert_eq!(vm.registers.get_v(Register::V0), Byte::new(5 + 2 + 2));
+        assert_eq!(vm.pc.current(), Address::new(0x202));
+    }
+
+    #[test]
+    fn modified_blocks_are_interpreted() {
+        let mut vm = get_vm();
+        vm.write_memory(Address::new(0x203), Byte::new(0x03))
+            .unwrap();
+
+        vm.execute_compiled(&Counting, 5).unwrap();
+
+        assert_eq!(vm.registers.get_v(Register::V0), Byte::new(5 + 3 + 3));
+    }
+}
@@ -0,0 +1,759 @@
+//! Breakpoints, watchpoints and stepping on top of a `VirtualMachine`.
+//!
+//! The debugger drives the machine one cycle at a time and checks the next
+//! instruction before it is executed. Breakpoints stop before the instruction
+//! at the address runs, watchpoints stop right after the instruction that
+//! touched the watched memory or register, so the host sees the new value.
+//!
+//! Only instructions are cycled through, the timers are left to the host to
+//! tick once per frame as usual.
+//!
+
+use super::VirtualMachine;
+use data::Address;
+use instruction::{Instruction, InstructionKind};
+use model::{
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank, Chip8VirtualMachine, Register,
+};
+
+/// Something a watchpoint can be set on.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Watch {
+    Memory(Address),
+    Register(Register),
+}
+
+/// The accesses a watchpoint stops on.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    /// Returns a flag indicating if the access is one to stop on.
+    ///
+    ///### Arguments
+    ///
+    ///- **write** : Whether the access is a write.
+    ///
+    fn matches(self, write: bool) -> bool {
+        match self {
+            Access::Read => !write,
+            Access::Write => write,
+            Access::ReadWrite => true,
+        }
+    }
+}
+
+/// Why the debugger handed control back to the host.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+    /// The program counter reached a breakpoint. The instruction has not
+    /// been executed yet.
+    Breakpoint(Address),
+
+    /// The next instruction is of a kind with a breakpoint on it. The
+    /// instruction has not been executed yet.
+    InstructionBreakpoint(Address, InstructionKind),
+
+    /// The instruction at pc read or wrote the watched target.
+    Watchpoint {
+        pc: Address,
+        target: Watch,
+        write: bool,
+    },
+
+    /// The step, step over or step out finished.
+    Step,
+
+    /// The cycles given ran out without anything else stopping the machine.
+    CycleLimit,
+
+    /// The machine is paused and will not execute anything.
+    Paused,
+
+    /// The program exited.
+    Exited,
+}
+
+/// What has to happen for a resume to end on its own.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    Run,
+    Step,
+    /// Stops once the stack is back to the depth.
+    Over(usize),
+    /// Stops once the stack is below the depth.
+    Out(usize),
+}
+
+/// A span of memory or a register touched by an instruction.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Span {
+    Memory(Address, usize),
+    Register(Register),
+}
+
+impl Span {
+    /// Returns a flag indicating if the span covers the target.
+    ///
+    fn covers(self, target: Watch) -> bool {
+        match (self, target) {
+            (Span::Memory(start, len), Watch::Memory(addr)) => {
+                (addr.get_raw().wrapping_sub(start.get_raw()) as usize) < len
+            }
+            (Span::Register(r), Watch::Register(target)) => r == target,
+            _ => false,
+        }
+    }
+}
+
+/// Controls the execution of a machine for a debugger front end.
+///
+#[derive(Debug, Default)]
+pub struct Debugger {
+    breakpoints: Vec<Address>,
+    kind_breakpoints: Vec<InstructionKind>,
+    watchpoints: Vec<(Watch, Access)>,
+
+    /// The address of the breakpoint the machine is stopped at, which
+    /// does not fire again when it resumes.
+    stopped_at: Option<Address>,
+}
+
+impl Debugger {
+    /// Creates a new Debugger without any breakpoints.
+    ///
+    pub fn new() -> Self {
+        Debugger::default()
+    }
+
+    /// Stops before the instruction at the address is executed.
+    ///
+    ///### Arguments
+    ///
+    ///- **addr** : The address of the instruction.
+    ///
+    pub fn add_breakpoint(&mut self, addr: Address) {
+        if !self.breakpoints.contains(&addr) {
+            self.breakpoints.push(addr);
+        }
+    }
+
+    /// Removes the breakpoint at the address. Returns a flag indicating if
+    /// there was one.
+    ///
+    pub fn remove_breakpoint(&mut self, addr: Address) -> bool {
+        let len = self.breakpoints.len();
+        self.breakpoints.retain(|a| *a != addr);
+
+        self.breakpoints.len() != len
+    }
+
+    /// Returns the addresses with a breakpoint.
+    ///
+    pub fn breakpoints(&self) -> &[Address] {
+        &self.breakpoints
+    }
+
+    /// Stops before every instruction of the kind is executed.
+    ///
+    ///### Arguments
+    ///
+    ///- **kind** : The kind of instruction, e.g. `InstructionKind::Draw`.
+    ///
+    pub fn add_kind_breakpoint(&mut self, kind: InstructionKind) {
+        if !self.kind_breakpoints.contains(&kind) {
+            self.kind_breakpoints.push(kind);
+        }
+    }
+
+    /// Removes the breakpoint on the kind of instruction. Returns a flag
+    /// indicating if there was one.
+    ///
+    pub fn remove_kind_breakpoint(&mut self, kind: InstructionKind) -> bool {
+        let len = self.kind_breakpoints.len();
+        self.kind_breakpoints.retain(|k| *k != kind);
+
+        self.kind_breakpoints.len() != len
+    }
+
+    /// Stops after an instruction accesses the target. Replaces the access of
+    /// an existing watchpoint on the same target.
+    ///
+    ///### Arguments
+    ///
+    ///- **target** : The memory address or register to watch.
+    ///- **access** : Whether to stop on reads, writes or both.
+    ///
+    pub fn add_watchpoint(&mut self, target: Watch, access: Access) {
+        self.remove_watchpoint(target);
+        self.watchpoints.push((target, access));
+    }
+
+    /// Removes the watchpoint on the target. Returns a flag indicating if
+    /// there was one.
+    ///
+    pub fn remove_watchpoint(&mut self, target: Watch) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|(t, _)| *t != target);
+
+        self.watchpoints.len() != len
+    }
+
+    /// Removes every breakpoint and watchpoint.
+    ///
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.kind_breakpoints.clear();
+        self.watchpoints.clear();
+    }
+
+    /// Runs until something stops the machine.
+    ///
+    /// The breakpoint the machine last stopped at does not fire again, so
+    /// calling run after a breakpoint moves on.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm**         : The machine.
+    ///- **max_cycles** : The most cycles to run before giving control back.
+    ///
+    pub fn run<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        max_cycles: u64,
+    ) -> Result<StopReason, Chip8Error>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        self.resume(vm, Mode::Run, max_cycles)
+    }
+
+    /// Executes a single instruction, or a single check for the key while
+    /// the machine waits for one.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine.
+    ///
+    pub fn step<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    ) -> Result<StopReason, Chip8Error>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        self.resume(vm, Mode::Step, 1)
+    }
+
+    /// Executes a single instruction, running a `Call` through to its return.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm**         : The machine.
+    ///- **max_cycles** : The most cycles to run before giving control back.
+    ///
+    pub fn step_over<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        max_cycles: u64,
+    ) -> Result<StopReason, Chip8Error>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        let depth = vm.pc.depth();
+
+        self.resume(vm, Mode::Over(depth), max_cycles)
+    }
+
+    /// Runs until the current subroutine returns.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm**         : The machine.
+    ///- **max_cycles** : The most cycles to run before giving control back.
+    ///
+    pub fn step_out<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        max_cycles: u64,
+    ) -> Result<StopReason, Chip8Error>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        let depth = vm.pc.depth();
+
+        self.resume(vm, Mode::Out(depth), max_cycles)
+    }
+
+    /// Executes cycles until a breakpoint, a watchpoint or the mode stops
+    /// the machine.
+    ///
+    fn resume<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        mode: Mode,
+        max_cycles: u64,
+    ) -> Result<StopReason, Chip8Error>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        let resumed_at = self.stopped_at.take();
+
+        for cycle in 0..max_cycles {
+            if vm.is_exited() {
+                return Ok(StopReason::Exited);
+            }
+
+            if vm.is_paused() {
+                return Ok(StopReason::Paused);
+            }
+
+            if vm.is_waiting() {
+                vm.execute_cycles(1)?;
+            } else {
+                let pc = vm.pc.current();
+                let instruction = vm.get_instr()?;
+
+                if cycle > 0 || resumed_at != Some(pc) {
+                    let kind = instruction.kind();
+
+                    if self.breakpoints.contains(&pc) {
+                        self.stopped_at = Some(pc);
+                        return Ok(StopReason::Breakpoint(pc));
+                    }
+
+                    if self.kind_breakpoints.contains(&kind) {
+                        self.stopped_at = Some(pc);
+                        return Ok(StopReason::InstructionBreakpoint(pc, kind));
+                    }
+                }
+
+                // The accesses depend on I and the registers, which the
+                // instruction may change.
+                let hit = self.watch_hit(vm, instruction);
+
+                vm.execute_cycles(1)?;
+
+                if let Some((target, write)) = hit {
+                    return Ok(StopReason::Watchpoint { pc, target, write });
+                }
+            }
+
+            let depth = vm.pc.depth();
+            let done = match mode {
+                Mode::Run => false,
+                Mode::Step => true,
+                Mode::Over(d) => depth <= d,
+                Mode::Out(d) => depth < d,
+            };
+
+            if done {
+                return Ok(StopReason::Step);
+            }
+        }
+
+        Ok(StopReason::CycleLimit)
+    }
+
+    /// Returns the first watchpoint the instruction will trigger.
+    ///
+    fn watch_hit<M, PC, R, FB, KB, RNG, AU>(
+        &self,
+        vm: &VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        instruction: Instruction,
+    ) -> Option<(Watch, bool)>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        if self.watchpoints.is_empty() {
+            return None;
+        }
+
+        let accesses = vm.accesses(instruction);
+
+        accesses.iter().find_map(|(span, write)| {
+            self.watchpoints
+                .iter()
+                .find(|(target, access)| access.matches(*write) && span.covers(*target))
+                .map(|(target, _)| (*target, *write))
+        })
+    }
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> VirtualMachine<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Returns the memory and registers the instruction will read and write,
+    /// with a flag indicating if the access is a write.
+    ///
+    /// This mirrors `interpret_instruction` and has to be kept in step with it.
+    ///
+    ///### Arguments
+    ///
+    ///- **instruction** : The next instruction.
+    ///
+    fn accesses(&self, instruction: Instruction) -> Vec<(Span, bool)> {
+        use Instruction::*;
+        use Span::Register as Reg;
+
+        let i = self.registers.get_i();
+        let memory = |len: usize| Span::Memory(i, len);
+
+        let read = |r| (Reg(r), false);
+        let write = |r| (Reg(r), true);
+
+        match instruction {
+            SkipEqualByte(vx, _) | SkipNotEqualByte(vx, _) => vec![read(vx)],
+            SkipEqualReg(vx, vy) | SkipNotEqualReg(vx, vy) => vec![read(vx), read(vy)],
+
+            SaveRange(vx, vy) => {
+                let regs = Self::register_range(vx, vy);
+                let mut out: Vec<_> = regs.iter().map(|r| read(*r)).collect();
+                out.push((memory(regs.len()), true));
+                out
+            }
+
+            LoadRange(vx, vy) => {
+                let regs = Self::register_range(vx, vy);
+                let mut out = vec![(memory(regs.len()), false)];
+                out.extend(regs.iter().map(|r| write(*r)));
+                out
+            }
+
+            Load(vx, _) | Rand(vx, _) | LoadDelayTimer(vx) | WaitForKey(vx) => vec![write(vx)],
+            Add(vx, _) => vec![read(vx), write(vx)],
+            LoadFromReg(vx, vy) => vec![read(vy), write(vx)],
+
+            Or(vx, vy) | And(vx, vy) | XOr(vx, vy) => {
+                let mut out = vec![read(vx), read(vy), write(vx)];
+                if self.quirks.logic_resets_vf {
+                    out.push(write(Register::VF));
+                }
+                out
+            }
+
+            AddReg(vx, vy) | SubReg(vx, vy) | ReverseSub(vx, vy) => {
+                vec![read(vx), read(vy), write(vx), write(Register::VF)]
+            }
+
+            ShiftRight(vx, vy) | ShiftLeft(vx, vy) => {
+                let source = if self.quirks.shift_uses_vy { vy } else { vx };
+                vec![read(source), write(vx), write(Register::VF)]
+            }
+
+            JumpPlus(addr) => {
+                let offset = if self.quirks.jump_uses_vx {
+                    let x = (addr.get_raw() >> 8) as u8;
+                    Register::new(x & 0xF).unwrap_or(Register::V0)
+                } else {
+                    Register::V0
+                };
+
+                vec![read(offset)]
+            }
+
+            Draw(vx, vy, n) => {
+                let planes = self.framebuffer.selected_planes().count_ones() as usize;
+                let len = match n.get_raw() {
+                    0 => 32,
+                    n => n as usize,
+                };
+
+                vec![
+                    read(vx),
+                    read(vy),
+                    (memory(len * planes), false),
+                    write(Register::VF),
+                ]
+            }
+
+            LoadAudioPattern => vec![(memory(super::AUDIO_PATTERN_SIZE), false)],
+
+            SkipPressed(vx)
+            | SkipNotPressed(vx)
+            | SetDelayTimer(vx)
+            | SetSoundTimer(vx)
+            | IncrementAddress(vx)
+            | LoadSpriteAddress(vx)
+            | LoadBigSpriteAddress(vx)
+            | SetPitch(vx) => vec![read(vx)],
+
+            LoadBCD(vx) => vec![read(vx), (memory(3), true)],
+
+            CopyToRam(vx) => {
+                let mut out: Vec<_> = Register::iter_to(vx).map(read).collect();
+                out.push((memory(vx as usize + 1), true));
+                out
+            }
+
+            CopyToRegisters(vx) => {
+                let mut out = vec![(memory(vx as usize + 1), false)];
+                out.extend(Register::iter_to(vx).map(write));
+                out
+            }
+
+            SaveFlags(vx) => Register::iter_to(vx).map(read).collect(),
+            LoadFlags(vx) => Register::iter_to(vx).map(write).collect(),
+
+            Invalid(_) | Sys(_) | Cls | Return | ScrollDown(_) | ScrollUp(_) | ScrollRight
+            | ScrollLeft | Exit | LowRes | HighRes | Jump(_) | Call(_) | LoadInstr(_)
+            | LoadLongInstr(_) | SelectPlanes(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{TestVm, VmBuilder};
+    use super::super::{Quirks, VMState};
+    use super::*;
+
+    fn get_vm(program: &[u8]) -> TestVm {
+        VmBuilder::new()
+            .quirks(Quirks::cosmac_vip())
+            .program(program)
+            .build()
+    }
+
+    /// 200: CALL 208, 202: LD V1, 2, 204: JP 204, 208: LD V2, 3, 20A: RET
+    const CALL_PROGRAM: [u8; 12] = [
+        0x22, 0x08, 0x61, 0x02, 0x12, 0x04, 0x00, 0x00, 0x62, 0x03, 0x00, 0xEE,
+    ];
+
+    #[test]
+    fn stops_at_breakpoints_before_executing() {
+        let mut vm = get_vm(&CALL_PROGRAM);
+        let mut debugger = Debugger::new();
+        debugger.add_breakpoint(Address::new(0x20A));
+
+        let reason = debugger.run(&mut vm, 100).unwrap();
+
+        assert_eq!(reason, StopReason::Breakpoint(Address::new(0x20A)));
+        assert_eq!(vm.pc.depth(), 2);
+
+        // Running again moves past the breakpoint.
+        debugger.add_breakpoint(Address::new(0x204));
+        let reason = debugger.run(&mut vm, 100).unwrap();
+        assert_eq!(reason, StopReason::Breakpoint(Address::new(0x204)));
+        assert_eq!(vm.registers.get_v(Register::V1), 2.into());
+    }
+
+    #[test]
+    fn breakpoints_fire_between_runs() {
+        let mut vm = get_vm(&CALL_PROGRAM);
+        let mut debugger = Debugger::new();
+        debugger.add_breakpoint(Address::new(0x200));
+        debugger.add_breakpoint(Address::new(0x208));
+
+        // The machine did not stop at the first instruction, so it breaks.
+        let reason = debugger.run(&mut vm, 100).unwrap();
+        assert_eq!(reason, StopReason::Breakpoint(Address::new(0x200)));
+
+        assert_eq!(debugger.run(&mut vm, 1).unwrap(), StopReason::CycleLimit);
+        let reason = debugger.run(&mut vm, 100).unwrap();
+        assert_eq!(reason, StopReason::Breakpoint(Address::new(0x208)));
+    }
+
+    #[test]
+    fn stops_on_kinds_of_instructions() {
+        let mut vm = get_vm(&[0x60, 0x01, 0xD0, 0x05, 0x12, 0x02]);
+        let mut debugger = Debugger::new();
+        debugger.add_kind_breakpoint(InstructionKind::Draw);
+
+        let reason = debugger.run(&mut vm, 100).unwrap();
+
+        assert_eq!(
+            reason,
+            StopReason::InstructionBreakpoint(Address::new(0x202), InstructionKind::Draw)
+        );
+    }
+
+    #[test]
+    fn step_over_runs_the_whole_subroutine() {
+        let mut vm = get_vm(&CALL_PROGRAM);
+        let mut debugger = Debugger::new();
+
+        assert_eq!(debugger.step_over(&mut vm, 100).unwrap(), StopReason::Step);
+        assert_eq!(vm.pc.current(), Address::new(0x202));
+        assert_eq!(vm.registers.get_v(Register::V2), 3.into());
+
+        assert_eq!(debugger.step_over(&mut vm, 100).unwrap(), StopReason::Step);
+        assert_eq!(vm.pc.current(), Address::new(0x204));
+    }
+
+    #[test]
+    fn step_and_step_out() {
+        let mut vm = get_vm(&CALL_PROGRAM);
+        let mut debugger = Debugger::new();
+
+        assert_eq!(debugger.step(&mut vm).unwrap(), StopReason::Step);
+        assert_eq!(vm.pc.current(), Address::new(0x208));
+
+        assert_eq!(debugger.step_out(&mut vm, 100).unwrap(), StopReason::Step);
+        assert_eq!(vm.pc.current(), Address::new(0x202));
+        assert_eq!(vm.pc.depth(), 1);
+
+        // There is nothing to step out of in the main routine.
+        assert_eq!(
+            debugger.step_out(&mut vm, 10).unwrap(),
+            StopReason::CycleLimit
+        );
+    }
+
+    #[test]
+    fn watchpoints_stop_after_the_access() {
+        // 200: LD I, 300, 202: LD V0, 7, 204: LD [I], V1, 206: LD V1, [I], 208: JP 208
+        let program = [0xA3, 0x00, 0x60, 0x07, 0xF1, 0x55, 0xF1, 0x65, 0x12, 0x08];
+        let mut vm = get_vm(&program);
+        let mut debugger = Debugger::new();
+        debugger.add_watchpoint(Watch::Memory(Address::new(0x301)), Access::Write);
+        debugger.add_watchpoint(Watch::Register(Register::V0), Access::Write);
+
+        let reason = debugger.run(&mut vm, 100).unwrap();
+        assert_eq!(
+            reason,
+            StopReason::Watchpoint {
+                pc: Address::new(0x202),
+                target: Watch::Register(Register::V0),
+                write: true,
+            }
+        );
+        assert_eq!(vm.registers.get_v(Register::V0), 7.into());
+
+        let reason = debugger.run(&mut vm, 100).unwrap();
+        assert_eq!(
+            reason,
+            StopReason::Watchpoint {
+                pc: Address::new(0x204),
+                target: Watch::Memory(Address::new(0x301)),
+                write: true,
+            }
+        );
+
+        // Memory reads are not watched, but the load writes V0.
+        let reason = debugger.run(&mut vm, 100).unwrap();
+        assert_eq!(
+            reason,
+            StopReason::Watchpoint {
+                pc: Address::new(0x206),
+                target: Watch::Register(Register::V0),
+                write: true,
+            }
+        );
+    }
+
+    #[test]
+    fn the_carry_of_add_is_a_write_to_vf() {
+        // 200: LD V0, FF, 202: LD V1, 1, 204: ADD V0, V1
+        let mut vm = get_vm(&[0x60, 0xFF, 0x61, 0x01, 0x80, 0x14]);
+        let mut debugger = Debugger::new();
+        debugger.add_watchpoint(Watch::Register(Register::VF), Access::Write);
+
+        let reason = debugger.run(&mut vm, 100).unwrap();
+        assert_eq!(
+            reason,
+            StopReason::Watchpoint {
+                pc: Address::new(0x204),
+                target: Watch::Register(Register::VF),
+                write: true,
+            }
+        );
+    }
+
+    #[test]
+    fn read_watchpoints_ignore_writes() {
+        // 200: LD V3, 1, 202: SE V3, 1, 204: JP 204
+        let mut vm = get_vm(&[0x63, 0x01, 0x33, 0x01, 0x12, 0x04]);
+        let mut debugger = Debugger::new();
+        debugger.add_watchpoint(Watch::Register(Register::V3), Access::Read);
+
+        let reason = debugger.run(&mut vm, 100).unwrap();
+
+        assert_eq!(
+            reason,
+            StopReason::Watchpoint {
+                pc: Address::new(0x202),
+                target: Watch::Register(Register::V3),
+                write: false,
+            }
+        );
+    }
+
+    #[test]
+    fn stops_when_the_program_exits() {
+        let mut vm = get_vm(&[0x00, 0xFD]);
+
+        assert_eq!(
+            Debugger::new().run(&mut vm, 100).unwrap(),
+            StopReason::Exited
+        );
+        assert_eq!(vm.state, VMState::Exited);
+    }
+
+    #[test]
+    fn copy_to_ram_reads_registers_and_writes_memory() {
+        let vm = get_vm(&[]);
+
+        assert_eq!(
+            vm.accesses(Instruction::CopyToRam(Register::V1)),
+            vec![
+                (Span::Register(Register::V0), false),
+                (Span::Register(Register::V1), false),
+                (Span::Memory(Address::new(0), 2), true),
+            ]
+        );
+    }
+}
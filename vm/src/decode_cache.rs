@@ -0,0 +1,120 @@
+//! A cache of decoded instructions, keyed by address.
+//!
+//! Decoding is the most expensive part of a cycle, and most programs run the
+//! same few hundred instructions over and over. Entries are dropped whenever
+//! the memory under them is written, so self-modifying code still sees its
+//! own changes.
+//!
+
+use data::Address;
+use instruction::Instruction;
+
+/// The most bytes an instruction covers, `F000 NNNN`.
+const MAX_INSTRUCTION_SIZE: usize = 4;
+
+/// An instruction together with the raw words it was decoded from.
+///
+pub(crate) type Decoded = ((u16, u16), Instruction);
+
+/// Holds the decoded instruction at every address that has been executed.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DecodeCache {
+    entries: Vec<Option<Decoded>>,
+}
+
+impl DecodeCache {
+    /// Creates an empty cache for a memory of the given size.
+    ///
+    ///### Arguments
+    ///
+    ///- **size** : The number of addressable bytes.
+    ///
+    pub(crate) fn new(size: usize) -> Self {
+        DecodeCache {
+            entries: vec![None; size],
+        }
+    }
+
+    /// Returns the instruction decoded at the address, if there is one.
+    ///
+    pub(crate) fn get(&self, address: Address) -> Option<Decoded> {
+        self.entries
+            .get(address.get_raw() as usize)
+            .copied()
+            .flatten()
+    }
+
+    /// Stores the instruction decoded at the address.
+    ///
+    pub(crate) fn insert(&mut self, address: Address, decoded: Decoded) {
+        if let Some(entry) = self.entries.get_mut(address.get_raw() as usize) {
+            *entry = Some(decoded);
+        }
+    }
+
+    /// Drops every instruction that covers the address.
+    ///
+    ///### Arguments
+    ///
+    ///- **address** : The address that was written.
+    ///
+    pub(crate) fn invalidate(&mut self, address: Address) {
+        let end = address.get_raw() as usize + 1;
+        let start = end.saturating_sub(MAX_INSTRUCTION_SIZE);
+
+        for entry in self.entries.iter_mut().take(end).skip(start) {
+            *entry = None;
+        }
+    }
+
+    /// Drops every instruction.
+    ///
+    pub(crate) fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoded(word: u16) -> Decoded {
+        ((word, 0), Instruction::new(word))
+    }
+
+    #[test]
+    fn writes_drop_every_instruction_covering_the_address() {
+        let mut cache = DecodeCache::new(0x1000);
+
+        for addr in 0x200..0x208 {
+            cache.insert(Address::new(addr), decoded(0x6001));
+        }
+
+        cache.invalidate(Address::new(0x205));
+
+        let cached: Vec<bool> = (0x200..0x208)
+            .map(|addr| cache.get(Address::new(addr)).is_some())
+            .collect();
+
+        assert_eq!(cached, [true, true, false, false, false, false, true, true]);
+    }
+
+    #[test]
+    fn addresses_outside_the_memory_are_never_cached() {
+        let mut cache = DecodeCache::new(0x10);
+
+        cache.insert(Address::new(0x10), decoded(0x00E0));
+        cache.invalidate(Address::new(0x11));
+
+        assert_eq!(cache.get(Address::new(0x10)), None);
+
+        cache.insert(Address::new(0x0F), decoded(0x00E0));
+        cache.invalidate(Address::new(0x01));
+        cache.invalidate(Address::new(0x0));
+
+        assert_eq!(cache.get(Address::new(0x0F)), Some(decoded(0x00E0)));
+    }
+}
@@ -0,0 +1,829 @@
+//! A GDB remote serial protocol stub, so gdb or any other RSP client can
+//! debug a machine over TCP.
+//!
+//! The registers are numbered V0 to VF (0 to 15, 8 bits), I (16, 16 bits),
+//! PC (17, 16 bits), SP (18, 8 bits), DT (19, 8 bits) and ST (20, 8 bits).
+//! The 16 bit registers are sent big endian like the rest of CHIP-8. The
+//! layout is also described by the `target.xml` the stub serves.
+//!
+//! SP is the number of return addresses on the stack and can not be written.
+//!
+//! While the machine runs, the timers are ticked every frame's worth of
+//! instructions, so programs waiting on the delay timer make progress.
+//!
+
+use super::{cycles_for_frame, Access, Debugger, StopReason, VirtualMachine, Watch};
+use data::Address;
+use model::{
+    Chip8Audio, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter, Chip8Random,
+    Chip8RegisterBank, Chip8VirtualMachine, Register,
+};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+/// The byte a client sends to interrupt a running machine.
+///
+const INTERRUPT: u8 = 0x03;
+
+/// The number of registers and the size of each in bytes.
+///
+const REGISTER_SIZES: [usize; 21] = [
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 1, 1, 1,
+];
+
+const REG_I: usize = 16;
+const REG_PC: usize = 17;
+const REG_SP: usize = 18;
+const REG_DT: usize = 19;
+const REG_ST: usize = 20;
+
+/// Serves a single RSP client.
+///
+pub struct GdbStub {
+    stream: TcpStream,
+    debugger: Debugger,
+    instructions_per_second: u64,
+
+    /// The frame the machine is in and the instructions left before its
+    /// timers tick.
+    frame: u64,
+    budget: usize,
+
+    /// Whether packets are acknowledged, until the client turns it off.
+    ack: bool,
+
+    /// Bytes read while checking for an interrupt that were not one, read
+    /// again before the stream.
+    unread: VecDeque<u8>,
+}
+
+impl GdbStub {
+    /// Waits for a client to connect.
+    ///
+    ///### Arguments
+    ///
+    ///- **addr**                    : The address to listen on, e.g. "127.0.0.1:9000".
+    ///- **instructions_per_second** : The speed of the CPU, for ticking the timers.
+    ///
+    pub fn listen<A: ToSocketAddrs>(addr: A, instructions_per_second: u64) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, _) = listener.accept()?;
+
+        Ok(GdbStub::new(stream, instructions_per_second))
+    }
+
+    /// Creates a new GdbStub for a connected client.
+    ///
+    ///### Arguments
+    ///
+    ///- **stream**                  : The connection to the client.
+    ///- **instructions_per_second** : The speed of the CPU, for ticking the timers.
+    ///
+    pub fn new(stream: TcpStream, instructions_per_second: u64) -> Self {
+        GdbStub {
+            stream,
+            debugger: Debugger::new(),
+            instructions_per_second,
+            frame: 0,
+            budget: cycles_for_frame(instructions_per_second, 0),
+            ack: true,
+            unread: VecDeque::new(),
+        }
+    }
+
+    /// Returns the debugger, to set breakpoints the client does not know about.
+    ///
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    /// Answers the client until it detaches, kills the machine or hangs up.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine to debug, stopped until the client continues it.
+    ///
+    pub fn serve<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    ) -> io::Result<()>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        loop {
+            let packet = match self.read_packet() {
+                Ok(packet) => packet,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+
+            match packet.as_str() {
+                "QStartNoAckMode" => {
+                    self.write_packet("OK")?;
+                    self.ack = false;
+                }
+                "D" => return self.write_packet("OK"),
+                "k" | "vKill" => return Ok(()),
+                _ => {
+                    let reply = self.handle(vm, &packet)?;
+                    self.write_packet(&reply)?;
+                }
+            }
+        }
+    }
+
+    /// Returns the reply to a packet.
+    ///
+    fn handle<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        packet: &str,
+    ) -> io::Result<String>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        let (command, args) = packet.split_at(packet.len().min(1));
+
+        let reply = match command {
+            "?" => "S05".to_string(),
+            "g" => encode_hex(&read_registers(vm)),
+            "G" => ok_or_error(write_registers(vm, args)),
+            "p" => usize::from_str_radix(args, 16)
+                .ok()
+                .and_then(|n| read_register(vm, n))
+                .map_or_else(|| "E01".to_string(), |bytes| encode_hex(&bytes)),
+            "P" => ok_or_error(args.split_once('=').and_then(|(n, value)| {
+                let n = usize::from_str_radix(n, 16).ok()?;
+
+                write_register(vm, n, &decode_hex(value)?)
+            })),
+            "m" => read_memory(vm, args).unwrap_or_else(|| "E01".to_string()),
+            "M" => ok_or_error(write_memory(vm, args)),
+            "c" | "s" => {
+                if !args.is_empty() {
+                    match u16::from_str_radix(args, 16) {
+                        Ok(addr) => vm.pc.set(Address::new(addr)),
+                        Err(_) => return Ok("E01".to_string()),
+                    }
+                }
+
+                self.resume(vm, command == "s")?
+            }
+            "Z" | "z" => ok_or_error(self.set_point(command == "Z", args)),
+            "H" => "OK".to_string(),
+            "q" => query(args),
+            _ => String::new(),
+        };
+
+        Ok(reply)
+    }
+
+    /// Runs or steps the machine and returns the stop reply.
+    ///
+    fn resume<M, PC, R, FB, KB, RNG, AU>(
+        &mut self,
+        vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        step: bool,
+    ) -> io::Result<String>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        loop {
+            while self.budget == 0 {
+                vm.tick_timers();
+                self.frame += 1;
+                self.budget = cycles_for_frame(self.instructions_per_second, self.frame);
+            }
+
+            let before = vm.cycles();
+            let result = if step {
+                self.debugger.step(vm)
+            } else {
+                self.debugger.run(vm, self.budget as u64)
+            };
+
+            let used = (vm.cycles() - before) as usize;
+            self.budget = self.budget.saturating_sub(used);
+
+            let reply = match result {
+                // Errors such as invalid opcodes stop the machine as an
+                // illegal instruction, leaving the state for inspection.
+                Err(_) => "S04".to_string(),
+                Ok(StopReason::Exited) => "W00".to_string(),
+                Ok(StopReason::Watchpoint {
+                    target: Watch::Memory(addr),
+                    write,
+                    ..
+                }) => {
+                    let kind = if write { "watch" } else { "rwatch" };
+                    format!("T05{}:{:x};", kind, addr.get_raw())
+                }
+                Ok(StopReason::CycleLimit) => {
+                    if self.interrupted()? {
+                        "S02".to_string()
+                    } else {
+                        continue;
+                    }
+                }
+                Ok(_) => "S05".to_string(),
+            };
+
+            return Ok(reply);
+        }
+    }
+
+    /// Adds or removes a breakpoint or watchpoint. Returns None for packets
+    /// that can not be parsed.
+    ///
+    ///### Arguments
+    ///
+    ///- **add**  : Whether the packet was a Z or a z.
+    ///- **args** : The type, address and kind.
+    ///
+    fn set_point(&mut self, add: bool, args: &str) -> Option<()> {
+        let mut parts = args.split(',');
+        let kind = parts.next()?;
+        let addr = u16::from_str_radix(parts.next()?, 16).ok()?;
+        let len = usize::from_str_radix(parts.next()?, 16).ok()?;
+
+        let access = match kind {
+            "0" | "1" => {
+                if add {
+                    self.debugger.add_breakpoint(Address::new(addr));
+                } else {
+                    self.debugger.remove_breakpoint(Address::new(addr));
+                }
+
+                return Some(());
+            }
+            "2" => Access::Write,
+            "3" => Access::Read,
+            "4" => Access::ReadWrite,
+            _ => return None,
+        };
+
+        for n in 0..len {
+            let target = Watch::Memory(Address::new(addr.wrapping_add(n as u16)));
+
+            if add {
+                self.debugger.add_watchpoint(target, access);
+            } else {
+                self.debugger.remove_watchpoint(target);
+            }
+        }
+
+        Some(())
+    }
+
+    /// Returns a flag indicating if the client asked to stop the machine.
+    ///
+    fn interrupted(&mut self) -> io::Result<bool> {
+        let mut byte = [0];
+
+        self.stream.set_nonblocking(true)?;
+        let result = self.stream.read(&mut byte);
+        self.stream.set_nonblocking(false)?;
+
+        match result {
+            Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(_) if byte[0] == INTERRUPT => Ok(true),
+            Ok(_) => {
+                self.unread.push_back(byte[0]);
+
+                Ok(false)
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads a single byte from the client.
+    ///
+    fn read_byte(&mut self) -> io::Result<u8> {
+        if let Some(byte) = self.unread.pop_front() {
+            return Ok(byte);
+        }
+
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte)?;
+
+        Ok(byte[0])
+    }
+
+    /// Reads the next packet, skipping acknowledgements and interrupts sent
+    /// while the machine is already stopped.
+    ///
+    fn read_packet(&mut self) -> io::Result<String> {
+        loop {
+            while self.read_byte()? != b'$' {}
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    b'#' => break,
+                    byte => data.push(byte),
+                }
+            }
+
+            let sum = [self.read_byte()?, self.read_byte()?];
+            let valid = decode_hex(&String::from_utf8_lossy(&sum))
+                .is_some_and(|sum| sum[0] == checksum(&data));
+
+            if self.ack {
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+            }
+
+            if valid || !self.ack {
+                return Ok(String::from_utf8_lossy(&data).into_owned());
+            }
+        }
+    }
+
+    /// Sends a packet, resending it until the client acknowledges it.
+    ///
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let mut packet = Vec::with_capacity(data.len() + 4);
+        packet.push(b'$');
+        for byte in data.bytes() {
+            // These would end or corrupt the packet.
+            if let b'$' | b'#' | b'}' | b'*' = byte {
+                packet.push(b'}');
+                packet.push(byte ^ 0x20);
+            } else {
+                packet.push(byte);
+            }
+        }
+
+        let sum = checksum(&packet[1..]);
+        packet.extend_from_slice(format!("#{:02x}", sum).as_bytes());
+
+        loop {
+            self.stream.write_all(&packet)?;
+
+            if !self.ack {
+                return Ok(());
+            }
+
+            loop {
+                match self.read_byte()? {
+                    b'+' => return Ok(()),
+                    b'-' => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Returns the reply to a general query.
+///
+fn query(args: &str) -> String {
+    const FEATURES: &str = "Xfer:features:read:target.xml:";
+
+    if args.starts_with("Supported") {
+        "PacketSize=1000;qXfer:features:read+;QStartNoAckMode+".to_string()
+    } else if args == "Attached" {
+        "1".to_string()
+    } else if let Some(range) = args.strip_prefix(FEATURES) {
+        let xml = target_xml();
+        let part = range.split_once(',').and_then(|(offset, len)| {
+            let offset = usize::from_str_radix(offset, 16).ok()?;
+            let len = usize::from_str_radix(len, 16).ok()?;
+
+            Some((offset.min(xml.len()), len))
+        });
+
+        match part {
+            Some((offset, len)) => {
+                let end = (offset + len).min(xml.len());
+                let more = if end < xml.len() { "m" } else { "l" };
+
+                format!("{}{}", more, &xml[offset..end])
+            }
+            None => "E01".to_string(),
+        }
+    } else {
+        String::new()
+    }
+}
+
+/// Returns the description of the registers for the client.
+///
+fn target_xml() -> String {
+    let mut regs = String::new();
+
+    for n in 0..16 {
+        regs.push_str(&format!(
+            "<reg name=\"v{:x}\" bitsize=\"8\" type=\"uint8\"/>",
+            n
+        ));
+    }
+
+    regs.push_str("<reg name=\"i\" bitsize=\"16\" type=\"data_ptr\"/>");
+    regs.push_str("<reg name=\"pc\" bitsize=\"16\" type=\"code_ptr\"/>");
+    regs.push_str("<reg name=\"sp\" bitsize=\"8\" type=\"uint8\"/>");
+    regs.push_str("<reg name=\"dt\" bitsize=\"8\" type=\"uint8\"/>");
+    regs.push_str("<reg name=\"st\" bitsize=\"8\" type=\"uint8\"/>");
+
+    format!(
+        "<?xml version=\"1.0\"?><!DOCTYPE target SYSTEM \"gdb-target.dtd\">\
+         <target version=\"1.0\"><feature name=\"org.chip8.core\">{}</feature></target>",
+        regs
+    )
+}
+
+/// Returns the contents of the register, big endian.
+///
+fn read_register<M, PC, R, FB, KB, RNG, AU>(
+    vm: &VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    n: usize,
+) -> Option<Vec<u8>>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    let bytes = match n {
+        0..=15 => vec![vm.registers.get_v(Register::new(n as u8).ok()?).get_raw()],
+        REG_I => vm.registers.get_i().get_raw().to_be_bytes().to_vec(),
+        REG_PC => vm.pc.current().get_raw().to_be_bytes().to_vec(),
+        REG_SP => vec![vm.pc.depth().saturating_sub(1) as u8],
+        REG_DT => vec![vm.delay_timer],
+        REG_ST => vec![vm.sound_timer],
+        _ => return None,
+    };
+
+    Some(bytes)
+}
+
+/// Sets the register. Returns None if it does not exist or can not be set.
+///
+fn write_register<M, PC, R, FB, KB, RNG, AU>(
+    vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    n: usize,
+    bytes: &[u8],
+) -> Option<()>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    if bytes.len() != *REGISTER_SIZES.get(n)? {
+        return None;
+    }
+
+    let word = || Address::new(u16::from_be_bytes([bytes[0], bytes[1]]));
+
+    match n {
+        0..=15 => vm
+            .registers
+            .set_v(Register::new(n as u8).ok()?, bytes[0].into()),
+        REG_I => vm.registers.set_i(word()),
+        REG_PC => vm.pc.set(word()),
+        // Writing back the value read, as G does, is fine.
+        REG_SP if read_register(vm, REG_SP)? == bytes => {}
+        REG_DT => vm.delay_timer = bytes[0],
+        REG_ST => {
+            vm.sound_timer = bytes[0];
+            vm.update_audio();
+        }
+        _ => return None,
+    }
+
+    Some(())
+}
+
+/// Returns every register in order.
+///
+fn read_registers<M, PC, R, FB, KB, RNG, AU>(
+    vm: &VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+) -> Vec<u8>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    (0..REGISTER_SIZES.len())
+        .filter_map(|n| read_register(vm, n))
+        .flatten()
+        .collect()
+}
+
+/// Sets every register from the hex of a G packet.
+///
+fn write_registers<M, PC, R, FB, KB, RNG, AU>(
+    vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    hex: &str,
+) -> Option<()>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    let bytes = decode_hex(hex)?;
+    if bytes.len() != REGISTER_SIZES.iter().sum::<usize>() {
+        return None;
+    }
+
+    let mut offset = 0;
+    for (n, size) in REGISTER_SIZES.iter().enumerate() {
+        write_register(vm, n, &bytes[offset..offset + size])?;
+        offset += size;
+    }
+
+    Some(())
+}
+
+/// Parses the address and length of an m or M packet.
+///
+fn parse_range(args: &str) -> Option<(u16, usize)> {
+    let (addr, len) = args.split_once(',')?;
+
+    Some((
+        u16::from_str_radix(addr, 16).ok()?,
+        usize::from_str_radix(len, 16).ok()?,
+    ))
+}
+
+/// Returns the hex of the memory an m packet asks for, stopping at the end
+/// of memory.
+///
+fn read_memory<M, PC, R, FB, KB, RNG, AU>(
+    vm: &VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    args: &str,
+) -> Option<String>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    let (addr, len) = parse_range(args)?;
+
+    let bytes: Vec<u8> = (0..len)
+        .map_while(|n| vm.memory.get(Address::new(addr) + (n as u16)).ok())
+        .map(|byte| byte.get_raw())
+        .collect();
+
+    if bytes.is_empty() && len > 0 {
+        None
+    } else {
+        Some(encode_hex(&bytes))
+    }
+}
+
+/// Writes the memory of an M packet.
+///
+fn write_memory<M, PC, R, FB, KB, RNG, AU>(
+    vm: &mut VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    args: &str,
+) -> Option<()>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    let (range, hex) = args.split_once(':')?;
+    let (addr, len) = parse_range(range)?;
+    let bytes = decode_hex(hex)?;
+
+    if bytes.len() != len {
+        return None;
+    }
+
+    for (n, byte) in bytes.into_iter().enumerate() {
+        vm.write_memory(Address::new(addr) + (n as u16), byte.into())
+            .ok()?;
+    }
+
+    Some(())
+}
+
+fn ok_or_error(result: Option<()>) -> String {
+    match result {
+        Some(()) => "OK".to_string(),
+        None => "E01".to_string(),
+    }
+}
+
+/// Returns the sum of the bytes modulo 256.
+///
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|n| u8::from_str_radix(hex.get(n..n + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{TestVm, VmBuilder};
+    use super::super::Quirks;
+    use super::*;
+    use std::thread;
+
+    /// Serves a vm running the program on another thread and returns the
+    /// client side of the connection.
+    ///
+    fn connect(program: &'static [u8]) -> (TcpStream, thread::JoinHandle<TestVm>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let mut vm = VmBuilder::new()
+                .quirks(Quirks::cosmac_vip())
+                .program(program)
+                .build();
+
+            let (stream, _) = listener.accept().unwrap();
+            GdbStub::new(stream, 600).serve(&mut vm).unwrap();
+
+            vm
+        });
+
+        (TcpStream::connect(addr).unwrap(), server)
+    }
+
+    /// Sends a packet and returns the reply, acknowledging it.
+    ///
+    fn request(client: &mut TcpStream, packet: &str) -> String {
+        send(client, packet);
+
+        let mut byte = [0];
+        client.read_exact(&mut byte).unwrap();
+        assert_eq!(byte[0], b'+');
+
+        reply(client)
+    }
+
+    /// Sends a packet without waiting for anything.
+    ///
+    fn send(client: &mut TcpStream, packet: &str) {
+        let sum = checksum(packet.as_bytes());
+        write!(client, "${}#{:02x}", packet, sum).unwrap();
+    }
+
+    /// Reads a reply, acknowledging it.
+    ///
+    fn reply(client: &mut TcpStream) -> String {
+        let mut byte = [0];
+        client.read_exact(&mut byte).unwrap();
+        assert_eq!(byte[0], b'$');
+
+        let mut reply = Vec::new();
+        loop {
+            client.read_exact(&mut byte).unwrap();
+            if byte[0] == b'#' {
+                break;
+            }
+            reply.push(byte[0]);
+        }
+
+        let mut sum = [0; 2];
+        client.read_exact(&mut sum).unwrap();
+        client.write_all(b"+").unwrap();
+
+        String::from_utf8(reply).unwrap()
+    }
+
+    #[test]
+    fn reads_and_writes_registers_and_memory() {
+        let (mut client, server) = connect(&[0x6A, 0x05, 0x12, 0x02]);
+
+        assert_eq!(request(&mut client, "?"), "S05");
+        assert_eq!(request(&mut client, "p11"), "0200");
+        assert_eq!(request(&mut client, "m200,4"), "6a051202");
+
+        assert_eq!(request(&mut client, "M300,2:abcd"), "OK");
+        assert_eq!(request(&mut client, "m300,2"), "abcd");
+
+        assert_eq!(request(&mut client, "P3=2a"), "OK");
+        assert_eq!(request(&mut client, "P10=0300"), "OK");
+        assert_eq!(request(&mut client, "P12=05"), "E01");
+
+        let registers = request(&mut client, "g");
+        assert_eq!(registers.len(), 2 * 23);
+        assert_eq!(&registers[6..8], "2a");
+        assert_eq!(&registers[32..42], "0300020000");
+
+        request(&mut client, "D");
+        let vm = server.join().unwrap();
+        assert_eq!(vm.registers.get_v(Register::V3), 0x2A.into());
+        assert_eq!(vm.memory.get(Address::new(0x301)).unwrap(), 0xCD.into());
+    }
+
+    #[test]
+    fn continues_to_breakpoints_and_steps() {
+        // 200: LD V0, 1, 202: LD V1, 2, 204: LD V2, 3, 206: JP 206
+        let (mut client, server) = connect(&[0x60, 0x01, 0x61, 0x02, 0x62, 0x03, 0x12, 0x06]);
+
+        assert_eq!(request(&mut client, "Z0,204,2"), "OK");
+        assert_eq!(request(&mut client, "c"), "S05");
+        assert_eq!(request(&mut client, "p11"), "0204");
+
+        assert_eq!(request(&mut client, "s"), "S05");
+        assert_eq!(request(&mut client, "p11"), "0206");
+
+        assert_eq!(request(&mut client, "Z2,300,1"), "OK");
+        assert_eq!(request(&mut client, "M200,2:a300"), "OK");
+        assert_eq!(request(&mut client, "M202,2:f033"), "OK");
+        assert_eq!(request(&mut client, "c200"), "T05watch:300;");
+        assert_eq!(request(&mut client, "p11"), "0204");
+
+        client.write_all(b"$k#6b").unwrap();
+        let vm = server.join().unwrap();
+        assert_eq!(vm.memory.get(Address::new(0x302)).unwrap(), 1.into());
+    }
+
+    #[test]
+    fn packets_sent_while_running_are_kept() {
+        // 200: JP 200
+        let (mut client, server) = connect(&[0x12, 0x00]);
+
+        // Without acks the next packet can follow the continue right away.
+        assert_eq!(request(&mut client, "QStartNoAckMode"), "OK");
+        send(&mut client, "c");
+        send(&mut client, "p11");
+        client.write_all(&[INTERRUPT]).unwrap();
+
+        assert_eq!(reply(&mut client), "S02");
+        assert_eq!(reply(&mut client), "0200");
+
+        send(&mut client, "k");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn reports_when_the_program_exits() {
+        let (mut client, server) = connect(&[0x00, 0xFD]);
+
+        assert_eq!(request(&mut client, "c"), "W00");
+
+        drop(client);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn serves_the_register_layout() {
+        let xml = query("Xfer:features:read:target.xml:0,fff");
+
+        assert!(xml.starts_with("l<?xml"));
+        assert_eq!(xml.matches("<reg ").count(), REGISTER_SIZES.len());
+
+        assert!(query("Xfer:features:read:target.xml:0,10").starts_with('m'));
+    }
+}
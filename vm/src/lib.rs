@@ -1,18 +1,50 @@
 extern crate data;
 extern crate instruction;
 extern crate model;
-extern crate rand;
 
+mod clock;
+mod compiled;
+mod debugger;
+mod decode_cache;
+mod gdb;
+mod lockstep;
+mod movie;
+mod quirks;
+mod reader;
+mod rewind;
+mod save_state;
+#[cfg(test)]
+mod test_util;
+mod threaded;
+mod timing;
+mod trace;
 mod vm_state;
 
+pub use clock::{
+    cycles_for_frame, Clock, RealTime, TimeSource, VirtualTime, FRAMES_PER_SECOND, MAX_CATCH_UP,
+};
+pub use compiled::CompiledProgram;
+pub use debugger::{Access, Debugger, StopReason, Watch};
+pub use gdb::GdbStub;
+pub use lockstep::{Lockstep, LockstepError};
+pub use movie::{checksum, Movie, MoviePlayer, MovieRecorder, MOVIE_VERSION};
+pub use quirks::{LoadStoreQuirk, Quirks};
+pub use rewind::Rewind;
+pub use save_state::SAVE_STATE_VERSION;
+pub use threaded::{Engine, Handler};
+pub use timing::{Timing, VIP_CYCLES_PER_SECOND, VIP_FRAME_CYCLES, VIP_INTERRUPT_CYCLES};
+pub use trace::{diff_traces, Divergence, TraceEntry, Tracer};
+pub use vm_state::VMState;
+
 use data::{Address, Byte, Nibble};
+use decode_cache::{DecodeCache, Decoded};
 use instruction::Instruction;
 use model::{
-    Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter, Chip8RegisterBank,
-    Chip8VirtualMachine, Register,
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank, Chip8VirtualMachine, InputEvent, Register,
 };
-use rand::Rng;
-use vm_state::VMState;
+use threaded::BlockCache;
+use timing::KEY_POLL_CYCLES;
 
 /// System fonts in byte form.
 ///
@@ -35,6 +67,43 @@ pub const FONTS: [u8; 80] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
 
+/// SUPER-CHIP high resolution fonts in byte form. Each digit is 8x10.
+///
+pub const BIG_FONTS: [u8; 160] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+    0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, // F
+];
+
+/// The address the high resolution fonts are loaded at, right after FONTS.
+///
+const BIG_FONTS_ADDRESS: u16 = FONTS.len() as u16;
+
+/// The number of RPL user flags available to `Fx75` and `Fx85`.
+///
+const RPL_FLAGS: usize = 16;
+
+/// The number of bytes in an XO-CHIP audio pattern (128 1-bit samples).
+///
+pub const AUDIO_PATTERN_SIZE: usize = 16;
+
+/// The initial XO-CHIP pitch, which plays the pattern back at 4000 Hz.
+///
+const DEFAULT_PITCH: u8 = 64;
+
 /// An implementation of the Chip8 virtual machine.
 ///
 pub struct VirtualMachine<
@@ -43,57 +112,202 @@ pub struct VirtualMachine<
     R: Chip8RegisterBank,
     FB: Chip8FrameBuffer,
     KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
 > {
-    pub memory: M,
+    /// Read through `memory`, written through `write_memory` so the decoded
+    /// instructions stay in step with it.
+    memory: M,
+
     pub pc: PC,
     pub registers: R,
     pub framebuffer: FB,
     pub keyboard: KB,
+    pub random: RNG,
+    pub audio: AU,
     pub state: VMState,
+    pub quirks: Quirks,
     pub delay_timer: u8,
     pub sound_timer: u8,
+    pub rpl_flags: [Byte; RPL_FLAGS],
+    /// The XO-CHIP audio pattern, None until `F002` loads one.
+    pub audio_pattern: Option<[Byte; AUDIO_PATTERN_SIZE]>,
+    pub pitch: Byte,
+    pub timing: Timing,
+    pub engine: Engine,
+
+    /// The raw words of the last instruction fetched.
+    opcode: (u16, u16),
+
+    /// The instructions decoded so far, dropped when their memory is written.
+    decoded: DecodeCache,
+
+    /// The blocks compiled by the threaded engine.
+    blocks: BlockCache<Self>,
+
+    /// Whether the audio was last told to start or stop.
+    buzzing: bool,
+
+    /// The number of instructions executed and frames completed.
+    cycles: u64,
+    frames: u64,
+
+    /// The machine cycles elapsed in the current frame, with VIP timing.
+    frame_cycles: u32,
+
+    /// Input events waiting for their time, in the order they were queued.
+    input: Vec<InputEvent>,
+
+    /// Sees every executed instruction when set.
+    tracer: Option<Tracer>,
 }
 
-impl<M, PC, R, FB, KB> VirtualMachine<M, PC, R, FB, KB>
+impl<M, PC, R, FB, KB, RNG, AU> VirtualMachine<M, PC, R, FB, KB, RNG, AU>
 where
     M: Chip8Memory,
     PC: Chip8ProgramCounter,
     R: Chip8RegisterBank,
     FB: Chip8FrameBuffer,
     KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
 {
     /// Constructs a new VirtualMachine
     ///
-    pub fn new(memory: M, pc: PC, registers: R, framebuffer: FB, keyboard: KB) -> Self {
+    ///### Arguments
+    ///
+    ///- **quirks** : The behaviour of the contested instructions.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        memory: M,
+        pc: PC,
+        registers: R,
+        framebuffer: FB,
+        keyboard: KB,
+        random: RNG,
+        audio: AU,
+        quirks: Quirks,
+    ) -> Self {
+        let size = memory.size();
+
         let mut vm = VirtualMachine {
             memory,
             pc,
             registers,
             framebuffer,
             keyboard,
+            random,
+            audio,
             state: VMState::Initializing, // TODO: What should be the initial state?
+            quirks,
             delay_timer: 0,
             sound_timer: 0,
+            rpl_flags: [0.into(); RPL_FLAGS],
+            audio_pattern: None,
+            pitch: DEFAULT_PITCH.into(),
+            timing: Timing::default(),
+            engine: Engine::default(),
+            opcode: (0, 0),
+            decoded: DecodeCache::new(size),
+            blocks: BlockCache::new(size),
+            buzzing: false,
+            cycles: 0,
+            frames: 0,
+            frame_cycles: 0,
+            input: Vec::new(),
+            tracer: None,
         };
 
-        for i in 0..FONTS.len() {
-            let byte = FONTS[i];
+        for (i, byte) in FONTS.iter().chain(BIG_FONTS.iter()).enumerate() {
             let addr = Address::new(i as u16);
 
-            vm.memory.set(addr, byte.into());
+            vm.memory
+                .set(addr, (*byte).into())
+                .expect("the memory is too small to hold the fonts");
         }
 
         vm
     }
 
+    /// Reads the raw 16 bit word stored at the address.
+    ///
+    fn get_word(&self, addr: Address) -> Result<u16, Chip8Error> {
+        let left: u8 = self.memory.get(addr)?.into();
+        let right: u8 = self.memory.get(addr + 1u16)?.into();
+
+        Ok((left as u16) << 8 | (right as u16))
+    }
+
+    /// Reads the raw words of the instruction at the program counter.
+    ///
+    fn fetch(&self) -> Result<(u16, u16), Chip8Error> {
+        self.fetch_at(self.pc.current())
+    }
+
+    /// Reads the raw words of the instruction at the address.
+    ///
+    /// Only F000 NNNN needs the following word, it is 0 for everything else.
+    ///
+    fn fetch_at(&self, addr: Address) -> Result<(u16, u16), Chip8Error> {
+        let raw = self.get_word(addr)?;
+
+        if raw == 0xF000 {
+            Ok((raw, self.get_word(addr + 2u16)?))
+        } else {
+            Ok((raw, 0))
+        }
+    }
+
     /// Gets the next instruction.
     ///
-    fn get_instr(&self) -> Instruction {
-        let pc_addr = self.pc.current();
-        let left: u8 = self.memory.get(pc_addr).into();
-        let right: u8 = self.memory.get(pc_addr + (1 as u16)).into();
+    fn get_instr(&self) -> Result<Instruction, Chip8Error> {
+        if let Some((_, instruction)) = self.decoded.get(self.pc.current()) {
+            return Ok(instruction);
+        }
+
+        let (raw, next) = self.fetch()?;
+
+        Ok(Instruction::from_words(raw, next))
+    }
+
+    /// Gets the raw words and the instruction at the program counter,
+    /// decoding them only the first time the address is executed.
+    ///
+    fn fetch_decoded(&mut self) -> Result<Decoded, Chip8Error> {
+        let pc = self.pc.current();
+
+        if let Some(decoded) = self.decoded.get(pc) {
+            return Ok(decoded);
+        }
+
+        let (raw, next) = self.fetch()?;
+        let decoded = ((raw, next), Instruction::from_words(raw, next));
+        self.decoded.insert(pc, decoded);
+
+        Ok(decoded)
+    }
+
+    /// Returns the memory.
+    ///
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    /// Writes a byte to memory, dropping any decoded instruction it is part
+    /// of.
+    ///
+    ///### Arguments
+    ///
+    ///- **address** : The address being written.
+    ///- **byte**    : The new value.
+    ///
+    pub fn write_memory(&mut self, address: Address, byte: Byte) -> Result<(), Chip8Error> {
+        self.memory.set(address, byte)?;
+        self.decoded.invalidate(address);
+        self.blocks.invalidate(address);
 
-        Instruction::new((left as u16) << 8 | (right as u16))
+        Ok(())
     }
 
     /// Sets the carry register.
@@ -137,19 +351,129 @@ where
         (self.get_reg(rx), self.get_reg(ry))
     }
 
-    /// Increments the current address by 2.
+    /// Returns the value to be shifted by `8xy6` and `8xyE`.
     ///
-    fn inc_pc(&mut self) {
-        self.pc.inc_by(2.into());
+    ///### Arguments
+    ///
+    ///- **vx** : The destination register.
+    ///- **vy** : The source register, used when the shift quirk is enabled.
+    ///
+    fn get_shift_operand(&self, vx: Register, vy: Register) -> Byte {
+        if self.quirks.shift_uses_vy {
+            self.get_reg(vy)
+        } else {
+            self.get_reg(vx)
+        }
+    }
+
+    /// Resets VF after a logic instruction if the quirk asks for it.
+    ///
+    fn reset_logic_flag(&mut self) {
+        if self.quirks.logic_resets_vf {
+            self.set_carry(0);
+        }
+    }
+
+    /// Updates I after `Fx55` or `Fx65` according to the quirks.
+    ///
+    ///### Arguments
+    ///
+    ///- **vx** : The last register that was copied.
+    ///
+    fn advance_i_after_load_store(&mut self, vx: Register) {
+        let i = self.registers.get_i();
+
+        match self.quirks.load_store {
+            LoadStoreQuirk::IncrementByXPlusOne => self.registers.set_i(i + (vx as u16) + 1u16),
+            LoadStoreQuirk::IncrementByX => self.registers.set_i(i + (vx as u16)),
+            LoadStoreQuirk::Unchanged => {}
+        }
+    }
+
+    /// Moves the program counter past the current instruction and the one
+    /// following it.
+    ///
+    /// The following instruction may be the 4 byte long `F000 NNNN`.
+    ///
+    fn skip_next(&mut self) -> Result<(), Chip8Error> {
+        // The next instruction is read before moving, so a failed read
+        // leaves the program counter at the skip.
+        let (raw, next) = self.fetch_at(self.pc.current() + 2u16)?;
+        let size = 2 + Instruction::from_words(raw, next).size();
+
+        self.pc.inc_by((size as u8).into());
+
+        Ok(())
+    }
+
+    /// Returns the registers from Vx to Vy, walking backwards if x > y.
+    ///
+    fn register_range(vx: Register, vy: Register) -> Vec<Register> {
+        let (x, y) = (vx as u8, vy as u8);
+
+        let range: Vec<u8> = if x <= y {
+            (x..=y).collect()
+        } else {
+            (y..=x).rev().collect()
+        };
+
+        range
+            .into_iter()
+            .filter_map(|r| Register::new(r).ok())
+            .collect()
     }
 
     /// Returns a flag indicating if the vm is waiting for a key press.
     ///
     fn is_waiting(&self) -> bool {
-        if let VMState::WaitingForKey(Some(n)) = self.state {
-            !self.keyboard.is_pressed(n)
-        } else {
-            false
+        matches!(self.state, VMState::WaitingForKey(_, _))
+    }
+
+    /// Applies the queued input events that are due.
+    ///
+    fn apply_input(&mut self) {
+        if self.input.is_empty() {
+            return;
+        }
+
+        let (cycles, frames) = (self.cycles, self.frames);
+        let (due, pending) = self
+            .input
+            .drain(..)
+            .partition(|event| event.is_due(cycles, frames));
+
+        self.input = pending;
+
+        for event in due {
+            let key = Nibble::from(event.key);
+
+            if event.pressed {
+                self.keyboard.press(key);
+            } else {
+                self.keyboard.release(key);
+            }
+        }
+    }
+
+    /// Checks the keyboard for the key Fx0A is waiting for.
+    ///
+    /// A key has to be pressed and then released, as on the COSMAC VIP. The
+    /// key is stored into the register once it is released.
+    ///
+    fn poll_key(&mut self) {
+        match self.state {
+            VMState::WaitingForKey(vx, None) => {
+                let pressed = (0..16u8)
+                    .map(Nibble::new)
+                    .find(|key| self.keyboard.is_pressed(*key));
+
+                self.state = VMState::WaitingForKey(vx, pressed);
+            }
+            VMState::WaitingForKey(vx, Some(key)) if !self.keyboard.is_pressed(key) => {
+                self.registers.set_v(vx, key.get_raw().into());
+                self.state = VMState::Initializing;
+            }
+            _ => {}
         }
     }
 
@@ -159,26 +483,135 @@ where
         self.state == VMState::Paused
     }
 
-    /// Decrements delay and sound timers.
+    /// Returns a flag indicating if the program has exited.
+    ///
+    fn is_exited(&self) -> bool {
+        self.state == VMState::Exited
+    }
+
+    /// Starts or stops the audio when the sound timer started or ran out.
+    ///
+    pub(crate) fn update_audio(&mut self) {
+        let buzzing = self.sound_timer > 0;
+
+        if buzzing != self.buzzing {
+            if buzzing {
+                self.audio.start();
+            } else {
+                self.audio.stop();
+            }
+
+            self.buzzing = buzzing;
+        }
+    }
+
+    /// Hands the audio pattern and pitch to the audio.
+    ///
+    pub(crate) fn update_pattern(&mut self) {
+        match &self.audio_pattern {
+            Some(pattern) => self.audio.set_pattern(pattern, self.pitch),
+            None => self.audio.clear_pattern(),
+        }
+    }
+
+    /// Returns the cycles left to execute in a frame of the given length.
+    ///
+    /// With VIP timing the display interrupt and the overrun of the last
+    /// frame come out of the frame's budget.
+    ///
+    fn frame_budget(&self, cycles: usize) -> usize {
+        match self.timing {
+            Timing::Instructions => cycles,
+            Timing::CosmacVip => cycles.saturating_sub(self.frame_cycles as usize),
+        }
+    }
+
+    /// Executes the next instruction, or checks for the key Fx0A is waiting
+    /// for. Returns the machine cycles taken with VIP timing, 1 otherwise, or
+    /// None if the vm is paused or has exited.
+    ///
+    fn step(&mut self) -> Result<Option<u32>, Chip8Error> {
+        self.apply_input();
+
+        if self.is_paused() || self.is_exited() {
+            return Ok(None);
+        }
+
+        // Every check for the key takes up a cycle, so queued input lands
+        // on the same cycle whatever the batch sizes are.
+        if self.is_waiting() {
+            self.poll_key();
+            self.cycles += 1;
+
+            if self.timing == Timing::Instructions {
+                return Ok(Some(1));
+            }
+
+            self.frame_cycles += KEY_POLL_CYCLES;
+
+            return Ok(Some(KEY_POLL_CYCLES));
+        }
+
+        let (opcode, instruction) = self.fetch_decoded()?;
+
+        self.retire(opcode, instruction, Self::interpret_instruction)
+            .map(Some)
+    }
+
+    /// Executes an instruction that has been fetched: traces it, performs it
+    /// with the handler, moves the program counter on and counts the cycles.
+    /// Returns the machine cycles taken with VIP timing, 1 otherwise.
+    ///
+    ///### Arguments
+    ///
+    ///- **opcode**      : The raw words of the instruction.
+    ///- **instruction** : The decoded instruction.
+    ///- **handler**     : Performs the instruction, like `interpret_instruction`.
     ///
-    fn decrement_timers(&mut self) {
-        if self.delay_timer > 0 {
-            self.delay_timer -= 1;
+    fn retire(
+        &mut self,
+        opcode: (u16, u16),
+        instruction: Instruction,
+        handler: Handler<Self>,
+    ) -> Result<u32, Chip8Error> {
+        let vip = self.timing == Timing::CosmacVip;
+
+        self.opcode = opcode;
+        self.state = VMState::Executing(instruction);
+        self.trace(instruction);
+
+        // The cost depends on the registers before the instruction.
+        let mut cost = if vip { self.vip_cycles(instruction) } else { 1 };
+
+        if handler(self, instruction)? {
+            if vip {
+                cost += Self::vip_skip_cycles(instruction);
+            }
+        } else {
+            self.pc.inc_by((instruction.size() as u8).into());
         }
 
-        if self.sound_timer > 0 {
-            self.sound_timer -= 1;
+        self.cycles += 1;
+
+        if vip {
+            self.frame_cycles += cost;
         }
+
+        Ok(cost)
     }
 
     /// Performs the instruction. Returns a flag indicating if the program counter
     /// was updated.
     ///
+    /// Only performs it: the program counter is not moved past it and the
+    /// cycle is not counted. Compiled code uses it as the handler of the
+    /// instructions it does not translate, see `execute_decoded`.
+    ///
     ///### Arguments
     ///
     ///- **instruction** : The Instruction being executed.
     ///
-    fn interpret_instruction(&mut self, instruction: Instruction) -> bool {
+    pub fn interpret_instruction(&mut self, instruction: Instruction) -> Result<bool, Chip8Error> {
         use Instruction::*;
 
         match instruction {
@@ -186,34 +619,64 @@ where
                 // This is purposely left unimplemented.
             }
 
-            Invalid(instr) => println!("{:#X} is not a valid instruction", instr),
+            Invalid(instr) => {
+                return Err(Chip8Error::InvalidOpcode(self.pc.current(), instr));
+            }
 
             Cls => {
                 self.framebuffer.clear();
             }
 
             Return => {
-                self.pc.rtrn();
+                self.pc.rtrn()?;
+            }
+
+            ScrollDown(n) => {
+                self.framebuffer.scroll_down(n.get_raw() as usize);
+            }
+
+            ScrollUp(n) => {
+                self.framebuffer.scroll_up(n.get_raw() as usize);
+            }
+
+            ScrollRight => {
+                self.framebuffer.scroll_right(4);
+            }
+
+            ScrollLeft => {
+                self.framebuffer.scroll_left(4);
+            }
+
+            Exit => {
+                self.state = VMState::Exited;
+                return Ok(true);
+            }
+
+            LowRes => {
+                self.framebuffer.set_high_res(false);
+            }
+
+            HighRes => {
+                self.framebuffer.set_high_res(true);
             }
 
             Jump(addr) => {
                 self.pc.set(addr);
-                return true;
+                return Ok(true);
             }
 
             Call(addr) => {
                 // See Jump comment.
-                self.pc.to_subroutine(addr);
-                return true;
+                self.pc.to_subroutine(addr)?;
+                return Ok(true);
             }
 
             SkipEqualByte(vx, byte) => {
                 let contents = self.get_reg(vx);
 
                 if contents == byte {
-                    self.inc_pc();
-                    self.inc_pc();
-                    return true;
+                    self.skip_next()?;
+                    return Ok(true);
                 }
             }
 
@@ -221,9 +684,8 @@ where
                 let contents = self.get_reg(vx);
 
                 if contents != byte {
-                    self.inc_pc();
-                    self.inc_pc();
-                    return true;
+                    self.skip_next()?;
+                    return Ok(true);
                 }
             }
 
@@ -231,9 +693,8 @@ where
                 let (x, y) = self.get_regs(vx, vy);
 
                 if x == y {
-                    self.inc_pc();
-                    self.inc_pc();
-                    return true;
+                    self.skip_next()?;
+                    return Ok(true);
                 }
             }
 
@@ -241,9 +702,8 @@ where
                 let (x, y) = self.get_regs(vx, vy);
 
                 if x != y {
-                    self.inc_pc();
-                    self.inc_pc();
-                    return true;
+                    self.skip_next()?;
+                    return Ok(true);
                 }
             }
 
@@ -256,92 +716,129 @@ where
             Or(vx, vy) => {
                 let (x, y) = self.get_regs(vx, vy);
 
-                self.set_reg(vx, x | y)
+                self.set_reg(vx, x | y);
+                self.reset_logic_flag();
             }
 
             And(vx, vy) => {
                 let (x, y) = self.get_regs(vx, vy);
 
                 self.set_reg(vx, x & y);
+                self.reset_logic_flag();
             }
 
             XOr(vx, vy) => {
                 let (x, y) = self.get_regs(vx, vy);
 
                 self.set_reg(vx, x ^ y);
+                self.reset_logic_flag();
             }
 
+            // The flag is set after the result, so it wins when Vx is VF.
             AddReg(vx, vy) => {
                 let (x, y) = self.get_regs(vx, vy);
+                let (sum, carry) = x.get_raw().overflowing_add(y.get_raw());
 
-                self.set_reg(vx, x.wrapping_add(y));
+                self.set_reg(vx, sum.into());
+                self.set_carry(carry as u8);
             }
 
             SubReg(vx, vy) => {
                 let (x, y) = self.get_regs(vx, vy);
 
-                if x > y {
-                    self.set_carry(1);
-                }
-
                 self.set_reg(vx, x.wrapping_sub(y));
+                self.set_carry((x >= y) as u8);
             }
 
-            ShiftRight(vx) => {
-                let x = self.get_reg(vx);
-
-                self.set_carry(x.get_lsb());
+            ShiftRight(vx, vy) => {
+                let x = self.get_shift_operand(vx, vy);
 
                 self.set_reg(vx, x >> 1);
+                self.set_carry(x.get_lsb());
             }
 
             ReverseSub(vx, vy) => {
                 let (x, y) = self.get_regs(vx, vy);
 
-                if y > x {
-                    self.set_carry(1);
-                } else {
-                    self.set_carry(0);
-                }
-
-                self.set_reg(vx, y - x);
+                self.set_reg(vx, y.wrapping_sub(x));
+                self.set_carry((y >= x) as u8);
             }
 
-            ShiftLeft(vx) => {
-                let x = self.get_reg(vx);
-
-                self.set_carry(x.get_msb());
+            ShiftLeft(vx, vy) => {
+                let x = self.get_shift_operand(vx, vy);
 
                 self.set_reg(vx, x << 1);
+                self.set_carry(x.get_msb());
             }
 
             LoadInstr(addr) => {
                 self.registers.set_i(addr);
             }
 
+            LoadLongInstr(addr) => {
+                self.registers.set_i(addr);
+            }
+
+            SelectPlanes(mask) => {
+                self.framebuffer.select_planes(mask.get_raw());
+            }
+
+            LoadAudioPattern => {
+                let i = self.registers.get_i();
+                let mut pattern = [0.into(); AUDIO_PATTERN_SIZE];
+
+                for (n, byte) in pattern.iter_mut().enumerate() {
+                    *byte = self.memory.get(i + (n as u16))?;
+                }
+
+                self.audio_pattern = Some(pattern);
+                self.update_pattern();
+            }
+
+            SetPitch(vx) => {
+                self.pitch = self.registers.get_v(vx);
+                self.update_pattern();
+            }
+
+            SaveRange(vx, vy) => {
+                let i = self.registers.get_i();
+
+                for (n, reg) in Self::register_range(vx, vy).into_iter().enumerate() {
+                    self.write_memory(i + (n as u16), self.registers.get_v(reg))?;
+                }
+            }
+
+            LoadRange(vx, vy) => {
+                let i = self.registers.get_i();
+
+                for (n, reg) in Self::register_range(vx, vy).into_iter().enumerate() {
+                    self.registers.set_v(reg, self.memory.get(i + (n as u16))?);
+                }
+            }
+
             Add(vx, byte) => {
                 let contents = self.get_reg(vx);
                 self.set_reg(vx, contents.wrapping_add(byte));
             }
 
             JumpPlus(addr) => {
-                let summand = self.get_reg(Register::V0);
+                // CHIP-48 misread Bnnn as Bxnn, jumping to xnn + Vx.
+                let offset = if self.quirks.jump_uses_vx {
+                    let x = (addr.get_raw() >> 8) as u8;
+                    Register::new(x & 0xF).unwrap_or(Register::V0)
+                } else {
+                    Register::V0
+                };
+
+                let summand = self.get_reg(offset);
                 let addr = addr + (summand.get_raw() as u16);
 
-                // NOTE: Need to double check the spec here.
-                // Not sure if we are jumping based on the current pc
-                // or what. Doesn't look to be the case though.
                 self.pc.set(addr);
+                return Ok(true);
             }
 
             Rand(vx, byte) => {
-                let mut rng = rand::thread_rng();
-
-                let n: Byte = {
-                    let b: u8 = rng.gen();
-
-                    b.into()
-                };
+                let n = self.random.next_byte();
 
                 self.set_reg(vx, n & byte);
             }
@@ -350,10 +847,25 @@ where
                 let (x, y) = self.get_regs(vx, vy);
 
                 let i = self.registers.get_i();
-                let mem_slice = self.memory.get_slice(i, nibble);
+                let clip = self.quirks.clip_sprites;
+
+                // Each selected plane reads its own copy of the sprite data.
+                let planes = self.framebuffer.selected_planes().count_ones() as usize;
+
+                let collision = if nibble.get_raw() == 0 {
+                    // SUPER-CHIP draws a 16x16 sprite for Dxy0.
+                    let mem_slice = self.memory.get_slice(i, 32 * planes)?;
+
+                    self.framebuffer.draw_large(x, y, mem_slice, clip)
+                } else {
+                    let length = nibble.get_raw() as usize * planes;
+                    let mem_slice = self.memory.get_slice(i, length)?;
+
+                    self.framebuffer.draw(x, y, mem_slice, clip)
+                };
 
                 // Check if there was a collision
-                if self.framebuffer.draw(x, y, mem_slice) {
+                if collision {
                     self.set_carry(1);
                 } else {
                     self.set_carry(0);
@@ -365,9 +877,8 @@ where
                 let n = Nibble::new(key.get_raw());
 
                 if self.keyboard.is_pressed(n) {
-                    self.inc_pc();
-                    self.inc_pc();
-                    return true;
+                    self.skip_next()?;
+                    return Ok(true);
                 };
             }
 
@@ -376,9 +887,8 @@ where
                 let n: Nibble = key.get_raw().into();
 
                 if !self.keyboard.is_pressed(n) {
-                    self.inc_pc();
-                    self.inc_pc();
-                    return true;
+                    self.skip_next()?;
+                    return Ok(true);
                 }
             }
 
@@ -387,10 +897,7 @@ where
             }
 
             WaitForKey(vx) => {
-                let digit = self.registers.get_v(vx).get_raw();
-                let nib = Nibble::from(digit);
-
-                self.state = VMState::WaitingForKey(Some(nib));
+                self.state = VMState::WaitingForKey(vx, None);
             }
 
             SetDelayTimer(vx) => {
@@ -399,6 +906,7 @@ where
 
             SetSoundTimer(vx) => {
                 self.sound_timer = self.registers.get_v(vx).get_raw();
+                self.update_audio();
             }
 
             IncrementAddress(vx) => {
@@ -424,14 +932,25 @@ where
                 self.registers.set_i(address)
             }
 
+            LoadBigSpriteAddress(vx) => {
+                // The high resolution fonts follow the regular ones and
+                // are 10 bytes each.
+                let byte = self.registers.get_v(vx);
+                let digit: u16 = Nibble::new(byte.into()).get_raw().into();
+
+                let address = Address::new(BIG_FONTS_ADDRESS + digit * 10);
+
+                self.registers.set_i(address)
+            }
+
             LoadBCD(vx) => {
                 let x = self.registers.get_v(vx);
                 let i = self.registers.get_i();
                 let (h, t, o) = x.get_bcd_rep();
 
-                self.memory.set(i, h.into());
-                self.memory.set(i + 1u16, t.into());
-                self.memory.set(i + 2u16, o.into());
+                self.write_memory(i, h.into())?;
+                self.write_memory(i + 1u16, t.into())?;
+                self.write_memory(i + 2u16, o.into())?;
             }
 
             CopyToRam(vx) => {
@@ -442,83 +961,933 @@ where
                     let value = self.registers.get_v(reg);
                     let addr = i + (reg as u16);
 
-                    self.memory.set(addr, value);
+                    self.write_memory(addr, value)?;
                 }
 
-                self.registers.set_i(i + (vx as u16) + (1 as u16));
+                self.advance_i_after_load_store(vx);
             }
 
             CopyToRegisters(vx) => {
                 let i = self.registers.get_i();
 
                 for reg in Register::iter_to(vx) {
-                    let value = self.memory.get(i + (reg as u16));
+                    let value = self.memory.get(i + (reg as u16))?;
 
                     self.registers.set_v(reg, value);
                 }
 
-                self.registers.set_i(i + (vx as u16) + (1 as u16));
+                self.advance_i_after_load_store(vx);
+            }
+
+            SaveFlags(vx) => {
+                for reg in Register::iter_to(vx) {
+                    self.rpl_flags[reg as usize] = self.registers.get_v(reg);
+                }
+            }
+
+            LoadFlags(vx) => {
+                for reg in Register::iter_to(vx) {
+                    self.registers.set_v(reg, self.rpl_flags[reg as usize]);
+                }
             }
         };
 
-        false
+        Ok(false)
     }
 }
 
-impl<M, PC, R, FB, KB> Chip8VirtualMachine for VirtualMachine<M, PC, R, FB, KB>
+impl<M, PC, R, FB, KB, RNG, AU> Chip8VirtualMachine for VirtualMachine<M, PC, R, FB, KB, RNG, AU>
 where
     M: Chip8Memory,
     PC: Chip8ProgramCounter,
     R: Chip8RegisterBank,
     FB: Chip8FrameBuffer,
     KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
 {
     fn get_framebuffer(&self) -> &dyn Chip8FrameBuffer {
         &self.framebuffer
     }
 
-    fn load_rom(&mut self, data: &[u8]) {
-        self.state = VMState::LoadingROM;
-
+    fn load_rom(&mut self, data: &[u8]) -> Result<(), Chip8Error> {
         let start_addr = self.pc.current();
+        let capacity = self
+            .memory
+            .size()
+            .saturating_sub(start_addr.get_raw() as usize);
+
+        if data.len() > capacity {
+            return Err(Chip8Error::RomTooLarge {
+                size: data.len(),
+                capacity,
+            });
+        }
+
+        self.state = VMState::LoadingROM;
 
-        for i in 0..data.len() {
+        for (i, byte) in data.iter().enumerate() {
             let addr = start_addr + (i as u16);
-            let data: Byte = data[i].into();
 
-            self.memory.set(addr, data);
+            self.write_memory(addr, (*byte).into())?;
         }
 
         self.state = VMState::Initializing;
+
+        Ok(())
     }
 
     fn press_key(&mut self, key: u8) {
         self.keyboard.press(Nibble::from(key));
     }
 
-    fn execute(&mut self) {
-        self.execute_cycles(1);
+    fn release_key(&mut self, key: u8) {
+        self.keyboard.release(Nibble::from(key));
     }
 
-    fn execute_cycles(&mut self, cycles: usize) {
-        for _ in 0..cycles {
-            self.decrement_timers();
+    fn execute(&mut self) -> Result<(), Chip8Error> {
+        self.execute_cycles(1)
+    }
+
+    fn execute_cycles(&mut self, cycles: usize) -> Result<(), Chip8Error> {
+        // Cycles are instructions, or machine cycles with VIP timing.
+        let mut spent = 0;
+
+        while spent < cycles {
+            let taken = match self.engine {
+                Engine::Interpreter => self.step()?,
+                Engine::Threaded => self.run_block(cycles - spent)?,
+            };
 
-            if self.is_waiting() || self.is_paused() {
-                return;
+            match taken {
+                Some(cost) => spent += cost as usize,
+                None => break,
             }
+        }
 
-            let instruction = self.get_instr();
+        Ok(())
+    }
 
-            self.state = VMState::Executing(instruction);
+    fn run_frame(&mut self, cycles: usize) -> Result<(), Chip8Error> {
+        self.execute_cycles(self.frame_budget(cycles))?;
+        self.tick_timers();
 
-            if !self.interpret_instruction(instruction) {
-                self.inc_pc();
-            }
+        Ok(())
+    }
+
+    fn tick_timers(&mut self) {
+        self.delay_timer = self.delay_timer.saturating_sub(1);
+        self.sound_timer = self.sound_timer.saturating_sub(1);
+        self.update_audio();
+        self.audio.end_frame();
+        self.random.end_frame();
+
+        if self.timing == Timing::CosmacVip {
+            self.frame_cycles =
+                self.frame_cycles.saturating_sub(VIP_FRAME_CYCLES) + VIP_INTERRUPT_CYCLES;
         }
+
+        self.frames += 1;
+    }
+
+    fn queue_input(&mut self, event: InputEvent) {
+        self.input.push(event);
+    }
+
+    fn clear_input(&mut self) {
+        self.input.clear();
+        self.keyboard.clear();
+    }
+
+    fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    fn frames(&self) -> u64 {
+        self.frames
     }
 
     fn release_keys(&mut self) {
         self.keyboard.clear();
     }
+
+    fn snapshot(&self) -> Vec<u8> {
+        self.write_state()
+    }
+
+    fn restore(&mut self, snapshot: &[u8]) -> Result<(), Chip8Error> {
+        self.read_state(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate memory;
+    extern crate random;
+
+    use model::InputTime;
+    use random::{ScriptedRandom, VipRandom};
+    use test_util::{TestVm, VmBuilder};
+
+    /// The bytes returned by the random source, in order.
+    const SCRIPT: [u8; 3] = [0xA5, 0x3C, 0xFF];
+
+    /// Records what the machine told the audio.
+    ///
+    #[derive(Default)]
+    struct Buzz {
+        on: bool,
+        starts: usize,
+        frames: usize,
+        pattern: Option<([Byte; AUDIO_PATTERN_SIZE], Byte)>,
+    }
+
+    impl Chip8Audio for Buzz {
+        fn start(&mut self) {
+            self.on = true;
+            self.starts += 1;
+        }
+
+        fn stop(&mut self) {
+            self.on = false;
+        }
+
+        fn end_frame(&mut self) {
+            self.frames += 1;
+        }
+
+        fn set_pattern(&mut self, pattern: &[Byte; AUDIO_PATTERN_SIZE], pitch: Byte) {
+            self.pattern = Some((*pattern, pitch));
+        }
+
+        fn clear_pattern(&mut self) {
+            self.pattern = None;
+        }
+    }
+
+    type VM = TestVm<ScriptedRandom, Buzz>;
+
+    /// Creates a vm with the program loaded at 0x200.
+    ///
+    fn get_vm(quirks: Quirks, program: &[u8]) -> VM {
+        VmBuilder::new()
+            .quirks(quirks)
+            .program(program)
+            .random(ScriptedRandom::new(SCRIPT.to_vec()))
+            .audio(Buzz::default())
+            .build()
+    }
+
+    #[test]
+    fn shift_uses_vy_when_quirk_enabled() {
+        // V1 = 0x03, V0 >>= V1
+        let mut vm = get_vm(Quirks::cosmac_vip(), &[0x61, 0x03, 0x80, 0x16]);
+
+        vm.execute_cycles(2).unwrap();
+
+        assert_eq!(vm.registers.get_v(Register::V0), 0x01.into());
+        assert_eq!(vm.registers.get_v(Register::VF), 0x01.into());
+    }
+
+    #[test]
+    fn shift_ignores_vy_when_quirk_disabled() {
+        // V0 = 0x80, V1 = 0x03, V0 <<= V1
+        let mut vm = get_vm(Quirks::super_chip(), &[0x60, 0x80, 0x61, 0x03, 0x80, 0x1E]);
+
+        vm.execute_cycles(3).unwrap();
+
+        assert_eq!(vm.registers.get_v(Register::V0), 0x00.into());
+        assert_eq!(vm.registers.get_v(Register::VF), 0x01.into());
+    }
+
+    #[test]
+    fn load_store_updates_i_according_to_quirk() {
+        // I = 0x300, save V0 - V2
+        let program = [0xA3, 0x00, 0xF2, 0x55];
+
+        let expected = [
+            (Quirks::cosmac_vip(), 0x303),
+            (Quirks::chip48(), 0x302),
+            (Quirks::super_chip(), 0x300),
+        ];
+
+        for (quirks, i) in expected.iter() {
+            let mut vm = get_vm(*quirks, &program);
+
+            vm.execute_cycles(2).unwrap();
+
+            assert_eq!(vm.registers.get_i(), Address::new(*i));
+        }
+    }
+
+    #[test]
+    fn jump_plus_uses_vx_when_quirk_enabled() {
+        // V0 = 0x10, V3 = 0x20, jump 0x300 + V?
+        let program = [0x60, 0x10, 0x63, 0x20, 0xB3, 0x00];
+
+        let mut vm = get_vm(Quirks::cosmac_vip(), &program);
+        vm.execute_cycles(3).unwrap();
+        assert_eq!(vm.pc.current(), Address::new(0x310));
+
+        let mut vm = get_vm(Quirks::chip48(), &program);
+        vm.execute_cycles(3).unwrap();
+        assert_eq!(vm.pc.current(), Address::new(0x320));
+    }
+
+    #[test]
+    fn logic_resets_vf_when_quirk_enabled() {
+        // VF = 0x05, V0 |= V1
+        let program = [0x6F, 0x05, 0x80, 0x11];
+
+        let mut vm = get_vm(Quirks::cosmac_vip(), &program);
+        vm.execute_cycles(2).unwrap();
+        assert_eq!(vm.registers.get_v(Register::VF), 0x00.into());
+
+        let mut vm = get_vm(Quirks::modern(), &program);
+        vm.execute_cycles(2).unwrap();
+        assert_eq!(vm.registers.get_v(Register::VF), 0x05.into());
+    }
+
+    #[test]
+    fn arithmetic_sets_vf_after_the_result() {
+        // V0 = 0xFF, V1 = 0x01, V0 += V1, V2 = V1 - V1, V3 = V1 =- V0
+        let program = [
+            0x60, 0xFF, 0x61, 0x01, 0x80, 0x14, 0x82, 0x10, 0x82, 0x15, 0x83, 0x10, 0x83, 0x07,
+        ];
+
+        let mut vm = get_vm(Quirks::default(), &program);
+
+        vm.execute_cycles(3).unwrap();
+        assert_eq!(vm.registers.get_v(Register::V0), 0x00.into());
+        assert_eq!(vm.registers.get_v(Register::VF), 0x01.into());
+
+        // Equal operands do not borrow.
+        vm.execute_cycles(2).unwrap();
+        assert_eq!(vm.registers.get_v(Register::V2), 0x00.into());
+        assert_eq!(vm.registers.get_v(Register::VF), 0x01.into());
+
+        // 0 - 1 borrows and wraps.
+        vm.execute_cycles(2).unwrap();
+        assert_eq!(vm.registers.get_v(Register::V3), 0xFF.into());
+        assert_eq!(vm.registers.get_v(Register::VF), 0x00.into());
+
+        // VF as the destination holds the flag, not the result.
+        let mut vm = get_vm(Quirks::default(), &[0x6F, 0x05, 0x60, 0x03, 0x8F, 0x05]);
+        vm.execute_cycles(3).unwrap();
+        assert_eq!(vm.registers.get_v(Register::VF), 0x01.into());
+    }
+
+    #[test]
+    fn high_res_switches_the_framebuffer_mode() {
+        // HIGH, LOW
+        let mut vm = get_vm(Quirks::super_chip(), &[0x00, 0xFF, 0x00, 0xFE]);
+
+        vm.execute().unwrap();
+        assert_eq!(vm.framebuffer.width(), 128);
+
+        vm.execute().unwrap();
+        assert_eq!(vm.framebuffer.width(), 64);
+    }
+
+    #[test]
+    fn draw_with_zero_height_draws_a_16x16_sprite() {
+        // HIGH, I = 0x20A, DRW V0, V0, 0, EXIT, followed by the sprite data.
+        let mut program = vec![0x00, 0xFF, 0xA2, 0x0A, 0xD0, 0x00, 0x00, 0xFD, 0x00, 0x00];
+        program.extend(std::iter::repeat_n(0xFF, 32));
+
+        let mut vm = get_vm(Quirks::super_chip(), &program);
+
+        vm.execute_cycles(10).unwrap();
+
+        assert_eq!(vm.state, VMState::Exited);
+        assert_eq!(vm.pc.current(), Address::new(0x206));
+        assert!(vm.framebuffer.get_pixel(15, 15));
+        assert!(!vm.framebuffer.get_pixel(16, 15));
+        assert!(!vm.framebuffer.get_pixel(15, 16));
+    }
+
+    #[test]
+    fn flags_round_trip_through_rpl_storage() {
+        // V0 = 1, V1 = 2, save V1, V0 = 0, V1 = 0, load V1
+        let program = [
+            0x60, 0x01, 0x61, 0x02, 0xF1, 0x75, 0x60, 0x00, 0x61, 0x00, 0xF1, 0x85,
+        ];
+        let mut vm = get_vm(Quirks::super_chip(), &program);
+
+        vm.execute_cycles(6).unwrap();
+
+        assert_eq!(vm.registers.get_v(Register::V0), 0x01.into());
+        assert_eq!(vm.registers.get_v(Register::V1), 0x02.into());
+    }
+
+    #[test]
+    fn big_sprite_address_points_at_the_high_resolution_font() {
+        // V0 = 2, LD HF, V0
+        let mut vm = get_vm(Quirks::super_chip(), &[0x60, 0x02, 0xF0, 0x30]);
+
+        vm.execute_cycles(2).unwrap();
+
+        let i = vm.registers.get_i();
+        assert_eq!(vm.memory.get(i).unwrap(), BIG_FONTS[20].into());
+    }
+
+    /// Creates a vm with 64 KiB of memory and the program loaded at 0x200.
+    ///
+    fn get_xo_vm(program: &[u8]) -> VM {
+        VmBuilder::new()
+            .memory_size(memory::XO_CHIP_SIZE)
+            .quirks(Quirks::modern())
+            .program(program)
+            .random(ScriptedRandom::new(SCRIPT.to_vec()))
+            .audio(Buzz::default())
+            .build()
+    }
+
+    #[test]
+    fn long_load_sets_i_to_a_16_bit_address() {
+        // I = 0xABCD, V0 = 1
+        let mut vm = get_xo_vm(&[0xF0, 0x00, 0xAB, 0xCD, 0x60, 0x01]);
+
+        vm.execute_cycles(2).unwrap();
+
+        assert_eq!(vm.registers.get_i(), Address::new(0xABCD));
+        assert_eq!(vm.registers.get_v(Register::V0), 0x01.into());
+    }
+
+    #[test]
+    fn skips_step_over_long_loads() {
+        // SE V0, 0, I = 0xABCD, V1 = 1
+        let mut vm = get_xo_vm(&[0x30, 0x00, 0xF0, 0x00, 0xAB, 0xCD, 0x61, 0x01]);
+
+        vm.execute_cycles(2).unwrap();
+
+        assert_eq!(vm.registers.get_i(), Address::new(0));
+        assert_eq!(vm.registers.get_v(Register::V1), 0x01.into());
+    }
+
+    #[test]
+    fn save_and_load_range_walk_in_either_direction() {
+        // V1 = 1, V2 = 2, V3 = 3, I = 0x300, save V3 - V1, load V1 - V3
+        let program = [
+            0x61, 0x01, 0x62, 0x02, 0x63, 0x03, 0xA3, 0x00, 0x53, 0x12, 0x51, 0x33,
+        ];
+        let mut vm = get_xo_vm(&program);
+
+        vm.execute_cycles(6).unwrap();
+
+        assert_eq!(vm.memory.get(0x300.into()).unwrap(), 0x03.into());
+        assert_eq!(vm.memory.get(0x302.into()).unwrap(), 0x01.into());
+        assert_eq!(vm.registers.get_v(Register::V1), 0x03.into());
+        assert_eq!(vm.registers.get_v(Register::V3), 0x01.into());
+        assert_eq!(vm.registers.get_i(), Address::new(0x300));
+    }
+
+    #[test]
+    fn draw_reads_sprite_data_for_every_selected_plane() {
+        // PLANE 3, I = 0x208, DRW V0, V0, 1, followed by one byte per plane.
+        let program = [0xF3, 0x01, 0xA2, 0x08, 0xD0, 0x01, 0x00, 0x00, 0x80, 0xC0];
+        let mut vm = get_xo_vm(&program);
+
+        vm.execute_cycles(3).unwrap();
+
+        assert_eq!(vm.framebuffer.get_color(0, 0), 0b11);
+        assert_eq!(vm.framebuffer.get_color(1, 0), 0b10);
+    }
+
+    #[test]
+    fn audio_pattern_and_pitch_are_loaded() {
+        // I = 0x208, AUDIO, V0 = 0x70, PITCH V0, followed by the pattern.
+        let mut program = vec![0xA2, 0x08, 0xF0, 0x02, 0x60, 0x70, 0xF0, 0x3A];
+        program.extend(0..16u8);
+
+        let mut vm = get_xo_vm(&program);
+
+        vm.execute_cycles(2).unwrap();
+
+        let pattern = vm.audio_pattern.unwrap();
+        assert_eq!(pattern[15], 15.into());
+        assert_eq!(vm.audio.pattern, Some((pattern, 64.into())));
+
+        vm.execute_cycles(2).unwrap();
+
+        assert_eq!(vm.pitch, 0x70.into());
+        assert_eq!(vm.audio.pattern, Some((pattern, 0x70.into())));
+    }
+
+    #[test]
+    fn restoring_a_state_hands_its_pattern_to_the_audio() {
+        // I = 0x204, AUDIO, followed by the pattern.
+        let mut program = vec![0xA2, 0x04, 0xF0, 0x02];
+        program.extend(0..16u8);
+
+        let mut vm = get_xo_vm(&program);
+        let before = vm.snapshot();
+        vm.execute_cycles(2).unwrap();
+
+        let mut other = get_xo_vm(&[]);
+        other.restore(&vm.snapshot()).unwrap();
+        assert_eq!(other.audio_pattern, vm.audio_pattern);
+        assert_eq!(other.audio.pattern, vm.audio.pattern);
+
+        other.restore(&before).unwrap();
+        assert_eq!(other.audio_pattern, None);
+        assert_eq!(other.audio.pattern, None);
+    }
+
+    #[test]
+    fn invalid_opcodes_report_their_address() {
+        // V0 = 1, invalid
+        let mut vm = get_vm(Quirks::modern(), &[0x60, 0x01, 0xE0, 0x00]);
+
+        let result = vm.execute_cycles(2);
+
+        assert_eq!(result, Err(Chip8Error::InvalidOpcode(0x202.into(), 0xE000)));
+        assert_eq!(vm.pc.current(), Address::new(0x202));
+    }
+
+    #[test]
+    fn returning_from_the_main_routine_is_an_error() {
+        let mut vm = get_vm(Quirks::modern(), &[0x00, 0xEE]);
+
+        assert_eq!(vm.execute(), Err(Chip8Error::StackUnderflow(0x200.into())));
+    }
+
+    #[test]
+    fn unbounded_recursion_is_a_stack_overflow() {
+        // CALL 0x200
+        let mut vm = get_vm(Quirks::modern(), &[0x22, 0x00]);
+
+        assert_eq!(
+            vm.execute_cycles(100),
+            Err(Chip8Error::StackOverflow(0x200.into()))
+        );
+    }
+
+    #[test]
+    fn reading_past_the_end_of_memory_is_out_of_bounds() {
+        // I = 0xFFF, LD V1, [I]
+        let mut vm = get_vm(Quirks::modern(), &[0xAF, 0xFF, 0xF1, 0x65]);
+
+        assert_eq!(
+            vm.execute_cycles(2),
+            Err(Chip8Error::OutOfBounds(0x1000.into()))
+        );
+    }
+
+    #[test]
+    fn skips_past_the_end_of_memory_leave_the_pc_at_the_skip() {
+        let mut vm = get_vm(Quirks::modern(), &[0x12, 0x00]);
+        vm.write_memory(Address::new(0xFFE), Byte::new(0x30))
+            .unwrap();
+        vm.pc.set(Address::new(0xFFE));
+
+        // SE V0, 0 at 0xFFE is taken, the next instruction is past the end.
+        assert_eq!(
+            vm.execute_cycles(1),
+            Err(Chip8Error::OutOfBounds(0x1000.into()))
+        );
+        assert_eq!(vm.pc.current(), Address::new(0xFFE));
+    }
+
+    #[test]
+    fn load_rom_rejects_roms_that_do_not_fit() {
+        let mut vm = get_vm(Quirks::modern(), &[]);
+
+        let result = vm.load_rom(&[0; 0xE01]);
+
+        assert_eq!(
+            result,
+            Err(Chip8Error::RomTooLarge {
+                size: 0xE01,
+                capacity: 0xE00
+            })
+        );
+    }
+
+    #[test]
+    fn restore_brings_back_a_snapshot() {
+        // HIGH, CALL 0x206, (padding), V0 = 0x42, I = 0x300, DRW V0, V0, 5, V3 = 9
+        let program = [
+            0x00, 0xFF, 0x22, 0x06, 0x00, 0x00, 0x60, 0x42, 0xA3, 0x00, 0xD0, 0x05, 0x63, 0x09,
+        ];
+        let mut vm = get_vm(Quirks::super_chip(), &program);
+
+        vm.execute_cycles(5).unwrap();
+        vm.delay_timer = 30;
+        vm.press_key(0xA);
+
+        let snapshot = vm.snapshot();
+
+        let mut other = get_vm(Quirks::super_chip(), &[]);
+        other.restore(&snapshot).unwrap();
+
+        assert_eq!(other.snapshot(), snapshot);
+        assert_eq!(other.pc.stack(), vm.pc.stack());
+        assert_eq!(other.registers.get_i(), Address::new(0x300));
+        assert_eq!(other.delay_timer, 30);
+        assert!(other.framebuffer.is_high_res());
+        assert!(other.keyboard.is_pressed(0xA.into()));
+        assert_eq!(other.state, vm.state);
+
+        other.execute().unwrap();
+        assert_eq!(other.registers.get_v(Register::V3), 0x09.into());
+    }
+
+    #[test]
+    fn timers_do_not_follow_the_instruction_rate() {
+        // V0 = 10, DT = V0, ST = V0, jump 0x206
+        let mut vm = get_vm(
+            Quirks::modern(),
+            &[0x60, 0x0A, 0xF0, 0x15, 0xF0, 0x18, 0x12, 0x06],
+        );
+
+        vm.execute_cycles(20).unwrap();
+        assert_eq!((vm.delay_timer, vm.sound_timer), (10, 10));
+
+        vm.run_frame(8).unwrap();
+        vm.run_frame(8).unwrap();
+        assert_eq!((vm.delay_timer, vm.sound_timer), (8, 8));
+    }
+
+    #[test]
+    fn vip_random_numbers_follow_the_frames() {
+        // V0 = random, jump 0x200
+        let mut vm = VmBuilder::new()
+            .program(&[0xC0, 0xFF, 0x12, 0x00])
+            .random(VipRandom::cosmac_vip())
+            .build();
+        let mut expected = VipRandom::cosmac_vip();
+
+        for _ in 0..20 {
+            vm.run_frame(2).unwrap();
+
+            let value = expected.next_byte();
+            expected.end_frame();
+
+            assert_eq!(vm.registers.get_v(Register::V0), value);
+        }
+
+        assert_eq!(vm.random.state(), expected.state());
+    }
+
+    #[test]
+    fn audio_follows_the_sound_timer() {
+        // V0 = 2, ST = V0, jump 0x204
+        let mut vm = get_vm(Quirks::modern(), &[0x60, 0x02, 0xF0, 0x18, 0x12, 0x04]);
+
+        vm.run_frame(1).unwrap();
+        assert!(!vm.audio.on);
+
+        vm.run_frame(1).unwrap();
+        assert!(vm.audio.on);
+
+        vm.run_frame(1).unwrap();
+        assert!(!vm.audio.on);
+        assert_eq!(vm.audio.starts, 1);
+        assert_eq!(vm.audio.frames, 3);
+    }
+
+    #[test]
+    fn clock_runs_frames_in_virtual_time() {
+        // V0 = 60, DT = V0, jump 0x204
+        let mut vm = get_vm(Quirks::modern(), &[0x60, 0x3C, 0xF0, 0x15, 0x12, 0x04]);
+        vm.execute_cycles(2).unwrap();
+
+        let mut clock = Clock::new(VirtualTime::new(), 1000);
+        clock
+            .source_mut()
+            .advance(std::time::Duration::from_millis(500));
+
+        assert_eq!(clock.run(&mut vm).unwrap(), MAX_CATCH_UP);
+        assert_eq!(clock.frames(), 30);
+        assert_eq!(vm.delay_timer, 60 - MAX_CATCH_UP as u8);
+
+        clock
+            .source_mut()
+            .advance(std::time::Duration::from_millis(50));
+
+        assert_eq!(clock.run(&mut vm).unwrap(), 3);
+        assert_eq!(vm.delay_timer, 60 - MAX_CATCH_UP as u8 - 3);
+    }
+
+    #[test]
+    fn wait_for_key_stores_the_key_after_it_is_released() {
+        // LD V3, K; V1 = 1
+        let mut vm = get_vm(Quirks::modern(), &[0xF3, 0x0A, 0x61, 0x01]);
+
+        vm.execute_cycles(5).unwrap();
+        assert_eq!(vm.state, VMState::WaitingForKey(Register::V3, None));
+
+        vm.press_key(0x7);
+        vm.execute_cycles(5).unwrap();
+        assert_eq!(
+            vm.state,
+            VMState::WaitingForKey(Register::V3, Some(Nibble::new(0x7)))
+        );
+        assert_eq!(vm.get_reg(Register::V3), 0.into());
+
+        vm.release_key(0x7);
+        vm.execute_cycles(2).unwrap();
+
+        assert_eq!(vm.get_reg(Register::V3), 0x7.into());
+        assert_eq!(vm.get_reg(Register::V1), 0x1.into());
+    }
+
+    #[test]
+    fn waiting_for_key_survives_a_snapshot() {
+        // LD VA, K
+        let mut vm = get_vm(Quirks::modern(), &[0xFA, 0x0A]);
+
+        vm.execute().unwrap();
+        vm.press_key(0xC);
+        vm.execute().unwrap();
+
+        let mut other = get_vm(Quirks::modern(), &[]);
+        other.restore(&vm.snapshot()).unwrap();
+        assert_eq!(other.state, vm.state);
+
+        other.release_keys();
+        other.execute().unwrap();
+
+        assert_eq!(other.get_reg(Register::VA), 0xC.into());
+    }
+
+    #[test]
+    fn keys_are_held_independently() {
+        // SKP V0 (V0 = 0), V1 = 1, SKP V2 (V2 = 2), V3 = 3
+        let mut vm = get_vm(
+            Quirks::modern(),
+            &[0xE0, 0x9E, 0x61, 0x01, 0xE2, 0x9E, 0x63, 0x03],
+        );
+        vm.registers.set_v(Register::V2, 0x2.into());
+
+        vm.press_key(0x0);
+        vm.press_key(0x2);
+        vm.press_key(0x5);
+        vm.release_key(0x5);
+        vm.execute_cycles(2).unwrap();
+
+        assert_eq!(vm.get_reg(Register::V1), 0.into());
+        assert_eq!(vm.get_reg(Register::V3), 0.into());
+    }
+
+    /// SKP V0, jump 0x200, V1 = 1, jump 0x206
+    const TAP_PROGRAM: [u8; 8] = [0xE0, 0x9E, 0x12, 0x00, 0x61, 0x01, 0x12, 0x06];
+
+    #[test]
+    fn taps_inside_a_batch_reach_the_rom() {
+        let mut vm = get_vm(Quirks::modern(), &TAP_PROGRAM);
+
+        vm.queue_input(InputEvent::press(InputTime::Cycle(4), 0x0));
+        vm.queue_input(InputEvent::release(InputTime::Cycle(5), 0x0));
+        vm.execute_cycles(20).unwrap();
+
+        assert_eq!(vm.get_reg(Register::V1), 1.into());
+        assert!(!vm.keyboard.is_pressed(Nibble::new(0x0)));
+        assert_eq!(vm.cycles(), 20);
+    }
+
+    #[test]
+    fn taps_between_the_checks_are_missed_in_every_batch_size() {
+        // The key is down while the jump runs, so the ROM never sees it.
+        for batch in &[1, 3, 20] {
+            let mut vm = get_vm(Quirks::modern(), &TAP_PROGRAM);
+
+            vm.queue_input(InputEvent::press(InputTime::Cycle(5), 0x0));
+            vm.queue_input(InputEvent::release(InputTime::Cycle(6), 0x0));
+            for _ in 0..(60 / batch) {
+                vm.execute_cycles(*batch).unwrap();
+            }
+
+            assert_eq!(vm.get_reg(Register::V1), 0.into(), "batch {}", batch);
+        }
+    }
+
+    #[test]
+    fn frame_events_apply_at_the_start_of_the_frame() {
+        let mut vm = get_vm(Quirks::modern(), &TAP_PROGRAM);
+
+        vm.queue_input(InputEvent::press(InputTime::Frame(1), 0x0));
+        vm.run_frame(9).unwrap();
+        assert!(!vm.keyboard.is_pressed(Nibble::new(0x0)));
+
+        vm.run_frame(2).unwrap();
+        assert!(vm.keyboard.is_pressed(Nibble::new(0x0)));
+        assert_eq!(vm.get_reg(Register::V1), 0.into());
+        assert_eq!(vm.frames(), 2);
+    }
+
+    #[test]
+    fn clear_input_drops_queued_events_and_held_keys() {
+        let mut vm = get_vm(Quirks::modern(), &TAP_PROGRAM);
+
+        vm.press_key(0x5);
+        vm.queue_input(InputEvent::press(InputTime::Frame(1), 0x0));
+        vm.clear_input();
+
+        vm.run_frame(9).unwrap();
+        vm.run_frame(2).unwrap();
+        assert!(!vm.keyboard.is_pressed(Nibble::new(0x0)));
+        assert!(!vm.keyboard.is_pressed(Nibble::new(0x5)));
+    }
+
+    #[test]
+    fn wait_for_key_sees_a_tap_inside_a_batch() {
+        // LD V3, K
+        let mut vm = get_vm(Quirks::modern(), &[0xF3, 0x0A]);
+
+        vm.queue_input(InputEvent::press(InputTime::Cycle(3), 0xB));
+        vm.queue_input(InputEvent::release(InputTime::Cycle(4), 0xB));
+        vm.execute_cycles(10).unwrap();
+
+        assert_eq!(vm.get_reg(Register::V3), 0xB.into());
+    }
+
+    #[test]
+    fn rand_masks_the_injected_byte() {
+        // V0 = rand & 0x0F, V1 = rand & 0xF0
+        let mut vm = get_vm(Quirks::modern(), &[0xC0, 0x0F, 0xC1, 0xF0]);
+
+        vm.execute_cycles(2).unwrap();
+
+        assert_eq!(vm.get_reg(Register::V0), 0x05.into());
+        assert_eq!(vm.get_reg(Register::V1), 0x30.into());
+    }
+
+    #[test]
+    fn restore_resumes_the_random_sequence() {
+        // V0 = rand, V1 = rand
+        let mut vm = get_vm(Quirks::modern(), &[0xC0, 0xFF, 0xC1, 0xFF]);
+
+        vm.execute().unwrap();
+        let snapshot = vm.snapshot();
+
+        let mut other = get_vm(Quirks::modern(), &[]);
+        other.restore(&snapshot).unwrap();
+        other.execute().unwrap();
+
+        assert_eq!(other.get_reg(Register::V1), SCRIPT[1].into());
+    }
+
+    #[test]
+    fn restore_rejects_other_versions() {
+        let vm = get_vm(Quirks::modern(), &[]);
+        let mut snapshot = vm.snapshot();
+        snapshot[5] = snapshot[5].wrapping_add(1);
+
+        let mut other = get_vm(Quirks::modern(), &[0x60, 0x01]);
+
+        assert_eq!(
+            other.restore(&snapshot),
+            Err(Chip8Error::IncompatibleSaveState(SAVE_STATE_VERSION + 1))
+        );
+        assert_eq!(other.memory.get(0x200.into()).unwrap(), 0x60.into());
+    }
+
+    #[test]
+    fn restore_rejects_truncated_snapshots() {
+        let vm = get_vm(Quirks::modern(), &[]);
+        let snapshot = vm.snapshot();
+
+        let mut other = get_vm(Quirks::modern(), &[]);
+
+        assert!(other.restore(&snapshot[..snapshot.len() - 1]).is_err());
+    }
+
+    /// A random source with a large state, which is all of its bytes.
+    ///
+    struct Table(Vec<u8>);
+
+    impl Chip8Random for Table {
+        fn next_byte(&mut self) -> Byte {
+            self.0.rotate_left(1);
+            self.0[0].into()
+        }
+
+        fn state(&self) -> Vec<u8> {
+            self.0.clone()
+        }
+
+        fn set_state(&mut self, state: &[u8]) -> Result<(), Chip8Error> {
+            self.0 = state.to_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn random_states_longer_than_a_byte_are_saved() {
+        let table = || Table((0..=255).chain(0..=255).collect());
+        let mut vm = VmBuilder::new().random(table()).build();
+        vm.random.next_byte();
+
+        let mut other = VmBuilder::new().random(table()).build();
+        other.restore(&vm.snapshot()).unwrap();
+
+        assert_eq!(other.random.state(), vm.random.state());
+    }
+
+    #[test]
+    fn self_modifying_code_sees_its_own_writes() {
+        let program = [
+            0x60, 0x6B, // V0 = 0x6B
+            0x61, 0x42, // V1 = 0x42
+            0xA2, 0x10, // I = 0x210
+            0x22, 0x10, // call 0x210
+            0xF1, 0x55, // store V0 - V1 over the subroutine
+            0x22, 0x10, // call 0x210
+            0x12, 0x0C, // halt
+            0x00, 0x00, //
+            0x6B, 0x01, // 0x210: VB = 0x01
+            0x00, 0xEE, // return
+        ];
+        let mut vm = get_vm(Quirks::modern(), &program);
+
+        vm.execute_cycles(6).unwrap();
+        assert_eq!(vm.get_reg(Register::VB), 0x01.into());
+
+        vm.execute_cycles(4).unwrap();
+        assert_eq!(vm.get_reg(Register::VB), 0x42.into());
+    }
+
+    #[test]
+    fn written_instructions_run_in_every_engine() {
+        for engine in [Engine::Interpreter, Engine::Threaded] {
+            // VB = 0x01, jump 0x200
+            let mut vm = get_vm(Quirks::modern(), &[0x6B, 0x01, 0x12, 0x00]);
+            vm.engine = engine;
+
+            vm.execute_cycles(2).unwrap();
+            vm.write_memory(Address::new(0x201), Byte::new(0x02))
+                .unwrap();
+
+            vm.execute_cycles(1).unwrap();
+            assert_eq!(vm.get_reg(Register::VB), 0x02.into(), "{:?}", engine);
+            assert_eq!(vm.memory().get(0x201.into()).unwrap(), 0x02.into());
+        }
+    }
+
+    #[test]
+    fn bcd_and_restore_drop_decoded_instructions() {
+        let program = [
+            0x60, 0x60, // V0 = 0x60
+            0xA2, 0x08, // I = 0x208
+            0x12, 0x08, // jump 0x208
+            0xF0, 0x33, // BCD of V0 over 0x208
+            0x6B, 0x01, // 0x208: VB = 0x01
+            0x12, 0x06, // jump 0x206
+        ];
+        let mut vm = get_vm(Quirks::modern(), &program);
+        let snapshot = vm.snapshot();
+
+        // 0x208 now holds 00 09 06, SYS 0x009, which is left unimplemented.
+        vm.execute_cycles(7).unwrap();
+        assert_eq!(vm.state, VMState::Executing(Instruction::Sys(0x009.into())));
+
+        vm.restore(&snapshot).unwrap();
+        vm.pc.set(0x208.into());
+        vm.execute().unwrap();
+        assert_eq!(vm.get_reg(Register::VB), 0x01.into());
+    }
 }
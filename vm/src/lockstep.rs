@@ -0,0 +1,307 @@
+//! Runs the threaded engine and the interpreter side by side.
+//!
+//! Both machines are loaded the same way. After every block the threaded
+//! engine runs, the interpreter executes the same instructions and the two
+//! save states are compared, so the first difference is caught at the block
+//! that caused it.
+//!
+
+use super::{Engine, VirtualMachine};
+use data::Address;
+use model::{
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank, Chip8VirtualMachine, InputEvent,
+};
+use std::fmt;
+
+/// Why a lockstep run stopped.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum LockstepError {
+    /// Both engines failed the same way.
+    Machine(Chip8Error),
+
+    /// The engines disagreed after the block starting at the address, with
+    /// the threaded machine at the given cycle.
+    Mismatch { address: Address, cycle: u64 },
+}
+
+impl fmt::Display for LockstepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockstepError::Machine(e) => write!(f, "{}", e),
+            LockstepError::Mismatch { address, cycle } => write!(
+                f,
+                "the engines disagree after the block at {} (cycle {})",
+                address, cycle
+            ),
+        }
+    }
+}
+
+/// A threaded machine and an interpreted machine run in lockstep.
+///
+pub struct Lockstep<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    pub threaded: VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    pub interpreted: VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> Lockstep<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Pairs up two machines in the same state, switching each to its
+    /// engine.
+    ///
+    ///### Arguments
+    ///
+    ///- **threaded**    : Runs with the threaded engine.
+    ///- **interpreted** : Runs with the interpreter.
+    ///
+    pub fn new(
+        mut threaded: VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        mut interpreted: VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+    ) -> Self {
+        threaded.engine = Engine::Threaded;
+        interpreted.engine = Engine::Interpreter;
+
+        Lockstep {
+            threaded,
+            interpreted,
+        }
+    }
+
+    /// Queues the input event on both machines.
+    ///
+    pub fn queue_input(&mut self, event: InputEvent) {
+        self.threaded.queue_input(event);
+        self.interpreted.queue_input(event);
+    }
+
+    /// Executes the cycles on both machines, comparing them after every block.
+    ///
+    ///### Arguments
+    ///
+    ///- **cycles** : The number of cycles, as for `execute_cycles`.
+    ///
+    pub fn execute_cycles(&mut self, cycles: usize) -> Result<(), LockstepError> {
+        let mut spent = 0;
+
+        while spent < cycles {
+            let address = self.threaded.pc.current();
+
+            let threaded = self.threaded.run_block(cycles - spent);
+            let interpreted = self.catch_up(&threaded);
+
+            if threaded != interpreted || self.threaded.snapshot() != self.interpreted.snapshot() {
+                return Err(LockstepError::Mismatch {
+                    address,
+                    cycle: self.threaded.cycles(),
+                });
+            }
+
+            match threaded.map_err(LockstepError::Machine)? {
+                Some(cost) => spent += cost as usize,
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs a 60 Hz frame on both machines.
+    ///
+    ///### Arguments
+    ///
+    ///- **cycles** : The number of cycles executed during the frame.
+    ///
+    pub fn run_frame(&mut self, cycles: usize) -> Result<(), LockstepError> {
+        self.execute_cycles(self.threaded.frame_budget(cycles))?;
+
+        self.threaded.tick_timers();
+        self.interpreted.tick_timers();
+
+        Ok(())
+    }
+
+    /// Steps the interpreter through the instructions the threaded block ran,
+    /// and into the same failure or stop if it had one.
+    ///
+    fn catch_up(
+        &mut self,
+        threaded: &Result<Option<u32>, Chip8Error>,
+    ) -> Result<Option<u32>, Chip8Error> {
+        let target = self.threaded.cycles();
+        let mut spent = 0;
+
+        while self.interpreted.cycles() < target {
+            match self.interpreted.step()? {
+                Some(cost) => spent += cost,
+                None => return Ok(None),
+            }
+        }
+
+        match threaded {
+            Ok(Some(_)) => Ok(Some(spent)),
+            _ => self.interpreted.step(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{TestVm, VmBuilder};
+    use super::super::{Quirks, Timing, VIP_FRAME_CYCLES};
+    use super::*;
+    use framebuffer::FrameBuffer;
+    use keyboard::Keyboard;
+    use memory::Memory;
+    use model::{InputTime, Register};
+    use program_counter::ProgramCounter;
+    use random::SeededRandom;
+    use register_bank::RegisterBank;
+    use rom_library::{load_rom, ROM_NAMES};
+
+    fn get_vm(quirks: Quirks, program: &[u8]) -> TestVm<SeededRandom> {
+        VmBuilder::new()
+            .quirks(quirks)
+            .program(program)
+            .random(SeededRandom::new(7))
+            .build()
+    }
+
+    fn get_lockstep(
+        quirks: Quirks,
+        program: &[u8],
+    ) -> Lockstep<Memory, ProgramCounter, RegisterBank, FrameBuffer, Keyboard, SeededRandom, ()>
+    {
+        Lockstep::new(get_vm(quirks, program), get_vm(quirks, program))
+    }
+
+    #[test]
+    fn the_engines_agree_on_the_bundled_roms() {
+        // Comparing save states after every block is slow, so only the start
+        // of each game is played.
+        let configs = [
+            (Quirks::cosmac_vip(), Timing::Instructions, 11, 40u64),
+            (Quirks::super_chip(), Timing::Instructions, 11, 40),
+            (
+                Quirks::cosmac_vip(),
+                Timing::CosmacVip,
+                VIP_FRAME_CYCLES as usize,
+                10,
+            ),
+        ];
+
+        for name in ROM_NAMES.iter() {
+            let rom = load_rom(name.to_string()).unwrap();
+
+            for (quirks, timing, cycles, frames) in configs.iter() {
+                let mut lockstep = get_lockstep(*quirks, &rom);
+                lockstep.threaded.timing = *timing;
+                lockstep.interpreted.timing = *timing;
+
+                // Tap every key in turn, so the games move.
+                for frame in 0..*frames {
+                    let key = (frame / 4 % 16) as u8;
+                    let time = InputTime::Frame(frame);
+
+                    lockstep.queue_input(match frame % 4 {
+                        0 => InputEvent::press(time, key),
+                        2 => InputEvent::release(time, key),
+                        _ => continue,
+                    });
+                }
+
+                for _ in 0..*frames {
+                    match lockstep.run_frame(*cycles) {
+                        Ok(()) => {}
+                        Err(LockstepError::Machine(_)) => break,
+                        Err(e) => panic!("{} with {:?}: {}", name, timing, e),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn self_modifying_code_is_recompiled() {
+        let program = [
+            0x60, 0x6B, // V0 = 0x6B
+            0x61, 0x42, // V1 = 0x42
+            0xA2, 0x10, // I = 0x210
+            0x22, 0x10, // call 0x210
+            0xF1, 0x55, // store V0 - V1 over the subroutine
+            0x22, 0x10, // call 0x210
+            0x12, 0x0C, // halt
+            0x00, 0x00, //
+            0x6B, 0x01, // 0x210: VB = 0x01
+            0x00, 0xEE, // return
+        ];
+        let mut lockstep = get_lockstep(Quirks::modern(), &program);
+
+        lockstep.execute_cycles(10).unwrap();
+
+        assert_eq!(lockstep.threaded.cycles(), 10);
+        assert_eq!(lockstep.threaded.registers.get_v(Register::VB), 0x42.into());
+    }
+
+    #[test]
+    fn blocks_stop_when_the_budget_is_spent() {
+        // V0 = 1, V1 = 2, V2 = 3, jump 0x200
+        let program = [0x60, 0x01, 0x61, 0x02, 0x62, 0x03, 0x12, 0x00];
+        let mut lockstep = get_lockstep(Quirks::modern(), &program);
+
+        lockstep.execute_cycles(2).unwrap();
+
+        assert_eq!(lockstep.threaded.cycles(), 2);
+        assert_eq!(lockstep.threaded.pc.current(), Address::new(0x204));
+        assert_eq!(lockstep.threaded.registers.get_v(Register::V2), 0.into());
+    }
+
+    #[test]
+    fn failures_are_reported_once_both_engines_agree() {
+        // V0 = 1, then an invalid instruction
+        let mut lockstep = get_lockstep(Quirks::modern(), &[0x60, 0x01, 0xE0, 0x00]);
+
+        assert_eq!(
+            lockstep.execute_cycles(5),
+            Err(LockstepError::Machine(Chip8Error::InvalidOpcode(
+                Address::new(0x202),
+                0xE000
+            )))
+        );
+    }
+
+    #[test]
+    fn differences_are_caught_at_the_block_that_shows_them() {
+        // V0 += 1, jump 0x200
+        let mut lockstep = get_lockstep(Quirks::modern(), &[0x70, 0x01, 0x12, 0x00]);
+
+        lockstep.execute_cycles(10).unwrap();
+        lockstep.interpreted.registers.set_v(Register::V0, 0.into());
+
+        assert_eq!(
+            lockstep.execute_cycles(10),
+            Err(LockstepError::Mismatch {
+                address: Address::new(0x200),
+                cycle: 12
+            })
+        );
+    }
+}
@@ -0,0 +1,695 @@
+//! Recording and deterministic playback of input movies.
+//!
+//! A movie holds everything needed to replay a session besides the rom
+//! itself: the rom hash, the quirks, the timing, the seed of the random
+//! source, the speed of the CPU and the state of the keys for every frame.
+//!
+//! A checksum of the whole machine is taken every `checksum_interval` frames,
+//! before the frame runs, and once more after the last frame, so playback can
+//! tell where it diverged from the recording. The divergence is only narrowed
+//! down to the checksum interval: the frame reported is the first checked one
+//! whose state differs, the inputs that caused it may be up to
+//! `checksum_interval` frames earlier.
+//!
+//! All values are written big endian in the following order.
+//!
+//! ```text
+//! "C8MV"                       magic
+//! u16                          format version
+//! u64                          rom hash
+//! u8, u8, u8, u8, u8           quirks: shift uses Vy, load/store, jump uses
+//!                              Vx, logic resets VF, clip sprites
+//! u8                           timing: instructions, COSMAC VIP
+//! u64                          random seed
+//! u64                          instructions per second
+//! u32                          checksum interval in frames
+//! u32, [u16]                   frame count and the pressed keys per frame
+//! u32, [u64]                   checksum count and checksums
+//! u8, [u64]                    whether the recording was finished and the
+//!                              checksum after the last frame
+//! ```
+//!
+
+use super::clock::cycles_for_frame;
+use super::reader::Reader;
+use super::{LoadStoreQuirk, Quirks, Timing, VirtualMachine, VIP_CYCLES_PER_SECOND};
+use model::{
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank, Chip8VirtualMachine,
+};
+
+/// Identifies a movie.
+const MAGIC: &[u8; 4] = b"C8MV";
+
+/// The current version of the format. Bump it whenever the layout changes.
+pub const MOVIE_VERSION: u16 = 3;
+
+/// Hashes bytes with 64 bit FNV-1a.
+///
+/// Used for the rom hash and the state checksums.
+///
+pub fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+/// Creates a CorruptMovie error.
+///
+fn corrupt(reason: &str) -> Chip8Error {
+    Chip8Error::CorruptMovie(reason.into())
+}
+
+/// A recorded session.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Movie {
+    pub rom_hash: u64,
+    pub quirks: Quirks,
+    pub timing: Timing,
+    pub seed: u64,
+    pub instructions_per_second: u64,
+    pub checksum_interval: u32,
+
+    /// The pressed keys of every frame, bit n for key n.
+    pub frames: Vec<u16>,
+
+    /// The state checksum before frame `n * checksum_interval`.
+    pub checksums: Vec<u64>,
+
+    /// The state checksum after the last frame, set once recording finishes.
+    pub end_checksum: Option<u64>,
+}
+
+impl Movie {
+    /// Creates an empty Movie.
+    ///
+    /// The machine the movie is recorded and played on has to be created
+    /// with these quirks and timing and a random source seeded with this seed.
+    ///
+    /// The COSMAC VIP timing runs at the speed of the VIP, the given speed is
+    /// replaced with `VIP_CYCLES_PER_SECOND` for it.
+    ///
+    ///### Arguments
+    ///
+    ///- **rom**                     : The rom being played.
+    ///- **quirks**                  : The quirks of the machine.
+    ///- **timing**                  : The timing of the machine.
+    ///- **seed**                    : The seed of the random source.
+    ///- **instructions_per_second** : The speed of the CPU.
+    ///- **checksum_interval**       : The number of frames between checksums.
+    ///
+    pub fn new(
+        rom: &[u8],
+        quirks: Quirks,
+        timing: Timing,
+        seed: u64,
+        instructions_per_second: u64,
+        checksum_interval: u32,
+    ) -> Self {
+        Movie {
+            rom_hash: checksum(rom),
+            quirks,
+            timing,
+            seed,
+            instructions_per_second: match timing {
+                Timing::Instructions => instructions_per_second,
+                Timing::CosmacVip => VIP_CYCLES_PER_SECOND,
+            },
+            checksum_interval: checksum_interval.max(1),
+            frames: Vec::new(),
+            checksums: Vec::new(),
+            end_checksum: None,
+        }
+    }
+
+    /// Returns the number of frames recorded.
+    ///
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns a flag indicating if no frame has been recorded.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Checks that the rom is the one the movie was recorded with.
+    ///
+    pub fn check_rom(&self, rom: &[u8]) -> Result<(), Chip8Error> {
+        let found = checksum(rom);
+
+        if found != self.rom_hash {
+            return Err(Chip8Error::RomMismatch {
+                expected: self.rom_hash,
+                found,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the movie.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&MOVIE_VERSION.to_be_bytes());
+        out.extend_from_slice(&self.rom_hash.to_be_bytes());
+
+        let q = &self.quirks;
+        out.push(q.shift_uses_vy as u8);
+        out.push(match q.load_store {
+            LoadStoreQuirk::IncrementByXPlusOne => 0,
+            LoadStoreQuirk::IncrementByX => 1,
+            LoadStoreQuirk::Unchanged => 2,
+        });
+        out.push(q.jump_uses_vx as u8);
+        out.push(q.logic_resets_vf as u8);
+        out.push(q.clip_sprites as u8);
+
+        out.push(match self.timing {
+            Timing::Instructions => 0,
+            Timing::CosmacVip => 1,
+        });
+
+        out.extend_from_slice(&self.seed.to_be_bytes());
+        out.extend_from_slice(&self.instructions_per_second.to_be_bytes());
+        out.extend_from_slice(&self.checksum_interval.to_be_bytes());
+
+        out.extend_from_slice(&(self.frames.len() as u32).to_be_bytes());
+        for keys in &self.frames {
+            out.extend_from_slice(&keys.to_be_bytes());
+        }
+
+        out.extend_from_slice(&(self.checksums.len() as u32).to_be_bytes());
+        for sum in &self.checksums {
+            out.extend_from_slice(&sum.to_be_bytes());
+        }
+
+        match self.end_checksum {
+            Some(sum) => {
+                out.push(1);
+                out.extend_from_slice(&sum.to_be_bytes());
+            }
+            None => out.push(0),
+        }
+
+        out
+    }
+
+    /// Parses a movie.
+    ///
+    ///### Arguments
+    ///
+    ///- **data** : The bytes returned by `to_bytes`.
+    ///
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Chip8Error> {
+        let mut r = Reader::new(data, Chip8Error::CorruptMovie);
+
+        if r.bytes(MAGIC.len())? != MAGIC {
+            return Err(corrupt("not a movie"));
+        }
+
+        let version = r.u16()?;
+        if version != MOVIE_VERSION {
+            return Err(corrupt(&format!("version {} is not supported", version)));
+        }
+
+        let rom_hash = r.u64()?;
+
+        let flag = |b: u8| match b {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(corrupt("invalid quirk")),
+        };
+        let shift_uses_vy = flag(r.u8()?)?;
+        let load_store = match r.u8()? {
+            0 => LoadStoreQuirk::IncrementByXPlusOne,
+            1 => LoadStoreQuirk::IncrementByX,
+            2 => LoadStoreQuirk::Unchanged,
+            _ => return Err(corrupt("invalid quirk")),
+        };
+        let quirks = Quirks {
+            shift_uses_vy,
+            load_store,
+            jump_uses_vx: flag(r.u8()?)?,
+            logic_resets_vf: flag(r.u8()?)?,
+            clip_sprites: flag(r.u8()?)?,
+        };
+
+        let timing = match r.u8()? {
+            0 => Timing::Instructions,
+            1 => Timing::CosmacVip,
+            _ => return Err(corrupt("invalid timing")),
+        };
+
+        let seed = r.u64()?;
+        let instructions_per_second = r.u64()?;
+        if timing == Timing::CosmacVip && instructions_per_second != VIP_CYCLES_PER_SECOND {
+            return Err(corrupt("invalid speed for the COSMAC VIP timing"));
+        }
+
+        let checksum_interval = r.u32()?;
+        if checksum_interval == 0 {
+            return Err(corrupt("invalid checksum interval"));
+        }
+
+        let count = r.u32()? as usize;
+        let mut frames = Vec::with_capacity(count.min(data.len()));
+        for _ in 0..count {
+            frames.push(r.u16()?);
+        }
+
+        let count = r.u32()? as usize;
+        let mut checksums = Vec::with_capacity(count.min(data.len()));
+        for _ in 0..count {
+            checksums.push(r.u64()?);
+        }
+
+        let end_checksum = match r.u8()? {
+            0 => None,
+            1 => Some(r.u64()?),
+            _ => return Err(corrupt("invalid end checksum")),
+        };
+
+        if !r.is_at_end() {
+            return Err(corrupt("trailing data"));
+        }
+
+        Ok(Movie {
+            rom_hash,
+            quirks,
+            timing,
+            seed,
+            instructions_per_second,
+            checksum_interval,
+            frames,
+            checksums,
+            end_checksum,
+        })
+    }
+
+    /// Returns a flag indicating if a checksum is taken before the frame.
+    ///
+    fn is_checked(&self, frame: usize) -> bool {
+        frame.is_multiple_of(self.checksum_interval as usize)
+    }
+}
+
+/// Sets the keyboard of the machine to the pressed keys and runs a frame.
+///
+fn run_frame(
+    vm: &mut dyn Chip8VirtualMachine,
+    instructions_per_second: u64,
+    frame: usize,
+    keys: u16,
+) -> Result<(), Chip8Error> {
+    for key in 0..16u8 {
+        if keys & (1 << key) != 0 {
+            vm.press_key(key);
+        } else {
+            vm.release_key(key);
+        }
+    }
+
+    vm.run_frame(cycles_for_frame(instructions_per_second, frame as u64))
+}
+
+/// Drives a machine while recording a movie.
+///
+pub struct MovieRecorder {
+    movie: Movie,
+}
+
+impl MovieRecorder {
+    /// Creates a new MovieRecorder.
+    ///
+    ///### Arguments
+    ///
+    ///- **movie** : The movie to add the frames to, usually a new one.
+    ///
+    pub fn new(movie: Movie) -> Self {
+        MovieRecorder { movie }
+    }
+
+    /// Runs a frame with the keys pressed and records it.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm**   : The machine being played.
+    ///- **keys** : The pressed keys, bit n for key n.
+    ///
+    pub fn run_frame(
+        &mut self,
+        vm: &mut dyn Chip8VirtualMachine,
+        keys: u16,
+    ) -> Result<(), Chip8Error> {
+        let frame = self.movie.frames.len();
+
+        if self.movie.is_checked(frame) {
+            self.movie.checksums.push(checksum(&vm.snapshot()));
+        }
+        self.movie.frames.push(keys);
+
+        run_frame(vm, self.movie.instructions_per_second, frame, keys)
+    }
+
+    /// Returns the movie recorded so far.
+    ///
+    pub fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    /// Stops recording and takes the checksum of the final state.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine being played, after the last frame.
+    ///
+    pub fn finish(mut self, vm: &dyn Chip8VirtualMachine) -> Movie {
+        self.movie.end_checksum = Some(checksum(&vm.snapshot()));
+        self.movie
+    }
+}
+
+/// Feeds a movie back into a machine.
+///
+pub struct MoviePlayer<'a> {
+    movie: &'a Movie,
+    frame: usize,
+}
+
+impl<'a> MoviePlayer<'a> {
+    /// Creates a new MoviePlayer starting at the first frame.
+    ///
+    ///### Arguments
+    ///
+    ///- **movie** : The movie to play.
+    ///- **vm**    : The machine the movie is played on, before the first frame.
+    ///- **rom**   : The rom loaded into the machine.
+    ///
+    ///### Returns
+    ///
+    /// A RomMismatch error if the rom is not the recorded one, a
+    /// MovieMismatch error if the quirks or the timing differ, or a
+    /// CorruptMovie error if the COSMAC VIP timing is not run at its speed.
+    ///
+    pub fn new<M, PC, R, FB, KB, RNG, AU>(
+        movie: &'a Movie,
+        vm: &VirtualMachine<M, PC, R, FB, KB, RNG, AU>,
+        rom: &[u8],
+    ) -> Result<Self, Chip8Error>
+    where
+        M: Chip8Memory,
+        PC: Chip8ProgramCounter,
+        R: Chip8RegisterBank,
+        FB: Chip8FrameBuffer,
+        KB: Chip8Keyboard,
+        RNG: Chip8Random,
+        AU: Chip8Audio,
+    {
+        movie.check_rom(rom)?;
+
+        if vm.quirks != movie.quirks {
+            return Err(Chip8Error::MovieMismatch("quirks".into()));
+        }
+
+        if vm.timing != movie.timing {
+            return Err(Chip8Error::MovieMismatch("timing".into()));
+        }
+
+        if movie.timing == Timing::CosmacVip
+            && movie.instructions_per_second != VIP_CYCLES_PER_SECOND
+        {
+            return Err(corrupt("invalid speed for the COSMAC VIP timing"));
+        }
+
+        Ok(MoviePlayer { movie, frame: 0 })
+    }
+
+    /// Returns the number of the next frame.
+    ///
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Returns a flag indicating if every frame has been played.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.movie.frames.len()
+    }
+
+    /// Plays the next frame.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine the movie is played on.
+    ///
+    ///### Returns
+    ///
+    /// False once every frame has been played, or a MovieDesync error if the
+    /// machine is not in the recorded state. The state is checked before the
+    /// frames with a checksum and after the last frame, so the frame in the
+    /// error is the first checked one whose state differs.
+    ///
+    pub fn run_frame(&mut self, vm: &mut dyn Chip8VirtualMachine) -> Result<bool, Chip8Error> {
+        let frame = self.frame;
+        let keys = match self.movie.frames.get(frame) {
+            Some(keys) => *keys,
+            None => return Ok(false),
+        };
+
+        if self.movie.is_checked(frame) {
+            let index = frame / self.movie.checksum_interval as usize;
+            let expected = self.movie.checksums.get(index);
+
+            if expected.is_some_and(|sum| *sum != checksum(&vm.snapshot())) {
+                return Err(Chip8Error::MovieDesync {
+                    frame: frame as u64,
+                });
+            }
+        }
+
+        self.frame += 1;
+        run_frame(vm, self.movie.instructions_per_second, frame, keys)?;
+
+        if self.is_finished() {
+            let expected = self.movie.end_checksum;
+
+            if expected.is_some_and(|sum| sum != checksum(&vm.snapshot())) {
+                return Err(Chip8Error::MovieDesync {
+                    frame: self.frame as u64,
+                });
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Plays every remaining frame.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine the movie is played on.
+    ///
+    pub fn play(&mut self, vm: &mut dyn Chip8VirtualMachine) -> Result<(), Chip8Error> {
+        while self.run_frame(vm)? {}
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{TestVm, VmBuilder};
+    use model::{Chip8RegisterBank, Register};
+    use random::SeededRandom;
+
+    type VM = TestVm<SeededRandom>;
+
+    /// Adds a random byte to V1 while key 5 is held.
+    ///
+    /// V0 = 5, 0x202: SKNP V0, V2 = rand, V1 += V2, jump 0x202
+    const ROM: [u8; 10] = [0x60, 0x05, 0xE0, 0xA1, 0xC2, 0xFF, 0x81, 0x24, 0x12, 0x02];
+
+    fn get_vm(movie: &Movie) -> VM {
+        let mut vm = VmBuilder::new()
+            .quirks(movie.quirks)
+            .program(&ROM)
+            .random(SeededRandom::new(movie.seed))
+            .build();
+
+        vm.timing = movie.timing;
+
+        vm
+    }
+
+    fn play(movie: &Movie, vm: &mut VM) -> Result<(), Chip8Error> {
+        MoviePlayer::new(movie, vm, &ROM)?.play(vm)
+    }
+
+    fn record() -> (Movie, VM) {
+        let movie = Movie::new(
+            &ROM,
+            Quirks::cosmac_vip(),
+            Timing::Instructions,
+            1234,
+            500,
+            4,
+        );
+        let mut vm = get_vm(&movie);
+        let mut recorder = MovieRecorder::new(movie);
+
+        for frame in 0..30 {
+            let keys = if frame % 3 == 0 { 1 << 5 } else { 0 };
+            recorder.run_frame(&mut vm, keys).unwrap();
+        }
+
+        let movie = recorder.finish(&vm);
+
+        (movie, vm)
+    }
+
+    #[test]
+    fn playback_reproduces_the_recording() {
+        let (movie, recorded) = record();
+
+        let mut vm = get_vm(&movie);
+        play(&movie, &mut vm).unwrap();
+
+        assert_ne!(recorded.registers.get_v(Register::V1), 0.into());
+        assert_eq!(vm.snapshot(), recorded.snapshot());
+    }
+
+    #[test]
+    fn movies_round_trip_through_bytes() {
+        let (movie, _) = record();
+
+        assert_eq!(movie.len(), 30);
+        assert_eq!(movie.checksums.len(), 8);
+        assert_eq!(Movie::from_bytes(&movie.to_bytes()).unwrap(), movie);
+
+        let vip = Movie {
+            timing: Timing::CosmacVip,
+            instructions_per_second: VIP_CYCLES_PER_SECOND,
+            end_checksum: None,
+            ..movie
+        };
+        assert_eq!(Movie::from_bytes(&vip.to_bytes()).unwrap(), vip);
+    }
+
+    #[test]
+    fn damaged_movies_are_rejected() {
+        let (movie, _) = record();
+        let bytes = movie.to_bytes();
+
+        assert!(matches!(
+            Movie::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(Chip8Error::CorruptMovie(_))
+        ));
+        assert!(matches!(
+            Movie::from_bytes(b"C8SS"),
+            Err(Chip8Error::CorruptMovie(_))
+        ));
+    }
+
+    #[test]
+    fn desyncs_report_the_first_diverging_checkpoint() {
+        let (mut movie, _) = record();
+
+        // Changing an input changes every state after it.
+        movie.frames[9] ^= 1 << 5;
+        let mut vm = get_vm(&movie);
+
+        let result = play(&movie, &mut vm);
+
+        assert_eq!(result, Err(Chip8Error::MovieDesync { frame: 12 }));
+    }
+
+    #[test]
+    fn desyncs_after_the_last_checkpoint_are_caught_at_the_end() {
+        let (mut movie, _) = record();
+
+        // The last checksum is taken before frame 28.
+        movie.frames[29] ^= 1 << 5;
+        let mut vm = get_vm(&movie);
+
+        let result = play(&movie, &mut vm);
+
+        assert_eq!(result, Err(Chip8Error::MovieDesync { frame: 30 }));
+    }
+
+    #[test]
+    fn a_different_seed_desyncs_at_the_start() {
+        let (movie, _) = record();
+
+        let mut other = movie.clone();
+        other.seed += 1;
+        let mut vm = get_vm(&other);
+
+        let result = play(&movie, &mut vm);
+
+        assert_eq!(result, Err(Chip8Error::MovieDesync { frame: 0 }));
+    }
+
+    #[test]
+    fn players_check_the_machine_before_the_first_frame() {
+        let (movie, _) = record();
+        let vm = get_vm(&movie);
+
+        assert!(matches!(
+            MoviePlayer::new(&movie, &vm, &ROM[1..]),
+            Err(Chip8Error::RomMismatch { .. })
+        ));
+
+        let mut vm = get_vm(&movie);
+        vm.quirks = Quirks::super_chip();
+        assert_eq!(
+            MoviePlayer::new(&movie, &vm, &ROM).err(),
+            Some(Chip8Error::MovieMismatch("quirks".into()))
+        );
+
+        let mut vm = get_vm(&movie);
+        vm.timing = Timing::CosmacVip;
+        assert_eq!(
+            MoviePlayer::new(&movie, &vm, &ROM).err(),
+            Some(Chip8Error::MovieMismatch("timing".into()))
+        );
+    }
+
+    #[test]
+    fn the_cosmac_vip_timing_runs_at_its_own_speed() {
+        let movie = Movie::new(&ROM, Quirks::cosmac_vip(), Timing::CosmacVip, 1, 500, 4);
+        assert_eq!(movie.instructions_per_second, VIP_CYCLES_PER_SECOND);
+
+        let movie = Movie {
+            instructions_per_second: 500,
+            ..movie
+        };
+        let vm = get_vm(&movie);
+
+        assert!(matches!(
+            MoviePlayer::new(&movie, &vm, &ROM),
+            Err(Chip8Error::CorruptMovie(_))
+        ));
+        assert!(matches!(
+            Movie::from_bytes(&movie.to_bytes()),
+            Err(Chip8Error::CorruptMovie(_))
+        ));
+    }
+
+    #[test]
+    fn check_rom_compares_the_hash() {
+        let (movie, _) = record();
+
+        assert!(movie.check_rom(&ROM).is_ok());
+        assert!(matches!(
+            movie.check_rom(&ROM[1..]),
+            Err(Chip8Error::RomMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! Configuration for the contested Chip-8 instructions.
+//!
+//! The original COSMAC VIP interpreter, CHIP-48 and SUPER-CHIP all disagree on
+//! a handful of instructions. ROMs are written against one of them, so the
+//! behaviour has to be selectable per ROM.
+//!
+
+/// How `Fx55` and `Fx65` leave the address register once they are done.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoadStoreQuirk {
+    /// I is left pointing one past the last register copied (I = I + x + 1).
+    IncrementByXPlusOne,
+
+    /// I is incremented by x (CHIP-48).
+    IncrementByX,
+
+    /// I is left untouched (SUPER-CHIP 1.1).
+    Unchanged,
+}
+
+/// The set of switchable instruction behaviours.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quirks {
+    /// `8xy6` and `8xyE` shift Vy and store the result in Vx.
+    /// When false Vx is shifted in place and Vy is ignored.
+    pub shift_uses_vy: bool,
+
+    /// How `Fx55` and `Fx65` update I.
+    pub load_store: LoadStoreQuirk,
+
+    /// `Bnnn` jumps to nnn + Vx, where x is the high nibble of nnn.
+    /// When false the jump is always relative to V0.
+    pub jump_uses_vx: bool,
+
+    /// `8xy1`, `8xy2` and `8xy3` reset VF to 0.
+    pub logic_resets_vf: bool,
+
+    /// Sprites that run off the edge of the screen are clipped.
+    /// When false they wrap around to the opposite edge.
+    pub clip_sprites: bool,
+}
+
+impl Quirks {
+    /// The behaviour of the original COSMAC VIP interpreter.
+    ///
+    pub fn cosmac_vip() -> Self {
+        Quirks {
+            shift_uses_vy: true,
+            load_store: LoadStoreQuirk::IncrementByXPlusOne,
+            jump_uses_vx: false,
+            logic_resets_vf: true,
+            clip_sprites: true,
+        }
+    }
+
+    /// The behaviour of CHIP-48 on the HP-48 calculators.
+    ///
+    pub fn chip48() -> Self {
+        Quirks {
+            shift_uses_vy: false,
+            load_store: LoadStoreQuirk::IncrementByX,
+            jump_uses_vx: true,
+            logic_resets_vf: false,
+            clip_sprites: true,
+        }
+    }
+
+    /// The behaviour of SUPER-CHIP 1.1.
+    ///
+    pub fn super_chip() -> Self {
+        Quirks {
+            shift_uses_vy: false,
+            load_store: LoadStoreQuirk::Unchanged,
+            jump_uses_vx: true,
+            logic_resets_vf: false,
+            clip_sprites: true,
+        }
+    }
+
+    /// The behaviour most modern interpreters (Octo, XO-CHIP) settled on.
+    ///
+    pub fn modern() -> Self {
+        Quirks {
+            shift_uses_vy: true,
+            load_store: LoadStoreQuirk::IncrementByXPlusOne,
+            jump_uses_vx: false,
+            logic_resets_vf: false,
+            clip_sprites: false,
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Quirks::modern()
+    }
+}
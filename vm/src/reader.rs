@@ -0,0 +1,78 @@
+//! Reading of the big endian binary formats.
+//!
+
+use model::Chip8Error;
+
+/// Reads values from a binary blob, failing on truncation.
+///
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+
+    /// Creates the error raised when the data is damaged.
+    error: fn(String) -> Chip8Error,
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a new Reader.
+    ///
+    ///### Arguments
+    ///
+    ///- **data**  : The bytes to read.
+    ///- **error** : Creates the error raised when the data is damaged.
+    ///
+    pub(crate) fn new(data: &'a [u8], error: fn(String) -> Chip8Error) -> Self {
+        Reader {
+            data,
+            pos: 0,
+            error,
+        }
+    }
+
+    /// Creates the error for damaged data.
+    ///
+    pub(crate) fn corrupt(&self, reason: &str) -> Chip8Error {
+        (self.error)(reason.into())
+    }
+
+    /// Returns a flag indicating if every byte has been read.
+    ///
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    pub(crate) fn bytes(&mut self, n: usize) -> Result<&'a [u8], Chip8Error> {
+        let end = self.pos + n;
+        let bytes = match self.data.get(self.pos..end) {
+            Some(bytes) => bytes,
+            None => return Err(self.corrupt("unexpected end of data")),
+        };
+
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, Chip8Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, Chip8Error> {
+        let b = self.bytes(2)?;
+
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, Chip8Error> {
+        let b = self.bytes(4)?;
+
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, Chip8Error> {
+        let mut b = [0; 8];
+        b.copy_from_slice(self.bytes(8)?);
+
+        Ok(u64::from_be_bytes(b))
+    }
+}
@@ -0,0 +1,435 @@
+//! A bounded history of save states for stepping gameplay backwards.
+//!
+//! Only every `keyframe_interval`-th state is kept whole. The states in
+//! between are stored as the difference to the latest keyframe, which keeps
+//! the memory use low since most of a snapshot does not change frame to frame.
+//!
+
+use model::{Chip8Error, Chip8VirtualMachine};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// A recorded state.
+///
+struct Entry {
+    /// The full snapshot the delta was taken against.
+    keyframe: Rc<Vec<u8>>,
+
+    /// The encoded difference to the keyframe, empty for the keyframe itself.
+    delta: Vec<u8>,
+}
+
+impl Entry {
+    /// Rebuilds the snapshot.
+    ///
+    fn snapshot(&self) -> Vec<u8> {
+        if self.delta.is_empty() {
+            self.keyframe.to_vec()
+        } else {
+            apply_delta(&self.keyframe, &self.delta)
+        }
+    }
+}
+
+/// A ring buffer of periodic snapshots of a virtual machine.
+///
+pub struct Rewind {
+    capacity: usize,
+    interval: usize,
+    keyframe_interval: usize,
+    entries: VecDeque<Entry>,
+    since_keyframe: usize,
+    frame: usize,
+}
+
+impl Rewind {
+    /// Creates a new Rewind buffer.
+    ///
+    ///### Arguments
+    ///
+    ///- **capacity**          : The number of states kept, older ones are dropped.
+    ///- **interval**          : The number of frames between two recorded states.
+    ///- **keyframe_interval** : The number of states between two full snapshots, 1 stores every state whole.
+    ///
+    pub fn new(capacity: usize, interval: usize, keyframe_interval: usize) -> Self {
+        Rewind {
+            capacity: capacity.max(1),
+            interval: interval.max(1),
+            keyframe_interval: keyframe_interval.max(1),
+            entries: VecDeque::new(),
+            since_keyframe: 0,
+            frame: 0,
+        }
+    }
+
+    /// Records the state of the machine if the interval has passed.
+    ///
+    /// Call this once per frame.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine being played.
+    ///
+    pub fn record(&mut self, vm: &dyn Chip8VirtualMachine) {
+        self.frame += 1;
+
+        if self.frame < self.interval {
+            return;
+        }
+
+        self.frame = 0;
+        self.push(vm.snapshot());
+    }
+
+    /// Steps the machine back to the most recent recorded state.
+    ///
+    /// The state is removed from the buffer, so calling this repeatedly keeps
+    /// going further back.
+    ///
+    ///### Arguments
+    ///
+    ///- **vm** : The machine being played.
+    ///
+    ///### Returns
+    ///
+    /// False if there was nothing left to rewind to.
+    ///
+    pub fn rewind(&mut self, vm: &mut dyn Chip8VirtualMachine) -> Result<bool, Chip8Error> {
+        let entry = match self.entries.pop_back() {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        // Make the next recording a keyframe, the one the popped entries
+        // were based on may be gone.
+        self.since_keyframe = self.keyframe_interval;
+        self.frame = 0;
+
+        vm.restore(&entry.snapshot())?;
+
+        Ok(true)
+    }
+
+    /// Returns the number of recorded states.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns a flag indicating if there is nothing to rewind to.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the approximate number of bytes used by the recorded states.
+    ///
+    /// Each keyframe is counted once, even when the entry it was recorded
+    /// with has been dropped and only deltas against it are left.
+    ///
+    pub fn memory_usage(&self) -> usize {
+        let mut usage = 0;
+        let mut keyframe: Option<&Rc<Vec<u8>>> = None;
+
+        for entry in &self.entries {
+            // The entries sharing a keyframe follow each other.
+            if !keyframe.is_some_and(|keyframe| Rc::ptr_eq(keyframe, &entry.keyframe)) {
+                usage += entry.keyframe.len();
+                keyframe = Some(&entry.keyframe);
+            }
+
+            usage += entry.delta.len();
+        }
+
+        usage
+    }
+
+    /// Drops every recorded state.
+    ///
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.since_keyframe = 0;
+        self.frame = 0;
+    }
+
+    /// Adds a snapshot, as a delta against the latest keyframe if possible.
+    ///
+    fn push(&mut self, snapshot: Vec<u8>) {
+        let keyframe = self
+            .entries
+            .back()
+            .map(|entry| Rc::clone(&entry.keyframe))
+            .filter(|keyframe| keyframe.len() == snapshot.len());
+
+        let entry = match keyframe {
+            Some(keyframe) if self.since_keyframe < self.keyframe_interval => {
+                let delta = encode_delta(&keyframe, &snapshot);
+
+                Entry { keyframe, delta }
+            }
+            _ => {
+                self.since_keyframe = 0;
+
+                Entry {
+                    keyframe: Rc::new(snapshot),
+                    delta: Vec::new(),
+                }
+            }
+        };
+
+        self.since_keyframe += 1;
+        self.entries.push_back(entry);
+
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Writes a variable length unsigned integer.
+///
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+
+    out.push(value as u8);
+}
+
+/// Reads a variable length unsigned integer.
+///
+fn read_varint(data: &[u8], pos: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+
+    while let Some(byte) = data.get(*pos) {
+        *pos += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        shift += 7;
+
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    value
+}
+
+/// Encodes the bytes that differ between two snapshots of the same length.
+///
+/// The delta is a list of (unchanged run, changed run, changed bytes). An
+/// identical snapshot still produces a non-empty delta so it can be told
+/// apart from a keyframe.
+///
+fn encode_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+
+    while pos < target.len() {
+        let start = pos;
+        while pos < target.len() && base[pos] == target[pos] {
+            pos += 1;
+        }
+        let skip = pos - start;
+
+        let start = pos;
+        while pos < target.len() && base[pos] != target[pos] {
+            pos += 1;
+        }
+
+        write_varint(&mut out, skip);
+        write_varint(&mut out, pos - start);
+        out.extend_from_slice(&target[start..pos]);
+    }
+
+    if out.is_empty() {
+        write_varint(&mut out, 0);
+        write_varint(&mut out, 0);
+    }
+
+    out
+}
+
+/// Rebuilds a snapshot from its keyframe and delta.
+///
+fn apply_delta(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let mut out = base.to_vec();
+    let mut offset = 0;
+    let mut pos = 0;
+
+    while pos < delta.len() {
+        offset += read_varint(delta, &mut pos);
+        let len = read_varint(delta, &mut pos);
+
+        out[offset..offset + len].copy_from_slice(&delta[pos..pos + len]);
+
+        offset += len;
+        pos += len;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{TestVm, VmBuilder};
+    use model::{Chip8RegisterBank, Register};
+
+    /// Creates a vm running a loop that increments V0 every cycle.
+    ///
+    fn get_vm() -> TestVm {
+        // 0x200: V0 += 1, 0x202: jump 0x200
+        VmBuilder::new().program(&[0x70, 0x01, 0x12, 0x00]).build()
+    }
+
+    fn v0(vm: &TestVm) -> u8 {
+        vm.registers.get_v(Register::V0).get_raw()
+    }
+
+    #[test]
+    fn rewind_steps_back_through_recorded_states() {
+        let mut vm = get_vm();
+        let mut rewind = Rewind::new(10, 1, 4);
+
+        for _ in 0..6 {
+            vm.execute_cycles(2).unwrap();
+            rewind.record(&vm);
+        }
+        assert_eq!(v0(&vm), 6);
+
+        assert!(rewind.rewind(&mut vm).unwrap());
+        assert_eq!(v0(&vm), 6);
+        assert!(rewind.rewind(&mut vm).unwrap());
+        assert_eq!(v0(&vm), 5);
+        assert!(rewind.rewind(&mut vm).unwrap());
+        assert_eq!(v0(&vm), 4);
+        assert_eq!(rewind.len(), 3);
+    }
+
+    #[test]
+    fn rewind_drops_the_oldest_states() {
+        let mut vm = get_vm();
+        let mut rewind = Rewind::new(3, 1, 2);
+
+        for _ in 0..8 {
+            vm.execute_cycles(2).unwrap();
+            rewind.record(&vm);
+        }
+
+        assert_eq!(rewind.len(), 3);
+        while rewind.rewind(&mut vm).unwrap() {}
+
+        assert_eq!(v0(&vm), 6);
+        assert!(rewind.is_empty());
+    }
+
+    #[test]
+    fn rewind_records_on_the_interval() {
+        let mut vm = get_vm();
+        let mut rewind = Rewind::new(10, 3, 1);
+
+        for _ in 0..7 {
+            vm.execute_cycles(2).unwrap();
+            rewind.record(&vm);
+        }
+
+        assert_eq!(rewind.len(), 2);
+        rewind.rewind(&mut vm).unwrap();
+        assert_eq!(v0(&vm), 6);
+    }
+
+    #[test]
+    fn deltas_use_less_memory_than_keyframes() {
+        let mut vm = get_vm();
+        let mut whole = Rewind::new(10, 1, 1);
+        let mut deltas = Rewind::new(10, 1, 10);
+
+        for _ in 0..10 {
+            vm.execute_cycles(2).unwrap();
+            whole.record(&vm);
+            deltas.record(&vm);
+        }
+
+        assert!(deltas.memory_usage() * 5 < whole.memory_usage());
+    }
+
+    #[test]
+    fn recording_after_a_rewind_continues_from_there() {
+        let mut vm = get_vm();
+        let mut rewind = Rewind::new(10, 1, 4);
+
+        for _ in 0..5 {
+            vm.execute_cycles(2).unwrap();
+            rewind.record(&vm);
+        }
+        rewind.rewind(&mut vm).unwrap();
+        rewind.rewind(&mut vm).unwrap();
+        assert_eq!(v0(&vm), 4);
+
+        vm.execute_cycles(2).unwrap();
+        rewind.record(&vm);
+        rewind.rewind(&mut vm).unwrap();
+        assert_eq!(v0(&vm), 5);
+        rewind.rewind(&mut vm).unwrap();
+        assert_eq!(v0(&vm), 3);
+    }
+
+    #[test]
+    fn the_first_recording_after_a_rewind_is_a_keyframe() {
+        let mut vm = get_vm();
+        let mut rewind = Rewind::new(10, 1, 4);
+
+        for _ in 0..3 {
+            vm.execute_cycles(2).unwrap();
+            rewind.record(&vm);
+        }
+        rewind.rewind(&mut vm).unwrap();
+
+        vm.execute_cycles(2).unwrap();
+        rewind.record(&vm);
+
+        assert!(rewind.entries.back().unwrap().delta.is_empty());
+    }
+
+    #[test]
+    fn dropped_keyframes_are_counted_while_deltas_use_them() {
+        let mut vm = get_vm();
+        let mut rewind = Rewind::new(2, 1, 10);
+
+        for _ in 0..3 {
+            vm.execute_cycles(2).unwrap();
+            rewind.record(&vm);
+        }
+
+        let keyframe = &rewind.entries[0].keyframe;
+        let deltas: usize = rewind.entries.iter().map(|entry| entry.delta.len()).sum();
+
+        assert!(rewind.entries.iter().all(|entry| !entry.delta.is_empty()));
+        assert_eq!(rewind.memory_usage(), keyframe.len() + deltas);
+    }
+
+    #[test]
+    fn delta_round_trips() {
+        let base = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let target = vec![0, 9, 9, 3, 4, 5, 6, 8];
+
+        let delta = encode_delta(&base, &target);
+
+        assert_eq!(apply_delta(&base, &delta), target);
+        assert_eq!(apply_delta(&base, &encode_delta(&base, &base)), base);
+    }
+
+    #[test]
+    fn delta_of_a_long_run_is_small() {
+        let base = vec![0; 5000];
+        let mut target = base.clone();
+        target[4000] = 1;
+
+        let delta = encode_delta(&base, &target);
+
+        assert!(delta.len() < 8);
+        assert_eq!(apply_delta(&base, &delta), target);
+    }
+}
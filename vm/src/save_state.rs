@@ -0,0 +1,369 @@
+//! Versioned binary save states.
+//!
+//! A save state holds everything needed to resume a machine: memory, the
+//! call stack, the registers, the timers, the display, the keyboard and the
+//! vm state. All values are written big endian in the following order.
+//!
+//! ```text
+//! "C8SS"                       magic
+//! u16                          format version
+//! u32, [u8]                    memory size and contents
+//! [u8; 16], u16                V0 - VF and I
+//! u8, [u16]                    call stack depth and addresses
+//! u8, u8                       delay and sound timers
+//! u8, u8, u16, u16, [u8]       high resolution flag, selected planes,
+//!                              width, height and one color per pixel
+//! u16                          pressed keys, bit n for key n
+//! u8, ...                      vm state tag and its payload, the target
+//!                              register and key for WaitingForKey
+//! u16, u16                     last fetched opcode
+//! [u8; 16]                     RPL flags
+//! u8, [u8; 16], u8             audio pattern flag, pattern and pitch, the
+//!                              pattern is all zeros when none was loaded
+//! u32, [u8]                    random source state length and bytes
+//! u64, u64                     instructions executed and frames completed
+//! u32                          machine cycles elapsed in the frame
+//! ```
+//!
+//! Quirks and timing are configuration rather than state and are not saved,
+//! neither is queued input, which belongs to the frontend.
+//!
+
+use super::reader::Reader;
+use super::{VMState, VirtualMachine, AUDIO_PATTERN_SIZE, RPL_FLAGS};
+use data::{Address, Nibble};
+use instruction::Instruction;
+use model::{
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank, Register,
+};
+
+/// Identifies a save state.
+const MAGIC: &[u8; 4] = b"C8SS";
+
+/// The current version of the format. Bump it whenever the layout changes.
+pub const SAVE_STATE_VERSION: u16 = 7;
+
+/// Tags for the vm states.
+const STATE_INITIALIZING: u8 = 0;
+const STATE_LOADING_ROM: u8 = 1;
+const STATE_EXECUTING: u8 = 2;
+const STATE_PAUSED: u8 = 3;
+const STATE_WAITING_FOR_KEY: u8 = 4;
+const STATE_EXITED: u8 = 5;
+
+/// Marks a WaitingForKey state without a key.
+const NO_KEY: u8 = 0xFF;
+
+/// A parsed save state, held until every part of it has been validated.
+///
+struct SaveState {
+    memory: Vec<u8>,
+    v: Vec<u8>,
+    i: u16,
+    stack: Vec<Address>,
+    delay_timer: u8,
+    sound_timer: u8,
+    high_res: bool,
+    planes: u8,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    keys: u16,
+    state: VMState,
+    opcode: (u16, u16),
+    rpl_flags: Vec<u8>,
+    audio_pattern: Option<Vec<u8>>,
+    pitch: u8,
+    random: Vec<u8>,
+    cycles: u64,
+    frames: u64,
+    frame_cycles: u32,
+}
+
+/// Creates a CorruptSaveState error.
+///
+fn corrupt(reason: &str) -> Chip8Error {
+    Chip8Error::CorruptSaveState(reason.into())
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> VirtualMachine<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Serializes the machine into the save state format.
+    ///
+    pub(crate) fn write_state(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&SAVE_STATE_VERSION.to_be_bytes());
+
+        let size = self.memory.size();
+        out.extend_from_slice(&(size as u32).to_be_bytes());
+        for addr in 0..size {
+            // Every address below size is readable.
+            let byte = self
+                .memory
+                .get(Address::new(addr as u16))
+                .unwrap_or_default();
+            out.push(byte.get_raw());
+        }
+
+        for reg in Register::iter_to(Register::VF) {
+            out.push(self.registers.get_v(reg).get_raw());
+        }
+        out.extend_from_slice(&self.registers.get_i().get_raw().to_be_bytes());
+
+        let stack = self.pc.stack();
+        out.push(stack.len() as u8);
+        for addr in stack {
+            out.extend_from_slice(&addr.get_raw().to_be_bytes());
+        }
+
+        out.push(self.delay_timer);
+        out.push(self.sound_timer);
+
+        let fb = &self.framebuffer;
+        out.push(fb.is_high_res() as u8);
+        out.push(fb.selected_planes());
+        out.extend_from_slice(&(fb.width() as u16).to_be_bytes());
+        out.extend_from_slice(&(fb.height() as u16).to_be_bytes());
+        for y in 0..fb.height() {
+            for x in 0..fb.width() {
+                out.push(fb.get_color(x, y));
+            }
+        }
+
+        let keys = (0..16u8)
+            .filter(|k| self.keyboard.is_pressed(Nibble::new(*k)))
+            .fold(0u16, |keys, k| keys | 1 << k);
+        out.extend_from_slice(&keys.to_be_bytes());
+
+        match self.state {
+            VMState::Initializing => out.push(STATE_INITIALIZING),
+            VMState::LoadingROM => out.push(STATE_LOADING_ROM),
+            VMState::Executing(_) => out.push(STATE_EXECUTING),
+            VMState::Paused => out.push(STATE_PAUSED),
+            VMState::WaitingForKey(vx, key) => {
+                out.push(STATE_WAITING_FOR_KEY);
+                out.push(vx as u8);
+                out.push(key.map_or(NO_KEY, |k| k.get_raw()));
+            }
+            VMState::Exited => out.push(STATE_EXITED),
+        }
+        out.extend_from_slice(&self.opcode.0.to_be_bytes());
+        out.extend_from_slice(&self.opcode.1.to_be_bytes());
+
+        out.extend(self.rpl_flags.iter().map(|b| b.get_raw()));
+        out.push(self.audio_pattern.is_some() as u8);
+        let pattern = self.audio_pattern.unwrap_or([0.into(); AUDIO_PATTERN_SIZE]);
+        out.extend(pattern.iter().map(|b| b.get_raw()));
+        out.push(self.pitch.get_raw());
+
+        let random = self.random.state();
+        out.extend_from_slice(&(random.len() as u32).to_be_bytes());
+        out.extend_from_slice(&random);
+
+        out.extend_from_slice(&self.cycles.to_be_bytes());
+        out.extend_from_slice(&self.frames.to_be_bytes());
+        out.extend_from_slice(&self.frame_cycles.to_be_bytes());
+
+        out
+    }
+
+    /// Parses a save state and checks it against this machine.
+    ///
+    fn parse_state(&self, data: &[u8]) -> Result<SaveState, Chip8Error> {
+        let mut r = Reader::new(data, Chip8Error::CorruptSaveState);
+
+        if r.bytes(MAGIC.len())? != MAGIC {
+            return Err(corrupt("not a save state"));
+        }
+
+        let version = r.u16()?;
+        if version != SAVE_STATE_VERSION {
+            return Err(Chip8Error::IncompatibleSaveState(version));
+        }
+
+        let size = r.u32()? as usize;
+        if size != self.memory.size() {
+            return Err(corrupt("memory size does not match"));
+        }
+        let memory = r.bytes(size)?.to_vec();
+
+        let v = r.bytes(16)?.to_vec();
+        let i = r.u16()?;
+
+        let depth = r.u8()? as usize;
+        let mut stack = Vec::with_capacity(depth);
+        for _ in 0..depth {
+            stack.push(Address::new(r.u16()?));
+        }
+
+        let delay_timer = r.u8()?;
+        let sound_timer = r.u8()?;
+
+        let high_res = r.u8()? != 0;
+        let planes = r.u8()?;
+        let width = r.u16()? as usize;
+        let height = r.u16()? as usize;
+
+        // Switching resolution doubles or halves both dimensions.
+        let fb = &self.framebuffer;
+        let expected = match (fb.is_high_res(), high_res) {
+            (current, saved) if current == saved => (fb.width(), fb.height()),
+            (false, true) => (fb.width() * 2, fb.height() * 2),
+            _ => (fb.width() / 2, fb.height() / 2),
+        };
+        if expected != (width, height) {
+            return Err(corrupt("display size does not match"));
+        }
+        let pixels = r.bytes(width * height)?.to_vec();
+
+        let keys = r.u16()?;
+
+        let tag = r.u8()?;
+        let (vx, key) = if tag == STATE_WAITING_FOR_KEY {
+            (r.u8()?, r.u8()?)
+        } else {
+            (0, NO_KEY)
+        };
+        if vx > 0xF || (key > 0xF && key != NO_KEY) {
+            return Err(corrupt("invalid key wait"));
+        }
+        let vx = Register::from_nibble(vx);
+        let opcode = (r.u16()?, r.u16()?);
+
+        let state = match tag {
+            STATE_INITIALIZING => VMState::Initializing,
+            STATE_LOADING_ROM => VMState::LoadingROM,
+            STATE_EXECUTING => VMState::Executing(Instruction::from_words(opcode.0, opcode.1)),
+            STATE_PAUSED => VMState::Paused,
+            STATE_WAITING_FOR_KEY if key == NO_KEY => VMState::WaitingForKey(vx, None),
+            STATE_WAITING_FOR_KEY => VMState::WaitingForKey(vx, Some(Nibble::new(key))),
+            STATE_EXITED => VMState::Exited,
+            _ => return Err(corrupt("unknown vm state")),
+        };
+
+        let rpl_flags = r.bytes(RPL_FLAGS)?.to_vec();
+        let has_pattern = r.u8()? != 0;
+        let pattern = r.bytes(AUDIO_PATTERN_SIZE)?.to_vec();
+        let audio_pattern = if has_pattern { Some(pattern) } else { None };
+        let pitch = r.u8()?;
+
+        let len = r.u32()? as usize;
+        let random = r.bytes(len)?.to_vec();
+
+        let cycles = r.u64()?;
+        let frames = r.u64()?;
+        let frame_cycles = r.u32()?;
+
+        if !r.is_at_end() {
+            return Err(corrupt("trailing data"));
+        }
+
+        Ok(SaveState {
+            memory,
+            v,
+            i,
+            stack,
+            delay_timer,
+            sound_timer,
+            high_res,
+            planes,
+            width,
+            height,
+            pixels,
+            keys,
+            state,
+            opcode,
+            rpl_flags,
+            audio_pattern,
+            pitch,
+            random,
+            cycles,
+            frames,
+            frame_cycles,
+        })
+    }
+
+    /// Restores the machine from the save state format.
+    ///
+    /// Nothing is changed unless the whole save state is valid.
+    ///
+    pub(crate) fn read_state(&mut self, data: &[u8]) -> Result<(), Chip8Error> {
+        let saved = self.parse_state(data)?;
+
+        // The random source and the stack are the only parts a component can
+        // still refuse, so they go first.
+        let random = self.random.state();
+        self.random.set_state(&saved.random)?;
+
+        if let Err(e) = self.pc.restore_stack(&saved.stack) {
+            self.random.set_state(&random)?;
+
+            return Err(e);
+        }
+
+        for (addr, byte) in saved.memory.iter().enumerate() {
+            self.memory.set(Address::new(addr as u16), (*byte).into())?;
+        }
+        self.decoded.clear();
+        self.blocks.clear();
+
+        for (reg, byte) in Register::iter_to(Register::VF).zip(saved.v.iter()) {
+            self.registers.set_v(reg, (*byte).into());
+        }
+        self.registers.set_i(Address::new(saved.i));
+
+        self.delay_timer = saved.delay_timer;
+        self.sound_timer = saved.sound_timer;
+        self.update_audio();
+
+        self.framebuffer.set_high_res(saved.high_res);
+        for y in 0..saved.height {
+            for x in 0..saved.width {
+                let color = saved.pixels[y * saved.width + x];
+                self.framebuffer.set_color(x, y, color);
+            }
+        }
+        self.framebuffer.select_planes(saved.planes);
+
+        self.keyboard.clear();
+        for key in 0..16u8 {
+            if saved.keys & (1 << key) != 0 {
+                self.keyboard.press(Nibble::new(key));
+            }
+        }
+
+        self.state = saved.state;
+        self.opcode = saved.opcode;
+
+        for (flag, byte) in self.rpl_flags.iter_mut().zip(saved.rpl_flags.iter()) {
+            *flag = (*byte).into();
+        }
+        self.audio_pattern = saved.audio_pattern.map(|bytes| {
+            let mut pattern = [0.into(); AUDIO_PATTERN_SIZE];
+            for (sample, byte) in pattern.iter_mut().zip(bytes.iter()) {
+                *sample = (*byte).into();
+            }
+
+            pattern
+        });
+        self.pitch = saved.pitch.into();
+        self.update_pattern();
+
+        self.cycles = saved.cycles;
+        self.frames = saved.frames;
+        self.frame_cycles = saved.frame_cycles;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Builds the machines the tests run on.
+//!
+//! Every test machine has a 32 row framebuffer and the program counter at
+//! 0x200. Only the memory size, the quirks, the program, the random source
+//! and the audio differ between tests.
+//!
+
+use super::{Quirks, VirtualMachine};
+use framebuffer::FrameBuffer;
+use keyboard::Keyboard;
+use memory::Memory;
+use model::{Chip8Audio, Chip8Random, Chip8VirtualMachine};
+use program_counter::ProgramCounter;
+use random::ScriptedRandom;
+use register_bank::RegisterBank;
+
+/// A machine built from the default components.
+///
+pub(crate) type TestVm<RNG = ScriptedRandom, AU = ()> =
+    VirtualMachine<Memory, ProgramCounter, RegisterBank, FrameBuffer, Keyboard, RNG, AU>;
+
+/// Builds a `TestVm`.
+///
+pub(crate) struct VmBuilder<RNG, AU> {
+    memory_size: usize,
+    quirks: Quirks,
+    program: Vec<u8>,
+    random: RNG,
+    audio: AU,
+}
+
+impl VmBuilder<ScriptedRandom, ()> {
+    /// Starts a machine with the default memory and quirks, no program, a
+    /// random source that always returns 0 and no audio.
+    ///
+    pub(crate) fn new() -> Self {
+        VmBuilder {
+            memory_size: memory::DEFAULT_SIZE,
+            quirks: Quirks::default(),
+            program: Vec::new(),
+            random: ScriptedRandom::new(vec![0]),
+            audio: (),
+        }
+    }
+}
+
+impl<RNG, AU> VmBuilder<RNG, AU>
+where
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Sets the number of bytes of memory.
+    ///
+    pub(crate) fn memory_size(mut self, size: usize) -> Self {
+        self.memory_size = size;
+        self
+    }
+
+    /// Sets the quirks.
+    ///
+    pub(crate) fn quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    /// Sets the program loaded at 0x200.
+    ///
+    pub(crate) fn program(mut self, program: &[u8]) -> Self {
+        self.program = program.to_vec();
+        self
+    }
+
+    /// Replaces the random source.
+    ///
+    pub(crate) fn random<R: Chip8Random>(self, random: R) -> VmBuilder<R, AU> {
+        VmBuilder {
+            memory_size: self.memory_size,
+            quirks: self.quirks,
+            program: self.program,
+            random,
+            audio: self.audio,
+        }
+    }
+
+    /// Replaces the audio.
+    ///
+    pub(crate) fn audio<A: Chip8Audio>(self, audio: A) -> VmBuilder<RNG, A> {
+        VmBuilder {
+            memory_size: self.memory_size,
+            quirks: self.quirks,
+            program: self.program,
+            random: self.random,
+            audio,
+        }
+    }
+
+    /// Creates the machine and loads the program.
+    ///
+    pub(crate) fn build(self) -> TestVm<RNG, AU> {
+        let mut vm = VirtualMachine::new(
+            Memory::with_size(self.memory_size),
+            ProgramCounter::new(0x200u16.into()),
+            RegisterBank::new(),
+            FrameBuffer::new(32),
+            Keyboard::new(),
+            self.random,
+            self.audio,
+            self.quirks,
+        );
+
+        vm.load_rom(&self.program).unwrap();
+
+        vm
+    }
+}
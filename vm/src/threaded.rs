@@ -0,0 +1,286 @@
+//! A threaded-code engine.
+//!
+//! Instead of fetching and decoding every instruction as it runs, the
+//! threaded engine compiles the straight-line code at an address into a
+//! block: the decoded instructions, each bound to the handler that performs
+//! it. Running a block walks the handlers without fetching, decoding or
+//! dispatching on the instruction again.
+//!
+//! Blocks end at anything that leaves the straight line (jumps, calls,
+//! returns, skips, `Fx0A` and `00FD`), at `Draw`, and at the instructions that
+//! write memory, so a block never runs past a write to its own code. Writing
+//! to memory covered by a block drops every block.
+//!
+//! The handlers are the interpreter's own for all but the most common
+//! instructions, and every instruction still goes through `retire`, so the
+//! results are the same as the interpreter's. `Lockstep` checks that.
+//!
+
+use super::VirtualMachine;
+use data::Address;
+use instruction::Instruction;
+use model::{
+    Chip8Audio, Chip8Error, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter,
+    Chip8Random, Chip8RegisterBank,
+};
+
+/// The most instructions in a block.
+const MAX_BLOCK_SIZE: usize = 64;
+
+/// Performs an instruction. Returns a flag indicating if the program counter
+/// was updated.
+///
+pub type Handler<VM> = fn(&mut VM, Instruction) -> Result<bool, Chip8Error>;
+
+/// How instructions are executed.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Engine {
+    /// Every instruction is fetched, decoded and interpreted as it runs.
+    #[default]
+    Interpreter,
+
+    /// Straight-line code is compiled into blocks of bound handlers.
+    Threaded,
+}
+
+/// An instruction of a block, bound to its handler.
+///
+struct Op<VM> {
+    address: Address,
+    opcode: (u16, u16),
+    instruction: Instruction,
+    handler: Handler<VM>,
+}
+
+/// Straight-line code starting at an address.
+///
+pub(crate) struct Block<VM> {
+    ops: Vec<Op<VM>>,
+}
+
+/// The compiled blocks, keyed by their first address.
+///
+pub(crate) struct BlockCache<VM> {
+    blocks: Vec<Option<Box<Block<VM>>>>,
+
+    /// Whether a block covers the address.
+    covered: Vec<bool>,
+
+    /// Counts the times the blocks were dropped, so a running block that was
+    /// dropped under it is not put back.
+    generation: u64,
+}
+
+impl<VM> BlockCache<VM> {
+    /// Creates an empty cache for a memory of the given size.
+    ///
+    ///### Arguments
+    ///
+    ///- **size** : The number of addressable bytes.
+    ///
+    pub(crate) fn new(size: usize) -> Self {
+        BlockCache {
+            blocks: (0..size).map(|_| None).collect(),
+            covered: vec![false; size],
+            generation: 0,
+        }
+    }
+
+    /// Drops every block if one of them covers the address.
+    ///
+    ///### Arguments
+    ///
+    ///- **address** : The address that was written.
+    ///
+    pub(crate) fn invalidate(&mut self, address: Address) {
+        let covered = self.covered.get(address.get_raw() as usize);
+
+        if covered.copied().unwrap_or(false) {
+            self.clear();
+        }
+    }
+
+    /// Drops every block.
+    ///
+    pub(crate) fn clear(&mut self) {
+        for block in self.blocks.iter_mut() {
+            *block = None;
+        }
+
+        for covered in self.covered.iter_mut() {
+            *covered = false;
+        }
+
+        self.generation += 1;
+    }
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> VirtualMachine<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Runs the block at the program counter, compiling it first if needed.
+    /// Returns the cycles taken, like `step`.
+    ///
+    /// The block stops early once the budget is spent, or if the program
+    /// counter leaves it.
+    ///
+    ///### Arguments
+    ///
+    ///- **budget** : The cycles left, at least one instruction always runs.
+    ///
+    pub(crate) fn run_block(&mut self, budget: usize) -> Result<Option<u32>, Chip8Error> {
+        if self.is_paused() || self.is_exited() || self.is_waiting() {
+            return self.step();
+        }
+
+        let pc = self.pc.current();
+        let slot = pc.get_raw() as usize;
+
+        // The block is taken out while it runs and put back after, unless the
+        // program wrote over it.
+        let block = match self.blocks.blocks.get_mut(slot).and_then(Option::take) {
+            Some(block) => block,
+            None => self.compile_block(pc)?,
+        };
+        let generation = self.blocks.generation;
+
+        let result = self.run_ops(&block, budget);
+
+        if self.blocks.generation == generation {
+            if let Some(entry) = self.blocks.blocks.get_mut(slot) {
+                *entry = Some(block);
+            }
+        }
+
+        result.map(Some)
+    }
+
+    /// Runs the instructions of the block until the budget is spent or the
+    /// program counter leaves it. Returns the cycles taken.
+    ///
+    fn run_ops(&mut self, block: &Block<Self>, budget: usize) -> Result<u32, Chip8Error> {
+        let mut spent = 0;
+
+        for op in block.ops.iter() {
+            if spent > 0 && (spent as usize >= budget || self.pc.current() != op.address) {
+                break;
+            }
+
+            spent += self.execute_decoded(op.opcode, op.instruction, op.handler)?;
+        }
+
+        Ok(spent)
+    }
+
+    /// Compiles the straight-line code at the address into a block.
+    ///
+    fn compile_block(&mut self, start: Address) -> Result<Box<Block<Self>>, Chip8Error> {
+        let mut ops = Vec::new();
+        let mut address = start;
+
+        loop {
+            // Code that runs off the end of memory fails when it gets there,
+            // the same as in the interpreter.
+            let opcode = match self.fetch_at(address) {
+                Ok(opcode) => opcode,
+                Err(e) if ops.is_empty() => return Err(e),
+                Err(_) => break,
+            };
+
+            let instruction = Instruction::from_words(opcode.0, opcode.1);
+
+            ops.push(Op {
+                address,
+                opcode,
+                instruction,
+                handler: Self::handler(instruction),
+            });
+
+            address += instruction.size().into();
+
+            if instruction.ends_block() || ops.len() == MAX_BLOCK_SIZE {
+                break;
+            }
+        }
+
+        for op in ops.iter() {
+            let first = op.address.get_raw() as usize;
+
+            for covered in self
+                .blocks
+                .covered
+                .iter_mut()
+                .skip(first)
+                .take(op.instruction.size() as usize)
+            {
+                *covered = true;
+            }
+        }
+
+        Ok(Box::new(Block { ops }))
+    }
+
+    /// Returns the handler that performs the instruction.
+    ///
+    fn handler(instruction: Instruction) -> Handler<Self> {
+        use Instruction::*;
+
+        match instruction {
+            Load(..) => Self::load,
+            Add(..) => Self::add,
+            LoadFromReg(..) => Self::load_from_reg,
+            LoadInstr(_) => Self::load_instr,
+            Jump(_) => Self::jump,
+            _ => Self::interpret_instruction,
+        }
+    }
+
+    fn load(&mut self, instruction: Instruction) -> Result<bool, Chip8Error> {
+        if let Instruction::Load(vx, byte) = instruction {
+            self.set_reg(vx, byte);
+        }
+
+        Ok(false)
+    }
+
+    fn add(&mut self, instruction: Instruction) -> Result<bool, Chip8Error> {
+        if let Instruction::Add(vx, byte) = instruction {
+            let contents = self.get_reg(vx);
+            self.set_reg(vx, contents.wrapping_add(byte));
+        }
+
+        Ok(false)
+    }
+
+    fn load_from_reg(&mut self, instruction: Instruction) -> Result<bool, Chip8Error> {
+        if let Instruction::LoadFromReg(vx, vy) = instruction {
+            self.set_reg(vx, self.get_reg(vy));
+        }
+
+        Ok(false)
+    }
+
+    fn load_instr(&mut self, instruction: Instruction) -> Result<bool, Chip8Error> {
+        if let Instruction::LoadInstr(addr) = instruction {
+            self.registers.set_i(addr);
+        }
+
+        Ok(false)
+    }
+
+    fn jump(&mut self, instruction: Instruction) -> Result<bool, Chip8Error> {
+        if let Instruction::Jump(addr) = instruction {
+            self.pc.set(addr);
+        }
+
+        Ok(true)
+    }
+}
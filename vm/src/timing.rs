@@ -0,0 +1,234 @@
+//! Instruction timing.
+//!
+//! By default every instruction takes one cycle. With `Timing::CosmacVip`
+//! each instruction costs about as many machine cycles as the original
+//! interpreter spends on it, and `execute_cycles` is budgeted in machine
+//! cycles instead.
+//!
+//! The VIP runs at 1.76 MHz with 8 clocks per machine cycle, 3668 machine
+//! cycles per 60 Hz frame. Each frame starts with the display interrupt: the
+//! video chip steals 1024 cycles for the DMA of the 128 displayed lines and
+//! the interrupt routine takes about 46 more, leaving the interpreter roughly
+//! 2600 cycles per frame. Sprites are only drawn right after the interrupt,
+//! so `Draw` first waits for the frame to end.
+//!
+//! The costs follow the routines of the VIP interpreter. They are close, not
+//! exact: the time the interpreter takes to fetch and decode an instruction is
+//! the same for all of them, and collisions do not change the cost of `Draw`.
+//!
+
+use super::VirtualMachine;
+use instruction::Instruction;
+use model::{
+    Chip8Audio, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter, Chip8Random,
+    Chip8RegisterBank,
+};
+
+/// The machine cycles in a 60 Hz frame of the COSMAC VIP.
+///
+pub const VIP_FRAME_CYCLES: u32 = 3668;
+
+/// The machine cycles per second of the COSMAC VIP, for `Clock` and
+/// `cycles_for_frame`.
+///
+pub const VIP_CYCLES_PER_SECOND: u64 = VIP_FRAME_CYCLES as u64 * 60;
+
+/// The machine cycles taken by the display interrupt at the start of a frame.
+///
+pub const VIP_INTERRUPT_CYCLES: u32 = 1024 + 46;
+
+/// The machine cycles of fetching and decoding an instruction.
+///
+const FETCH_CYCLES: u32 = 40;
+
+/// The extra machine cycles of a skip that is taken.
+///
+const SKIP_CYCLES: u32 = 4;
+
+/// The machine cycles of one check for the key of `Fx0A`.
+///
+pub(crate) const KEY_POLL_CYCLES: u32 = 18;
+
+/// How long instructions take.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Timing {
+    /// Every instruction takes one cycle.
+    #[default]
+    Instructions,
+
+    /// Instructions take the machine cycles of the COSMAC VIP.
+    CosmacVip,
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> VirtualMachine<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Returns the machine cycles elapsed in the current frame.
+    ///
+    /// Only counted with `Timing::CosmacVip`. A frame starts with the cost
+    /// of the display interrupt and what the last frame ran over.
+    ///
+    pub fn frame_cycles(&self) -> u32 {
+        self.frame_cycles
+    }
+
+    /// Returns the machine cycles the instruction takes on the VIP, before
+    /// it is executed.
+    ///
+    /// Taken skips add `SKIP_CYCLES` once it is known they were taken.
+    ///
+    ///### Arguments
+    ///
+    ///- **instruction** : The instruction about to be executed.
+    ///
+    pub(crate) fn vip_cycles(&self, instruction: Instruction) -> u32 {
+        use Instruction::*;
+
+        let execute = match instruction {
+            // Clears the 256 bytes of the display page.
+            Cls => 24 + 256 * 12,
+            Return => 10,
+            Jump(_) => 12,
+            Call(_) => 26,
+            JumpPlus(_) => 22,
+            SkipEqualByte(..) | SkipNotEqualByte(..) => 10,
+            SkipEqualReg(..) | SkipNotEqualReg(..) => 14,
+            SkipPressed(_) | SkipNotPressed(_) => 14,
+            Load(..) => 6,
+            Add(..) => 10,
+            LoadFromReg(..) | Or(..) | And(..) | XOr(..) | AddReg(..) | SubReg(..)
+            | ShiftRight(..) | ReverseSub(..) | ShiftLeft(..) => 44,
+            LoadInstr(_) => 12,
+            Rand(..) => 36,
+            Draw(vx, _, n) => {
+                // Sprites that are not byte aligned are shifted across two
+                // bytes of the display, row by row.
+                let x = self.registers.get_v(vx).get_raw();
+                let row = if x % 8 == 0 { 34 } else { 46 };
+
+                let wait = VIP_FRAME_CYCLES.saturating_sub(self.frame_cycles);
+
+                wait + 26 + n.get_raw() as u32 * row
+            }
+            LoadDelayTimer(_) | SetDelayTimer(_) | SetSoundTimer(_) => 10,
+            WaitForKey(_) => KEY_POLL_CYCLES,
+            IncrementAddress(_) => 12,
+            LoadSpriteAddress(_) => 16,
+            LoadBCD(vx) => {
+                // Each digit is found by repeated subtraction.
+                let (h, t, o) = self.registers.get_v(vx).get_bcd_rep();
+
+                80 + 16 * (h + t + o) as u32
+            }
+            CopyToRam(vx) | CopyToRegisters(vx) => 14 + 14 * (vx as u32 + 1),
+
+            // Not part of the VIP interpreter.
+            _ => 10,
+        };
+
+        FETCH_CYCLES + execute
+    }
+
+    /// Returns the extra machine cycles of a skip that was taken.
+    ///
+    pub(crate) fn vip_skip_cycles(instruction: Instruction) -> u32 {
+        use Instruction::*;
+
+        match instruction {
+            SkipEqualByte(..) | SkipNotEqualByte(..) | SkipEqualReg(..) | SkipNotEqualReg(..)
+            | SkipPressed(_) | SkipNotPressed(_) => SKIP_CYCLES,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{TestVm, VmBuilder};
+    use super::super::Quirks;
+    use super::*;
+    use data::Nibble;
+    use model::{Chip8VirtualMachine, Register};
+
+    fn get_vm(program: &[u8]) -> TestVm {
+        let mut vm = VmBuilder::new()
+            .quirks(Quirks::cosmac_vip())
+            .program(program)
+            .build();
+
+        vm.timing = Timing::CosmacVip;
+
+        vm
+    }
+
+    #[test]
+    fn execute_cycles_is_budgeted_in_machine_cycles() {
+        // V0 = 1, V1 = 2, V2 = 3, V3 = 4
+        let mut vm = get_vm(&[0x60, 0x01, 0x61, 0x02, 0x62, 0x03, 0x63, 0x04]);
+
+        vm.execute_cycles(100).unwrap();
+
+        assert_eq!(vm.cycles(), 3);
+        assert_eq!(vm.frame_cycles(), 3 * 46);
+    }
+
+    #[test]
+    fn taken_skips_cost_more() {
+        // skip if V0 == 1, skip if V0 == 0
+        let mut vm = get_vm(&[0x30, 0x01, 0x30, 0x00]);
+
+        vm.execute().unwrap();
+        assert_eq!(vm.frame_cycles(), 50);
+
+        vm.execute().unwrap();
+        assert_eq!(vm.frame_cycles(), 50 + 54);
+    }
+
+    #[test]
+    fn the_display_interrupt_shortens_later_frames() {
+        // loop: V0 += 1, jump loop
+        let mut vm = get_vm(&[0x70, 0x01, 0x12, 0x00]);
+
+        vm.run_frame(VIP_FRAME_CYCLES as usize).unwrap();
+        let first = vm.cycles();
+
+        vm.run_frame(VIP_FRAME_CYCLES as usize).unwrap();
+        let second = vm.cycles() - first;
+
+        assert!(second < first);
+    }
+
+    #[test]
+    fn draw_waits_for_the_display_interrupt() {
+        // draw the 0 at V0, V1; V2 += 1, jump back
+        let mut vm = get_vm(&[0xD0, 0x15, 0x72, 0x01, 0x12, 0x02]);
+
+        vm.run_frame(VIP_FRAME_CYCLES as usize).unwrap();
+
+        assert_eq!(vm.cycles(), 1);
+        assert_eq!(vm.registers.get_v(Register::V2), 0.into());
+        assert_eq!(vm.frame_cycles(), VIP_INTERRUPT_CYCLES + 40 + 26 + 5 * 34);
+    }
+
+    #[test]
+    fn draw_costs_depend_on_the_height_and_alignment() {
+        let mut vm = get_vm(&[]);
+        vm.frame_cycles = VIP_FRAME_CYCLES;
+
+        let draw = |n: u8| Instruction::Draw(Register::V0, Register::V1, Nibble::new(n));
+
+        assert_eq!(vm.vip_cycles(draw(5)), 40 + 26 + 5 * 34);
+        assert_eq!(vm.vip_cycles(draw(10)), 40 + 26 + 10 * 34);
+
+        vm.registers.set_v(Register::V0, 3.into());
+        assert_eq!(vm.vip_cycles(draw(5)), 40 + 26 + 5 * 46);
+    }
+}
@@ -0,0 +1,404 @@
+//! Per instruction execution traces.
+//!
+//! A tracer set with `VirtualMachine::set_tracer` sees the state of the
+//! machine right before every instruction is executed. Written out, each
+//! instruction is one line of fixed width columns followed by the
+//! disassembly, so traces from two runs can be compared line by line.
+//!
+//! ```text
+//! 00000012 PC:0206 OP:D015 V:0A 14 00 ... 00 I:0208 SP:00 DT:00 ST:00 DRW V0, V1, 5
+//! ```
+//!
+//! The columns are the cycle number in decimal, then in hex the program
+//! counter, the opcode, V0 to VF, I, the depth of the call stack (0 in the
+//! main routine) and the delay and sound timers.
+//!
+//! Cycles spent waiting for a key are counted but not traced.
+//!
+
+use super::VirtualMachine;
+use instruction::Instruction;
+use model::{
+    Chip8Audio, Chip8FrameBuffer, Chip8Keyboard, Chip8Memory, Chip8ProgramCounter, Chip8Random,
+    Chip8RegisterBank, Register,
+};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+
+/// The state of the machine before an instruction is executed.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceEntry {
+    pub cycle: u64,
+    pub pc: u16,
+
+    /// The first word of the instruction.
+    pub opcode: u16,
+    pub instruction: Instruction,
+    pub v: [u8; 16],
+    pub i: u16,
+
+    /// The number of subroutines the machine is in.
+    pub sp: u8,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08} PC:{:04X} OP:{:04X} V:",
+            self.cycle, self.pc, self.opcode
+        )?;
+
+        for (n, v) in self.v.iter().enumerate() {
+            if n > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:02X}", v)?;
+        }
+
+        write!(
+            f,
+            " I:{:04X} SP:{:02X} DT:{:02X} ST:{:02X} {}",
+            self.i, self.sp, self.delay_timer, self.sound_timer, self.instruction
+        )
+    }
+}
+
+/// Where the entries go.
+///
+enum Sink {
+    Writer(Box<dyn Write + Send>),
+    Callback(Box<dyn FnMut(&TraceEntry) + Send>),
+}
+
+/// Receives a `TraceEntry` for every executed instruction.
+///
+pub struct Tracer {
+    sink: Sink,
+
+    /// The first error writing the trace, after which nothing is written.
+    error: Option<io::Error>,
+}
+
+impl Tracer {
+    /// Creates a Tracer that writes a line per instruction.
+    ///
+    ///### Arguments
+    ///
+    ///- **writer** : Where the lines are written to.
+    ///
+    pub fn to_writer<W: Write + Send + 'static>(writer: W) -> Self {
+        Tracer {
+            sink: Sink::Writer(Box::new(writer)),
+            error: None,
+        }
+    }
+
+    /// Creates a Tracer that writes a line per instruction to a new file.
+    ///
+    ///### Arguments
+    ///
+    ///- **path** : The file, it is replaced if it exists.
+    ///
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::create(path)?;
+
+        Ok(Tracer::to_writer(BufWriter::new(file)))
+    }
+
+    /// Creates a Tracer that calls back for every instruction.
+    ///
+    ///### Arguments
+    ///
+    ///- **callback** : Called with the state before the instruction is executed.
+    ///
+    pub fn callback<F: FnMut(&TraceEntry) + Send + 'static>(callback: F) -> Self {
+        Tracer {
+            sink: Sink::Callback(Box::new(callback)),
+            error: None,
+        }
+    }
+
+    /// Passes the entry on.
+    ///
+    pub(crate) fn record(&mut self, entry: &TraceEntry) {
+        match &mut self.sink {
+            Sink::Writer(writer) => {
+                if self.error.is_none() {
+                    if let Err(e) = writeln!(writer, "{}", entry) {
+                        self.error = Some(e);
+                    }
+                }
+            }
+            Sink::Callback(callback) => callback(entry),
+        }
+    }
+
+    /// Flushes the trace.
+    ///
+    ///### Returns
+    ///
+    /// The first error writing the trace, if there was one.
+    ///
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+
+        match &mut self.sink {
+            Sink::Writer(writer) => writer.flush(),
+            Sink::Callback(_) => Ok(()),
+        }
+    }
+}
+
+impl<M, PC, R, FB, KB, RNG, AU> VirtualMachine<M, PC, R, FB, KB, RNG, AU>
+where
+    M: Chip8Memory,
+    PC: Chip8ProgramCounter,
+    R: Chip8RegisterBank,
+    FB: Chip8FrameBuffer,
+    KB: Chip8Keyboard,
+    RNG: Chip8Random,
+    AU: Chip8Audio,
+{
+    /// Sets the tracer that sees every executed instruction.
+    ///
+    ///### Arguments
+    ///
+    ///- **tracer** : The tracer, `None` turns tracing off.
+    ///
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+    }
+
+    /// Removes the tracer and returns it, so it can be finished.
+    ///
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    /// Passes the state before the instruction to the tracer, if there is
+    /// one.
+    ///
+    pub(crate) fn trace(&mut self, instruction: Instruction) {
+        if self.tracer.is_none() {
+            return;
+        }
+
+        let mut v = [0; 16];
+        for (n, value) in v.iter_mut().enumerate() {
+            *value = self
+                .registers
+                .get_v(Register::from_nibble(n as u8))
+                .get_raw();
+        }
+
+        let entry = TraceEntry {
+            cycle: self.cycles,
+            pc: self.pc.current().get_raw(),
+            opcode: self.opcode.0,
+            instruction,
+            v,
+            i: self.registers.get_i().get_raw(),
+            sp: self.pc.depth().saturating_sub(1) as u8,
+            delay_timer: self.delay_timer,
+            sound_timer: self.sound_timer,
+        };
+
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record(&entry);
+        }
+    }
+}
+
+/// The first line two traces differ in.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// The line number, counting from 1.
+    pub line: usize,
+
+    /// The lines of either trace, `None` where that trace already ended.
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl Divergence {
+    /// Returns the index of the first character the lines differ in.
+    ///
+    pub fn column(&self) -> usize {
+        let left = self.left.as_deref().unwrap_or("");
+        let right = self.right.as_deref().unwrap_or("");
+
+        left.chars()
+            .zip(right.chars())
+            .take_while(|(l, r)| l == r)
+            .count()
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |line: &Option<String>| match line {
+            Some(line) => line.clone(),
+            None => "<end of trace>".to_string(),
+        };
+
+        writeln!(f, "traces diverge at line {}", self.line)?;
+        writeln!(f, "< {}", show(&self.left))?;
+        writeln!(f, "> {}", show(&self.right))?;
+        write!(f, "  {}^", " ".repeat(self.column()))
+    }
+}
+
+/// Finds the first line two traces differ in.
+///
+/// Trailing whitespace is ignored, so traces written with different line
+/// endings compare equal.
+///
+///### Arguments
+///
+///- **left**  : The first trace.
+///- **right** : The second trace.
+///
+///### Returns
+///
+/// `None` if the traces are the same.
+///
+pub fn diff_traces<A: BufRead, B: BufRead>(left: A, right: B) -> io::Result<Option<Divergence>> {
+    let mut left = left.lines();
+    let mut right = right.lines();
+    let mut line = 0;
+
+    loop {
+        line += 1;
+
+        let l = left.next().transpose()?;
+        let r = right.next().transpose()?;
+
+        match (&l, &r) {
+            (None, None) => return Ok(None),
+            (Some(a), Some(b)) if a.trim_end() == b.trim_end() => continue,
+            _ => {
+                return Ok(Some(Divergence {
+                    line,
+                    left: l,
+                    right: r,
+                }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::{TestVm, VmBuilder};
+    use super::super::Quirks;
+    use super::*;
+    use model::Chip8VirtualMachine;
+    use std::sync::{Arc, Mutex};
+
+    fn get_vm(program: &[u8]) -> TestVm {
+        VmBuilder::new()
+            .quirks(Quirks::cosmac_vip())
+            .program(program)
+            .build()
+    }
+
+    /// 200: LD V0, 0x2A, 202: CALL 206, 204: JP 204, 206: LD I, 0x123, 208: RET
+    const PROGRAM: [u8; 10] = [0x60, 0x2A, 0x22, 0x06, 0x12, 0x04, 0xA1, 0x23, 0x00, 0xEE];
+
+    /// A writer the test can still read after handing it to the tracer.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn callback_sees_the_state_before_every_instruction() {
+        let entries = Arc::new(Mutex::new(Vec::new()));
+        let seen = entries.clone();
+
+        let mut vm = get_vm(&PROGRAM);
+        vm.set_tracer(Some(Tracer::callback(move |entry| {
+            seen.lock().unwrap().push(*entry)
+        })));
+        vm.execute_cycles(5).unwrap();
+
+        let entries = entries.lock().unwrap();
+        let pcs: Vec<u16> = entries.iter().map(|e| e.pc).collect();
+        assert_eq!(pcs, vec![0x200, 0x202, 0x206, 0x208, 0x204]);
+
+        assert_eq!(entries[0].v[0], 0);
+        assert_eq!(entries[1].v[0], 0x2A);
+        assert_eq!(entries[2].cycle, 2);
+        assert_eq!(entries[2].sp, 1);
+        assert_eq!(entries[3].i, 0x123);
+        assert_eq!(entries[3].opcode, 0x00EE);
+    }
+
+    #[test]
+    fn writer_gets_one_line_per_instruction() {
+        let out = Shared::default();
+
+        let mut vm = get_vm(&PROGRAM);
+        vm.set_tracer(Some(Tracer::to_writer(out.clone())));
+        vm.execute_cycles(3).unwrap();
+        vm.take_tracer().unwrap().finish().unwrap();
+
+        let text = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "00000001 PC:0202 OP:2206 \
+             V:2A 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 \
+             I:0000 SP:00 DT:00 ST:00 CALL 0x206"
+        );
+    }
+
+    #[test]
+    fn diff_finds_the_first_divergent_line() {
+        let left = "a 1\nb 2\nc 3\n";
+        let right = "a 1\nb 4\nc 3\n";
+
+        let divergence = diff_traces(left.as_bytes(), right.as_bytes())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(divergence.line, 2);
+        assert_eq!(divergence.column(), 2);
+        assert_eq!(
+            divergence.to_string(),
+            "traces diverge at line 2\n< b 2\n> b 4\n    ^"
+        );
+    }
+
+    #[test]
+    fn diff_reports_a_shorter_trace_and_ignores_line_endings() {
+        let divergence = diff_traces("a\r\nb\r\n".as_bytes(), "a\nb\nc\n".as_bytes())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(divergence.line, 3);
+        assert_eq!(divergence.left, None);
+        assert_eq!(divergence.right, Some("c".to_string()));
+
+        assert_eq!(diff_traces("a\n".as_bytes(), "a".as_bytes()).unwrap(), None);
+    }
+}
@@ -1,13 +1,17 @@
 use data::Nibble;
 use instruction::Instruction;
+use model::Register;
 
 /// Possible Virtual Machine States.
 ///
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum VMState {
     Initializing,
     LoadingROM,
     Executing(Instruction),
     Paused,
-    WaitingForKey(Option<Nibble>),
+    /// Fx0A is waiting for a key to be pressed and released. Holds the
+    /// register the key goes into and the key once it has been pressed.
+    WaitingForKey(Register, Option<Nibble>),
+    Exited,
 }